@@ -7,7 +7,7 @@ use std::{
 use futures_util::StreamExt;
 use http::StatusCode;
 use predawn::{
-    SecurityScheme, Tag, ToParameters, ToSchema,
+    Example, SecurityScheme, Tag, ToParameters, ToSchema,
     any_map::AnyMap,
     app::{Hooks, run_app},
     config::{Config, logger::LoggerConfig},
@@ -16,12 +16,13 @@ use predawn::{
     extract::{
         Path, Query,
         multipart::{JsonField, Multipart, Upload},
-        websocket::{Message, WebSocketRequest, WebSocketResponse},
+        websocket::{Message, TypedWebSocketRequest, WebSocketRequest, WebSocketResponse},
     },
     handler::{Handler, HandlerExt},
     middleware::{TowerLayerCompatExt, Tracing},
     openapi::{self, SecurityRequirement},
     payload::{Form, Json},
+    pubsub::Hub,
     response::{Download, sse::EventStream},
     response_error::ResponseError,
     route::Router,
@@ -111,7 +112,9 @@ async fn main() {
 
 #[derive(Clone)]
 #[Singleton]
-pub struct MyController {}
+pub struct MyController {
+    hub: Hub,
+}
 
 /// This is a controller.
 #[derive(Tag)]
@@ -130,7 +133,7 @@ struct MyScheme1;
 #[http(scheme = basic)]
 struct MyScheme2;
 
-#[controller(tags = [Controller])]
+#[controller(tags = [Controller], client)]
 impl MyController {
     /// no argument, no return
     ///
@@ -142,12 +145,13 @@ impl MyController {
     #[endpoint(paths = ["/no_arg"], methods = [GET], security = [{}, { MyScheme2: [] }])] // override the global security
     async fn no_arg(&self) {}
 
-    #[endpoint(methods = [POST, PUT], middleware = add_middlewares, tags = [Hello])]
+    // `timeout` overrides `server.request_timeout`
+    #[endpoint(methods = [POST, PUT], middleware = add_middlewares, timeout = "60s", tags = [Hello])]
     async fn hello(&self, name: String) -> Result<String, MyError> {
         Ok(format!("hello, {}", name))
     }
 
-    #[endpoint(paths = ["/json"], methods = [POST], security = [{ MyScheme2: ["read", "write"] }])]
+    #[endpoint(paths = ["/json"], methods = [POST], security = [{ MyScheme2: ["read", "write"] }], examples = [Alice])]
     async fn json_person(
         &self,
         /// The person to be updated.
@@ -179,7 +183,7 @@ impl MyController {
         Json(person)
     }
 
-    #[endpoint(paths = ["/multipart"], methods = [POST])]
+    #[endpoint(paths = ["/multipart"], methods = [POST], client = false)]
     async fn multipart_person(&self, m: MultipartStruct) -> Json<Person> {
         let MultipartStruct {
             person: JsonField(person),
@@ -220,7 +224,14 @@ impl MyController {
         Download::attachment(bytes, "test.json").unwrap()
     }
 
-    #[endpoint(paths = ["/unit_enum"], methods = [GET])]
+    #[endpoint(
+        paths = ["/unit_enum"],
+        methods = [GET],
+        operation_id = "unitEnum",
+        deprecated = "Sun, 01 Jun 2025 00:00:00 GMT",
+        sunset = "Wed, 31 Dec 2025 23:59:59 GMT",
+        external_docs = "https://example.com/docs/unit_enum"
+    )]
     async fn unit_enum(&self) -> Json<UnitEnum> {
         Json(UnitEnum::A)
     }
@@ -230,7 +241,7 @@ impl MyController {
         Json(UnitWithDescription::A)
     }
 
-    #[endpoint(paths = ["/complex_enum"], methods = [GET])]
+    #[endpoint(paths = ["/complex_enum"], methods = [GET], deprecated)]
     async fn complex_enum(&self) -> Json<ComplexEnum> {
         Json(ComplexEnum::A)
     }
@@ -243,7 +254,7 @@ impl MyController {
         })
     }
 
-    #[endpoint(paths = ["/websocket"], methods = [GET, CONNECT])]
+    #[endpoint(paths = ["/websocket"], methods = [GET, CONNECT], client = false)]
     async fn websocket(&self, ws: WebSocketRequest) -> WebSocketResponse {
         ws.on_upgrade(|mut socket| async move {
             loop {
@@ -260,7 +271,35 @@ impl MyController {
         })
     }
 
-    #[endpoint(paths = ["/event_stream"], methods = [GET])]
+    #[endpoint(paths = ["/typed_websocket"], methods = [GET, CONNECT], client = false)]
+    async fn typed_websocket(
+        &self,
+        ws: TypedWebSocketRequest<Person, Person>,
+    ) -> WebSocketResponse {
+        ws.on_upgrade(|mut socket| async move {
+            while let Some(Ok(mut person)) = socket.recv().await {
+                person.age += 1;
+
+                if let Err(e) = socket.send(&person).await {
+                    tracing::error!("send error: {}", e);
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Publish a person to the subscribers of `/people/events`
+    #[endpoint(paths = ["/people"], methods = [POST])]
+    async fn publish_person(&self, Json(person): Json<Person>) -> Json<usize> {
+        Json(self.hub.topic("people").publish(person))
+    }
+
+    #[endpoint(paths = ["/people/events"], methods = [GET], client = false)]
+    async fn person_events(&self) -> EventStream<Person> {
+        self.hub.topic("people").subscribe().into_event_stream()
+    }
+
+    #[endpoint(paths = ["/event_stream"], methods = [GET], client = false)]
     async fn event_stream(&self) -> EventStream<Person> {
         EventStream::new(
             #[expect(tail_expr_drop_order)]
@@ -296,14 +335,22 @@ fn CreateMiddleware() -> Tracing {
 }
 
 /// A person.
-#[derive(Debug, Serialize, Deserialize, ToSchema, ToParameters, Multipart)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToParameters, Multipart)]
 struct Person {
     /// The name of the person.
+    #[schema(example = "Bob")]
     name: Option<String>,
     /// The age of the person.
     age: u16,
 }
 
+/// Alice
+///
+/// A person who has just come of age.
+#[derive(Example)]
+#[example(value = Person { name: Some("Alice".into()), age: 18 })]
+struct Alice;
+
 #[derive(Serialize, Deserialize, ToSchema, ToParameters)]
 struct MultiValue {
     /// value
@@ -320,14 +367,14 @@ struct MultipartStruct {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, ToSchema, Serialize)]
+#[derive(Debug, ToSchema, Serialize, Deserialize)]
 enum UnitEnum {
     A,
     B,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, ToSchema, Serialize)]
+#[derive(Debug, ToSchema, Serialize, Deserialize)]
 enum UnitWithDescription {
     /// Hello
     A,
@@ -336,7 +383,7 @@ enum UnitWithDescription {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, ToSchema, Serialize)]
+#[derive(Debug, ToSchema, Serialize, Deserialize)]
 enum ComplexEnum {
     /// Hello
     A,
@@ -391,5 +438,104 @@ mod tests {
         let res = client.post("/").body("world").send().await.unwrap();
         assert_eq!(res.status(), 200);
         assert_eq!(res.text().await.unwrap(), "hello, world");
+
+        let res = client.get("/unit_enum").send().await.unwrap();
+        assert_eq!(res.headers()["deprecation"], "@1748736000");
+        assert_eq!(res.headers()["sunset"], "Wed, 31 Dec 2025 23:59:59 GMT");
+
+        let res = client.get("/complex_enum").send().await.unwrap();
+        assert!(res.headers().get("deprecation").is_none());
+
+        let api = client.openapi();
+
+        let mut events = client.get("/people/events").send().await.unwrap();
+
+        let client = MyControllerClient::new(client.typed_client());
+
+        let Json(delivered) = client
+            .publish_person(Json(Person {
+                name: Some("Carol".into()),
+                age: 30,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(delivered, 1);
+
+        let event = events.chunk().await.unwrap().unwrap();
+        assert_eq!(event, "data: {\"name\":\"Carol\",\"age\":30}\n\n");
+
+        client.no_arg().await.unwrap();
+
+        let hello = client.hello("world".to_string()).await.unwrap();
+        assert_eq!(hello, "hello, world");
+
+        let Json(person) = client
+            .json_person(Json(Person {
+                name: Some("Alice".into()),
+                age: 18,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(person.age, 19);
+
+        let Json(person) = client
+            .path_person(Path(Person {
+                name: Some("Bob Smith".into()),
+                age: 20,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(person.name.as_deref(), Some("Bob Smith"));
+        assert_eq!(person.age, 20);
+
+        let Json(multi_value) = client
+            .query_person(Query(MultiValue { values: vec![1, 2] }))
+            .await
+            .unwrap();
+        assert_eq!(multi_value.values, [1, 2]);
+
+        let Form(multi_value) = client
+            .form_multi_value(Form(MultiValue { values: vec![1] }))
+            .await
+            .unwrap();
+        assert_eq!(multi_value.values, [1, 1]);
+
+        let Json(person) = client.download_from_memory().await.unwrap();
+        assert_eq!(person.name.as_deref(), Some("Alice"));
+
+        assert_eq!(
+            api["paths"]["/json"]["post"]["requestBody"]["content"]["application/json"]["examples"]
+                ["Alice"]["$ref"],
+            "#/components/examples/Alice"
+        );
+        assert_eq!(api["components"]["examples"]["Alice"]["summary"], "Alice");
+        assert_eq!(api["components"]["examples"]["Alice"]["value"]["age"], 18);
+        assert_eq!(
+            api["components"]["schemas"]["hello_world.Person"]["properties"]["name"]["example"],
+            "Bob"
+        );
+
+        let unit_enum = &api["paths"]["/unit_enum"]["get"];
+        assert_eq!(unit_enum["operationId"], "unitEnum");
+        assert_eq!(unit_enum["deprecated"], true);
+        assert_eq!(
+            unit_enum["externalDocs"]["url"],
+            "https://example.com/docs/unit_enum"
+        );
+        assert!(unit_enum["responses"]["200"]["headers"]["sunset"].is_object());
+
+        let complex_enum = &api["paths"]["/complex_enum"]["get"];
+        assert_eq!(complex_enum["deprecated"], true);
+        assert!(complex_enum["responses"]["200"]["headers"]["deprecation"].is_null());
+        assert!(unit_enum["responses"]["504"].is_object());
+        assert!(api["paths"]["/json"]["post"]["responses"]["408"].is_object());
+        assert_eq!(
+            api["paths"]["/typed_websocket"]["get"]["x-websocket-messages"]["inbound"]["$ref"],
+            "#/components/schemas/hello_world.Person"
+        );
+        assert_eq!(
+            api["paths"]["/form"]["get"]["operationId"],
+            "hello_world::MyController::form_person_get"
+        );
     }
 }
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use bytes::Bytes;
 use http::{HeaderMap, Method, Uri, Version};
+use indexmap::IndexMap;
 use predawn_schema::ToSchema;
 
 use crate::{
@@ -17,6 +18,15 @@ pub trait ApiRequestHead {
         schemas: &mut BTreeMap<String, Schema>,
         schemas_in_progress: &mut Vec<String>,
     ) -> Option<Vec<Parameter>>;
+
+    /// [Specification extensions](https://swagger.io/specification/v3/#specification-extensions)
+    /// added to the operation, like `x-websocket-messages`.
+    fn extensions(
+        _: &mut BTreeMap<String, Schema>,
+        _: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        IndexMap::new()
+    }
 }
 
 pub trait ApiRequest<M = ViaRequest> {
@@ -29,6 +39,15 @@ pub trait ApiRequest<M = ViaRequest> {
         schemas: &mut BTreeMap<String, Schema>,
         schemas_in_progress: &mut Vec<String>,
     ) -> Option<openapi::RequestBody>;
+
+    /// [Specification extensions](https://swagger.io/specification/v3/#specification-extensions)
+    /// added to the operation, like `x-websocket-messages`.
+    fn extensions(
+        _: &mut BTreeMap<String, Schema>,
+        _: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        IndexMap::new()
+    }
 }
 
 impl<T> ApiRequest<ViaRequestHead> for T
@@ -48,6 +67,13 @@ where
     ) -> Option<openapi::RequestBody> {
         None
     }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        <T as ApiRequestHead>::extensions(schemas, schemas_in_progress)
+    }
 }
 
 impl ApiRequest for RequestBody {
@@ -131,6 +157,13 @@ macro_rules! optional_parameters {
 
 impl<T: ApiRequestHead> ApiRequestHead for Option<T> {
     optional_parameters!(T);
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        T::extensions(schemas, schemas_in_progress)
+    }
 }
 
 impl<T: ApiRequest> ApiRequest for Option<T> {
@@ -144,6 +177,13 @@ impl<T: ApiRequest> ApiRequest for Option<T> {
         request_body.required = false;
         Some(request_body)
     }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        T::extensions(schemas, schemas_in_progress)
+    }
 }
 
 impl<T, E> ApiRequestHead for Result<T, E>
@@ -156,6 +196,13 @@ where
     ) -> Option<Vec<Parameter>> {
         T::parameters(schemas, schemas_in_progress)
     }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        T::extensions(schemas, schemas_in_progress)
+    }
 }
 
 impl<T, E> ApiRequest for Result<T, E>
@@ -175,4 +222,11 @@ where
     ) -> Option<openapi::RequestBody> {
         T::request_body(schemas, schemas_in_progress)
     }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        T::extensions(schemas, schemas_in_progress)
+    }
 }
@@ -2,19 +2,341 @@ use std::{
     borrow::Cow,
     convert::Infallible,
     pin::Pin,
-    task::{Context, Poll},
+    task::{Context, Poll, ready},
+    time::Duration,
 };
 
-use bytes::{Bytes, BytesMut};
-use futures_core::TryStream;
-use futures_util::TryStreamExt;
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::{TryStream, stream::BoxStream};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use http::{HeaderMap, header::CONTENT_ENCODING};
 use http_body::SizeHint;
 use http_body_util::{BodyExt, Empty, Full, Limited, StreamBody, combinators::UnsyncBoxBody};
 use hyper::body::{Frame, Incoming};
+use snafu::IntoError;
+use tokio::time::{Instant, Sleep};
 
-use crate::error::BoxError;
+use crate::{
+    error::BoxError,
+    request::{BodyTimeout, Head, MinThroughput},
+    response_error::{
+        BodyTimeoutError, LengthLimitSnafu, MinThroughputSnafu, ReadBytesError, ReadTimeoutSnafu,
+        read_bytes_error,
+    },
+};
+
+pub type RequestBody = Limited<TimeoutBody<Incoming>>;
+
+pin_project_lite::pin_project! {
+    /// Fails with a [`BodyTimeoutError`] if the body is received slower than its [`BodyTimeout`] allows.
+    ///
+    /// Only the time spent waiting for the next frame counts, not the time the handler spends
+    /// between reading frames.
+    pub struct TimeoutBody<B> {
+        #[pin]
+        inner: B,
+        #[pin]
+        sleep: Option<Sleep>,
+        timeout: BodyTimeout,
+        waiting_since: Option<Instant>,
+        waited: Duration,
+        received: u64,
+        expiry: Expiry,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Expiry {
+    ReadTimeout,
+    MinThroughput,
+}
+
+impl<B> TimeoutBody<B> {
+    pub fn new(inner: B, timeout: BodyTimeout) -> Self {
+        Self {
+            inner,
+            sleep: None,
+            timeout,
+            waiting_since: None,
+            waited: Duration::ZERO,
+            received: 0,
+            expiry: Expiry::ReadTimeout,
+        }
+    }
+}
+
+impl<B> http_body::Body for TimeoutBody<B>
+where
+    B: http_body::Body,
+    B::Error: Into<BoxError>,
+{
+    type Data = B::Data;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        if this.timeout.is_disabled() {
+            return this.inner.poll_frame(cx).map_err(Into::into);
+        }
+
+        if let Poll::Ready(frame) = this.inner.poll_frame(cx) {
+            if let Some(Ok(frame)) = &frame
+                && let Some(data) = frame.data_ref()
+            {
+                *this.received += data.remaining() as u64;
+            }
+
+            // the next wait starts when the next frame is polled
+            if let Some(waiting_since) = this.waiting_since.take() {
+                *this.waited += waiting_since.elapsed();
+            }
+
+            this.sleep.set(None);
+
+            return Poll::Ready(frame.map(|frame| frame.map_err(Into::into)));
+        }
+
+        if this.sleep.is_none() {
+            let BodyTimeout {
+                read_timeout,
+                min_throughput,
+            } = *this.timeout;
+
+            let now = Instant::now();
+            *this.waiting_since = Some(now);
+
+            let read_deadline = read_timeout.map(|timeout| now + timeout);
+
+            let throughput_deadline = min_throughput.map(
+                |MinThroughput {
+                     bytes_per_second,
+                     grace_period,
+                 }| {
+                    let expected = *this.received as f64 / bytes_per_second.max(1) as f64;
+                    let allowed = grace_period + Duration::from_secs_f64(expected);
+                    now + allowed.saturating_sub(*this.waited)
+                },
+            );
+
+            let (deadline, expiry) = match (read_deadline, throughput_deadline) {
+                (Some(read), Some(throughput)) if throughput < read => {
+                    (throughput, Expiry::MinThroughput)
+                }
+                (Some(read), _) => (read, Expiry::ReadTimeout),
+                (None, Some(throughput)) => (throughput, Expiry::MinThroughput),
+                (None, None) => unreachable!("the timeout is not disabled"),
+            };
+
+            *this.expiry = expiry;
+            this.sleep.set(Some(tokio::time::sleep_until(deadline)));
+        }
+
+        ready!(this.sleep.as_pin_mut().unwrap().poll(cx));
+
+        let err: BodyTimeoutError = match this.expiry {
+            Expiry::ReadTimeout => ReadTimeoutSnafu {
+                timeout: this.timeout.read_timeout.unwrap_or_default(),
+            }
+            .build(),
+            Expiry::MinThroughput => MinThroughputSnafu {
+                bytes_per_second: this
+                    .timeout
+                    .min_throughput
+                    .map_or(0, |min| min.bytes_per_second),
+            }
+            .build(),
+        };
+
+        Poll::Ready(Some(Err(Box::new(err))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Turns the request body into a stream of bytes, decoded according to the `Content-Encoding` header.
+///
+/// The body limit is applied to the decoded bytes. Encodings other than `identity` are only
+/// supported with the `compression` feature enabled.
+pub fn decode_body(
+    head: &Head,
+    body: RequestBody,
+) -> BoxStream<'static, Result<Bytes, ReadBytesError>> {
+    decode(&head.headers, head.body_limit().0, body)
+}
+
+fn decode<B>(
+    headers: &HeaderMap,
+    limit: usize,
+    body: B,
+) -> BoxStream<'static, Result<Bytes, ReadBytesError>>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let mut encodings = Vec::new();
+
+    for value in headers.get_all(CONTENT_ENCODING) {
+        let Ok(value) = value.to_str() else {
+            let err = read_bytes_error::UnsupportedEncodingSnafu {
+                encoding: String::from_utf8_lossy(value.as_bytes()),
+            }
+            .build();
+
+            return stream::once(async move { Err(err) }).boxed();
+        };
+
+        encodings.extend(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|encoding| {
+                    !encoding.is_empty() && !encoding.eq_ignore_ascii_case("identity")
+                })
+                .map(str::to_ascii_lowercase),
+        );
+    }
+
+    if encodings.is_empty() {
+        return body
+            .into_data_stream()
+            .map_err(move |e| read_body_error(e.into(), limit))
+            .boxed();
+    }
+
+    #[cfg(feature = "compression")]
+    {
+        decompress::decompress(body, &encodings, limit)
+    }
 
-pub type RequestBody = Limited<Incoming>;
+    #[cfg(not(feature = "compression"))]
+    {
+        let err = read_bytes_error::UnsupportedEncodingSnafu {
+            encoding: encodings.join(", "),
+        }
+        .build();
+
+        stream::once(async move { Err(err) }).boxed()
+    }
+}
+
+fn read_body_error(err: BoxError, limit: usize) -> ReadBytesError {
+    let err = match err.downcast::<http_body_util::LengthLimitError>() {
+        Ok(_) => return length_limit_error(limit),
+        Err(err) => err,
+    };
+
+    match err.downcast::<BodyTimeoutError>() {
+        Ok(err) => read_bytes_error::TimeoutSnafu.into_error(*err),
+        Err(err) => read_bytes_error::UnknownBodySnafu.into_error(err),
+    }
+}
+
+fn length_limit_error(limit: usize) -> ReadBytesError {
+    let err = LengthLimitSnafu { limit }.build();
+    read_bytes_error::LengthLimitSnafu.into_error(err)
+}
+
+#[cfg(feature = "compression")]
+mod decompress {
+    use std::{error::Error as StdError, fmt, io};
+
+    use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+    use bytes::Bytes;
+    use futures_core::stream::BoxStream;
+    use futures_util::{StreamExt, TryStreamExt, stream};
+    use http_body_util::BodyExt;
+    use snafu::IntoError;
+    use tokio_util::io::{ReaderStream, StreamReader};
+
+    use super::{length_limit_error, read_body_error};
+    use crate::{
+        error::BoxError,
+        response_error::{ReadBytesError, read_bytes_error},
+    };
+
+    /// Marks errors coming from the underlying body, as opposed to errors produced by the decoders.
+    #[derive(Debug)]
+    struct BodyError(BoxError);
+
+    impl fmt::Display for BodyError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(&self.0, f)
+        }
+    }
+
+    impl StdError for BodyError {}
+
+    pub(super) fn decompress<B>(
+        body: B,
+        encodings: &[String],
+        limit: usize,
+    ) -> BoxStream<'static, Result<Bytes, ReadBytesError>>
+    where
+        B: http_body::Body<Data = Bytes> + Send + 'static,
+        B::Error: Into<BoxError>,
+    {
+        let mut stream: BoxStream<'static, io::Result<Bytes>> = body
+            .into_data_stream()
+            .map_err(|e| io::Error::other(BodyError(e.into())))
+            .boxed();
+
+        // encodings are listed in the order they were applied
+        for encoding in encodings.iter().rev() {
+            let reader = StreamReader::new(stream);
+
+            stream = match encoding.as_str() {
+                "gzip" | "x-gzip" => ReaderStream::new(GzipDecoder::new(reader)).boxed(),
+                "deflate" => ReaderStream::new(ZlibDecoder::new(reader)).boxed(),
+                "br" => ReaderStream::new(BrotliDecoder::new(reader)).boxed(),
+                "zstd" => ReaderStream::new(ZstdDecoder::new(reader)).boxed(),
+                _ => {
+                    let err = read_bytes_error::UnsupportedEncodingSnafu {
+                        encoding: encoding.as_str(),
+                    }
+                    .build();
+
+                    return stream::once(async move { Err(err) }).boxed();
+                }
+            };
+        }
+
+        let mut decoded = 0usize;
+
+        stream
+            .map(move |result| match result {
+                Ok(chunk) => {
+                    decoded += chunk.len();
+
+                    if decoded > limit {
+                        Err(length_limit_error(limit))
+                    } else {
+                        Ok(chunk)
+                    }
+                }
+                Err(e) => Err(io_error(e, limit)),
+            })
+            .boxed()
+    }
+
+    fn io_error(err: io::Error, limit: usize) -> ReadBytesError {
+        if err.get_ref().is_some_and(|e| e.is::<BodyError>()) {
+            let BodyError(err) = *err.into_inner().unwrap().downcast().unwrap();
+            return read_body_error(err, limit);
+        }
+
+        read_bytes_error::CorruptStreamSnafu.into_error(err)
+    }
+}
 
 #[derive(Debug)]
 pub struct ResponseBody(UnsyncBoxBody<Bytes, BoxError>);
@@ -26,11 +348,7 @@ impl ResponseBody {
         B::Data: Into<Bytes>,
         B::Error: Into<BoxError>,
     {
-        Self(
-            body.map_frame(|frame| frame.map_data(Into::into))
-                .map_err(Into::into)
-                .boxed_unsync(),
-        )
+        Self(MapData { inner: body }.map_err(Into::into).boxed_unsync())
     }
 
     pub fn empty() -> Self {
@@ -53,6 +371,44 @@ impl ResponseBody {
     }
 }
 
+pin_project_lite::pin_project! {
+    // unlike `MapFrame`, keeps the size hint of the inner body
+    struct MapData<B> {
+        #[pin]
+        inner: B,
+    }
+}
+
+impl<B> http_body::Body for MapData<B>
+where
+    B: http_body::Body,
+    B::Data: Into<Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    #[inline]
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.project()
+            .inner
+            .poll_frame(cx)
+            .map(|frame| frame.map(|frame| frame.map(|frame| frame.map_data(Into::into))))
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
 impl Default for ResponseBody {
     fn default() -> Self {
         Self::empty()
@@ -151,3 +507,141 @@ impl From<Infallible> for ResponseBody {
         match value {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, time::Duration};
+
+    use bytes::Bytes;
+    use futures_util::{StreamExt, TryStreamExt, stream};
+    use http::{HeaderMap, HeaderValue, header::CONTENT_ENCODING};
+    use http_body_util::{BodyExt, Full, Limited, StreamBody};
+    use hyper::body::Frame;
+
+    use super::{TimeoutBody, decode};
+    use crate::{
+        request::{BodyTimeout, MinThroughput},
+        response_error::ReadBytesError,
+    };
+
+    async fn decode_with(
+        content_encoding: &'static str,
+        body: impl Into<Bytes>,
+        limit: usize,
+    ) -> Result<Vec<u8>, ReadBytesError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(content_encoding));
+
+        let body = Limited::new(Full::new(body.into()), limit);
+
+        let chunks = decode(&headers, limit, body)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(chunks.concat())
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_encoding() {
+        let err = decode_with("gzip, unknown", "hello", 1024)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ReadBytesError::UnsupportedEncodingError { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_min_throughput_excludes_handler_time() {
+        let frame =
+            |data: &'static [u8]| Ok::<_, Infallible>(Frame::data(Bytes::from_static(data)));
+
+        let stream = stream::iter([frame(b"hello")]).chain(stream::once(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            frame(b" world")
+        }));
+
+        let timeout = BodyTimeout {
+            read_timeout: None,
+            min_throughput: Some(MinThroughput {
+                bytes_per_second: 1024 * 1024,
+                grace_period: Duration::from_millis(100),
+            }),
+        };
+
+        let mut body = std::pin::pin!(TimeoutBody::new(StreamBody::new(stream), timeout));
+
+        body.frame().await.unwrap().unwrap();
+
+        // the handler is busy longer than the grace period before reading on
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), " world");
+    }
+
+    #[cfg(feature = "compression")]
+    mod compression {
+        use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder};
+        use tokio::io::AsyncReadExt;
+
+        use super::decode_with;
+        use crate::response_error::ReadBytesError;
+
+        async fn gzip(data: &[u8]) -> Vec<u8> {
+            let mut buf = Vec::new();
+            GzipEncoder::new(data).read_to_end(&mut buf).await.unwrap();
+            buf
+        }
+
+        async fn br(data: &[u8]) -> Vec<u8> {
+            let mut buf = Vec::new();
+            BrotliEncoder::new(data)
+                .read_to_end(&mut buf)
+                .await
+                .unwrap();
+            buf
+        }
+
+        #[tokio::test]
+        async fn test_round_trip() {
+            let data = b"hello world".repeat(100);
+
+            let gzipped = gzip(&data).await;
+            assert_eq!(decode_with("gzip", gzipped, 4096).await.unwrap(), data);
+
+            let brotli = br(&data).await;
+            assert_eq!(decode_with("br", brotli, 4096).await.unwrap(), data);
+
+            // applied in the listed order, so decoded in reverse
+            let both = br(&gzip(&data).await).await;
+            assert_eq!(
+                decode_with("identity, gzip, br", both, 4096).await.unwrap(),
+                data
+            );
+        }
+
+        #[tokio::test]
+        async fn test_corrupt_stream() {
+            let mut gzipped = gzip(b"hello world").await;
+            let len = gzipped.len();
+            gzipped[10..len - 8].fill(0xff);
+
+            let err = decode_with("gzip", gzipped, 4096).await.unwrap_err();
+            assert!(matches!(err, ReadBytesError::CorruptStreamError { .. }));
+        }
+
+        #[tokio::test]
+        async fn test_limit_after_decompression() {
+            let data = vec![0; 64 * 1024];
+
+            let gzipped = gzip(&data).await;
+            assert!(gzipped.len() < 1024);
+
+            let err = decode_with("gzip", gzipped, 1024).await.unwrap_err();
+            assert!(matches!(err, ReadBytesError::LengthLimitError { .. }));
+        }
+    }
+}
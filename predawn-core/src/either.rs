@@ -10,6 +10,7 @@ use http::StatusCode;
 use crate::{
     error::BoxError,
     openapi::{self, Schema, merge_responses},
+    problem::ProblemDetails,
     response::Response,
     response_error::ResponseError,
 };
@@ -83,6 +84,13 @@ where
         }
     }
 
+    fn problem_details(&self) -> ProblemDetails {
+        match self {
+            Either::Left(l) => l.problem_details(),
+            Either::Right(r) => r.problem_details(),
+        }
+    }
+
     fn responses(
         schemas: &mut BTreeMap<String, Schema>,
         schemas_in_progress: &mut Vec<String>,
@@ -4,7 +4,14 @@ use error2::Location;
 use http::{HeaderValue, StatusCode, header::CONTENT_TYPE};
 use mime::TEXT_PLAIN_UTF_8;
 
-use crate::{response::Response, response_error::ResponseError};
+use crate::{
+    into_response::IntoResponse,
+    problem::{
+        ProblemDetails, problem_details_enabled, problem_details_scoped, with_problem_details,
+    },
+    response::Response,
+    response_error::ResponseError,
+};
 
 /// Alias for a type-erased error type.
 pub type BoxError = Box<dyn StdError + Send + Sync>;
@@ -12,6 +19,9 @@ pub type BoxError = Box<dyn StdError + Send + Sync>;
 #[derive(Debug)]
 pub struct Error {
     response: Response,
+    /// The response as problem details, kept when the error is created outside of
+    /// [`scope_problem_details`](crate::problem::scope_problem_details).
+    problem_response: Option<Box<Response>>,
     inner: BoxError,
     error_stack: Box<[Box<str>]>,
 }
@@ -39,6 +49,7 @@ impl Error {
     {
         let Self {
             response,
+            problem_response,
             inner,
             error_stack,
         } = self;
@@ -47,6 +58,7 @@ impl Error {
             Ok(err) => Ok((response, *err, error_stack)),
             Err(err) => Err(Self {
                 response,
+                problem_response,
                 inner: err,
                 error_stack,
             }),
@@ -61,9 +73,45 @@ impl Error {
         self.response
     }
 
+    pub fn response_mut(&mut self) -> &mut Response {
+        &mut self.response
+    }
+
     pub fn error_stack(&self) -> &[Box<str>] {
         &self.error_stack
     }
+
+    /// Renders the error as problem details or not, if it was created outside of
+    /// [`scope_problem_details`](crate::problem::scope_problem_details), such as in a spawned task.
+    ///
+    /// The headers added to the response in the meantime are kept.
+    pub fn resolve_problem_details(mut self, enabled: bool) -> Self {
+        let Some(mut problem_response) = self.problem_response.take() else {
+            return self;
+        };
+
+        if enabled {
+            let headers = std::mem::take(self.response.headers_mut());
+
+            for (name, value) in &headers {
+                if !problem_response.headers().contains_key(name) {
+                    problem_response.headers_mut().append(name, value.clone());
+                }
+            }
+
+            self.response = *problem_response;
+        }
+
+        self
+    }
+}
+
+/// Renders `render` as it is within the current scope, and also as problem details if there is none.
+fn render<F: Fn() -> Response>(render: F) -> (Response, Option<Box<Response>>) {
+    match problem_details_scoped() {
+        Some(_) => (render(), None),
+        None => (render(), Some(Box::new(with_problem_details(true, render)))),
+    }
 }
 
 impl<T> From<T> for Error
@@ -71,13 +119,14 @@ where
     T: ResponseError,
 {
     fn from(error: T) -> Self {
-        let response = error.as_response();
+        let (response, problem_response) = render(|| error.as_response());
         let error_stack = error.error_stack();
 
         let inner = error.inner();
 
         Self {
             response,
+            problem_response,
             inner,
             error_stack,
         }
@@ -103,20 +152,30 @@ impl From<(StatusCode, BoxError)> for Error {
             }
         }
 
-        let response = Response::builder()
-            .status(status)
-            .header(
-                CONTENT_TYPE,
-                HeaderValue::from_static(TEXT_PLAIN_UTF_8.as_ref()),
-            )
-            .body(error.to_string().into())
-            .unwrap();
+        let (response, problem_response) = render(|| {
+            if problem_details_enabled() {
+                ProblemDetails::new(status)
+                    .detail(error.to_string())
+                    .into_response()
+                    .unwrap_or_else(|a| match a {})
+            } else {
+                Response::builder()
+                    .status(status)
+                    .header(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static(TEXT_PLAIN_UTF_8.as_ref()),
+                    )
+                    .body(error.to_string().into())
+                    .unwrap()
+            }
+        });
 
         let mut stack = Vec::new();
         stack.push(format!("0: {}, at: {}", error, Location::caller()).into_boxed_str());
 
         Self {
             response,
+            problem_response,
             inner: error,
             error_stack: stack.into_boxed_slice(),
         }
@@ -1,17 +1,16 @@
 use std::convert::Infallible;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use futures_util::TryStreamExt;
 use http::{HeaderMap, Method, Uri, Version};
-use http_body_util::BodyExt;
-use snafu::{IntoError, ResultExt};
+use snafu::ResultExt;
 
 use crate::{
-    body::RequestBody,
+    body::{RequestBody, decode_body},
     private::{ViaRequest, ViaRequestHead},
     request::{BodyLimit, Head, LocalAddr, OriginalUri, RemoteAddr},
     response_error::{
-        InvalidUtf8Snafu, LengthLimitSnafu, ReadBytesError, ReadBytesSnafu, ReadStringError,
-        ResponseError, read_bytes_error,
+        InvalidUtf8Snafu, ReadBytesError, ReadBytesSnafu, ReadStringError, ResponseError,
     },
 };
 
@@ -25,6 +24,10 @@ pub trait FromRequestHead: Sized {
 pub trait FromRequest<M = ViaRequest>: Sized {
     type Error: ResponseError;
 
+    /// The limit of the request body, replacing the one of the request, such as
+    /// `server.request_body_limit`, when it is the last argument of an endpoint.
+    const BODY_LIMIT: Option<usize> = None;
+
     fn from_request(
         head: &mut Head,
         body: RequestBody,
@@ -86,6 +89,8 @@ impl<T: FromRequestHead> FromRequestHead for Result<T, T::Error> {
 impl<T: FromRequest> FromRequest for Result<T, T::Error> {
     type Error = Infallible;
 
+    const BODY_LIMIT: Option<usize> = T::BODY_LIMIT;
+
     async fn from_request(head: &mut Head, body: RequestBody) -> Result<Self, Self::Error> {
         Ok(T::from_request(head, body).await)
     }
@@ -103,18 +108,25 @@ impl FromRequest for Bytes {
     type Error = ReadBytesError;
 
     async fn from_request(head: &mut Head, body: RequestBody) -> Result<Self, Self::Error> {
-        let limit = head.body_limit().0;
-
-        match body.collect().await {
-            Ok(collected) => Ok(collected.to_bytes()),
-            Err(err) => match err.downcast::<http_body_util::LengthLimitError>() {
-                Ok(_) => {
-                    let err = LengthLimitSnafu { limit }.build();
-                    Err(read_bytes_error::LengthLimitSnafu.into_error(err))
-                }
-                Err(err) => Err(read_bytes_error::UnknownBodySnafu.into_error(err)),
-            },
+        let mut stream = decode_body(head, body);
+
+        let Some(first) = stream.try_next().await? else {
+            return Ok(Bytes::new());
+        };
+
+        let Some(second) = stream.try_next().await? else {
+            return Ok(first);
+        };
+
+        let mut buf = BytesMut::with_capacity(first.len() + second.len());
+        buf.extend_from_slice(&first);
+        buf.extend_from_slice(&second);
+
+        while let Some(chunk) = stream.try_next().await? {
+            buf.extend_from_slice(&chunk);
         }
+
+        Ok(buf.freeze())
     }
 }
 
@@ -8,6 +8,7 @@ pub mod into_response;
 mod macros;
 pub mod media_type;
 pub mod openapi;
+pub mod problem;
 pub mod request;
 pub mod response;
 pub mod response_error;
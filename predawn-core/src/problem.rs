@@ -0,0 +1,424 @@
+use std::{borrow::Cow, collections::BTreeMap, convert::Infallible};
+
+use http::{HeaderValue, StatusCode, header::CONTENT_TYPE};
+use indexmap::IndexMap;
+use predawn_schema::ToSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    into_response::IntoResponse,
+    openapi::{
+        self, AnySchema, ArrayType, IntegerType, ObjectType, ReferenceOr, Schema, SchemaData,
+        SchemaKind, StringType, Type,
+    },
+    response::Response,
+};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+tokio::task_local! {
+    static ENABLED: bool;
+}
+
+/// Runs `f` with every [`ResponseError`](crate::response_error::ResponseError) documented as
+/// [`ProblemDetails`] or not, `create_app` registers routes inside it according to `server.problem_details`.
+pub fn with_problem_details<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    ENABLED.sync_scope(enabled, f)
+}
+
+/// Polls `future` with every [`ResponseError`](crate::response_error::ResponseError) rendered as
+/// [`ProblemDetails`] or not, `create_app` handles each request inside it according to `server.problem_details`.
+///
+/// An error created outside of it, such as in a spawned task, is rendered again by
+/// [`Error::resolve_problem_details`](crate::error::Error::resolve_problem_details).
+pub async fn scope_problem_details<F: Future>(enabled: bool, future: F) -> F::Output {
+    ENABLED.scope(enabled, future).await
+}
+
+/// `false` outside of [`with_problem_details`] and [`scope_problem_details`].
+pub fn problem_details_enabled() -> bool {
+    problem_details_scoped().unwrap_or(false)
+}
+
+/// `None` outside of [`with_problem_details`] and [`scope_problem_details`].
+pub(crate) fn problem_details_scoped() -> Option<bool> {
+    ENABLED.try_with(|enabled| *enabled).ok()
+}
+
+/// An RFC 9457 problem details object, rendered as `application/problem+json`.
+///
+/// Extension members are written next to the standard members.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemDetails {
+    status: StatusCode,
+    type_uri: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    instance: Option<String>,
+    errors: Vec<Value>,
+    extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// The title defaults to the canonical reason of `status`.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            type_uri: None,
+            title: status.canonical_reason().map(ToString::to_string),
+            detail: None,
+            instance: None,
+            errors: Vec::new(),
+            extensions: Map::new(),
+        }
+    }
+
+    /// A URI reference identifying the problem type, `about:blank` if not set.
+    pub fn type_uri<T: Into<String>>(mut self, type_uri: T) -> Self {
+        self.type_uri = Some(type_uri.into());
+        self
+    }
+
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn detail<T: Into<String>>(mut self, detail: T) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn instance<T: Into<String>>(mut self, instance: T) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Appends an entry to the `errors` member.
+    pub fn error<T: Serialize>(mut self, error: T) -> Self {
+        self.errors.push(to_value(error));
+        self
+    }
+
+    pub fn extension<T: Serialize>(mut self, name: &str, value: T) -> Self {
+        self.extensions.insert(name.to_string(), to_value(value));
+        self
+    }
+
+    /// Adds every field of `value` as an extension member, `value` must serialize to a JSON object.
+    ///
+    /// Use [`ProblemDetails::schema_with`] to document them.
+    pub fn extensions<T: Serialize>(mut self, value: T) -> Self {
+        match to_value(value) {
+            Value::Object(map) => self.extensions.extend(map),
+            value => panic!("extension members must be an object, but got `{value}`"),
+        }
+
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn to_json(&self) -> Value {
+        let Self {
+            status,
+            type_uri,
+            title,
+            detail,
+            instance,
+            errors,
+            extensions,
+        } = self;
+
+        let mut map = extensions.clone();
+
+        map.insert(
+            "type".into(),
+            type_uri.as_deref().unwrap_or("about:blank").into(),
+        );
+
+        if let Some(title) = title {
+            map.insert("title".into(), title.as_str().into());
+        }
+
+        map.insert("status".into(), status.as_u16().into());
+
+        if let Some(detail) = detail {
+            map.insert("detail".into(), detail.as_str().into());
+        }
+
+        if let Some(instance) = instance {
+            map.insert("instance".into(), instance.as_str().into());
+        }
+
+        if !errors.is_empty() {
+            map.insert("errors".into(), errors.clone().into());
+        }
+
+        Value::Object(map)
+    }
+
+    /// Reads problem details written by [`ProblemDetails::to_json`], `None` if `value` is not an
+    /// object with a valid `status`.
+    pub fn from_json(value: Value) -> Option<Self> {
+        let Value::Object(mut map) = value else {
+            return None;
+        };
+
+        let status = map
+            .remove("status")
+            .and_then(|status| status.as_u64())
+            .and_then(|status| u16::try_from(status).ok())
+            .and_then(|status| StatusCode::from_u16(status).ok())?;
+
+        let mut string = |name: &str| match map.remove(name) {
+            Some(Value::String(value)) => Some(value),
+            _ => None,
+        };
+
+        let type_uri = string("type").filter(|type_uri| type_uri != "about:blank");
+        let title = string("title");
+        let detail = string("detail");
+        let instance = string("instance");
+
+        let errors = match map.remove("errors") {
+            Some(Value::Array(errors)) => errors,
+            _ => Vec::new(),
+        };
+
+        Some(Self {
+            status,
+            type_uri,
+            title,
+            detail,
+            instance,
+            errors,
+            extensions: map,
+        })
+    }
+
+    /// The schema of problem details whose extension members are described by `T`.
+    pub fn schema_with<T: ToSchema>(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> ReferenceOr<Schema> {
+        ReferenceOr::Item(Schema {
+            schema_data: Default::default(),
+            schema_kind: SchemaKind::AllOf {
+                all_of: vec![
+                    Self::schema_ref(schemas, schemas_in_progress),
+                    T::schema_ref(schemas, schemas_in_progress),
+                ],
+            },
+        })
+    }
+
+    #[doc(hidden)]
+    pub fn content(schema: ReferenceOr<Schema>) -> IndexMap<String, openapi::MediaType> {
+        let mut content = IndexMap::with_capacity(1);
+
+        content.insert(
+            PROBLEM_JSON.to_string(),
+            openapi::MediaType {
+                schema: Some(schema),
+                ..Default::default()
+            },
+        );
+
+        content
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).expect("failed to serialize problem details member")
+}
+
+impl IntoResponse for ProblemDetails {
+    type Error = Infallible;
+
+    fn into_response(self) -> Result<Response, Self::Error> {
+        Ok(Response::builder()
+            .status(self.status)
+            .header(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))
+            .body(self.to_json().to_string().into())
+            .unwrap())
+    }
+}
+
+impl ToSchema for ProblemDetails {
+    fn key() -> String {
+        "ProblemDetails".into()
+    }
+
+    fn title() -> Cow<'static, str> {
+        "ProblemDetails".into()
+    }
+
+    fn schema(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Schema {
+        let string = || Type::String(StringType::default());
+
+        let errors = Type::Array(ArrayType {
+            items: Some(ReferenceOr::Item(Box::new(Schema {
+                schema_data: Default::default(),
+                schema_kind: SchemaKind::Any(AnySchema::default()),
+            }))),
+            min_items: None,
+            max_items: None,
+            unique_items: false,
+        });
+
+        let properties = [
+            (
+                "type",
+                "A URI reference that identifies the problem type.",
+                string(),
+            ),
+            (
+                "title",
+                "A short, human-readable summary of the problem type.",
+                string(),
+            ),
+            (
+                "status",
+                "The HTTP status code.",
+                Type::Integer(IntegerType::default()),
+            ),
+            (
+                "detail",
+                "A human-readable explanation specific to this occurrence of the problem.",
+                string(),
+            ),
+            (
+                "instance",
+                "A URI reference that identifies the specific occurrence of the problem.",
+                string(),
+            ),
+            (
+                "errors",
+                "The individual errors that make up this problem.",
+                errors,
+            ),
+        ];
+
+        let mut obj = ObjectType::default();
+
+        for (name, description, ty) in properties {
+            let schema = Schema {
+                schema_data: SchemaData {
+                    description: Some(description.into()),
+                    ..Default::default()
+                },
+                schema_kind: SchemaKind::Type(ty),
+            };
+
+            obj.properties
+                .insert(name.into(), ReferenceOr::Item(Box::new(schema)));
+        }
+
+        obj.required = vec!["type".into(), "status".into()];
+
+        Schema {
+            schema_data: SchemaData {
+                title: Some(<Self as ToSchema>::title().into()),
+                description: Some("Problem details for HTTP APIs (RFC 9457).".into()),
+                ..Default::default()
+            },
+            schema_kind: SchemaKind::Type(Type::Object(obj)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use serde_json::json;
+
+    use super::{PROBLEM_JSON, ProblemDetails, problem_details_enabled, scope_problem_details};
+    use crate::{error::Error, response_error::LengthLimitSnafu};
+
+    #[test]
+    fn test_to_json() {
+        let problem = ProblemDetails::new(StatusCode::NOT_FOUND);
+        assert_eq!(
+            problem.to_json(),
+            json!({ "type": "about:blank", "title": "Not Found", "status": 404 })
+        );
+
+        let problem = ProblemDetails::new(StatusCode::FORBIDDEN)
+            .type_uri("https://example.com/probs/out-of-credit")
+            .detail("your balance is 30")
+            .instance("/account/12345")
+            .error(json!({ "path": "amount" }))
+            .extensions(json!({ "balance": 30 }));
+
+        assert_eq!(
+            ProblemDetails::from_json(problem.to_json()).as_ref(),
+            Some(&problem)
+        );
+
+        assert_eq!(
+            problem.to_json(),
+            json!({
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "Forbidden",
+                "status": 403,
+                "detail": "your balance is 30",
+                "instance": "/account/12345",
+                "errors": [{ "path": "amount" }],
+                "balance": 30,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scoped() {
+        let error = || Error::from(LengthLimitSnafu { limit: 1_usize }.build());
+
+        let (problem, plain) = tokio::join!(
+            scope_problem_details(true, async { error() }),
+            scope_problem_details(false, async { error() }),
+        );
+
+        assert_eq!(problem.response().headers()["content-type"], PROBLEM_JSON);
+        assert_eq!(
+            plain.response().headers()["content-type"],
+            "text/plain; charset=utf-8"
+        );
+
+        assert!(!problem_details_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_spawned() {
+        let error = || Error::from(LengthLimitSnafu { limit: 1_usize }.build());
+
+        // as the app does with the errors returned by the handlers
+        let spawned = |enabled| {
+            scope_problem_details(enabled, async move {
+                let error = tokio::spawn(async move { error() }).await.unwrap();
+                error.resolve_problem_details(enabled)
+            })
+        };
+
+        let (problem, plain) = tokio::join!(spawned(true), spawned(false));
+
+        assert_eq!(problem.response().headers()["content-type"], PROBLEM_JSON);
+        assert_eq!(
+            plain.response().headers()["content-type"],
+            "text/plain; charset=utf-8"
+        );
+
+        let mut error = tokio::spawn(async move { error() }).await.unwrap();
+        error
+            .response_mut()
+            .headers_mut()
+            .insert("retry-after", "1".parse().unwrap());
+
+        let response = error.resolve_problem_details(true).response();
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON);
+        assert_eq!(response.headers()["retry-after"], "1");
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt, net::SocketAddr};
+use std::{fmt, net::SocketAddr, time::Duration};
 
 use error2::{ErrorExt, Location, NextError};
 use http::{
@@ -9,7 +9,10 @@ use http::{
 use hyper::body::Incoming;
 use snafu::{OptionExt, Snafu};
 
-use crate::{body::RequestBody, impl_debug, impl_deref, impl_display};
+use crate::{
+    body::{RequestBody, TimeoutBody},
+    impl_debug, impl_deref, impl_display,
+};
 
 pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024; // 2 mb
 
@@ -20,6 +23,29 @@ impl_deref!(BodyLimit : usize);
 impl_debug!(BodyLimit);
 impl_display!(BodyLimit);
 
+/// Cuts off clients that send the request body too slowly, the body then fails to be read with a
+/// [`BodyTimeoutError`](crate::response_error::BodyTimeoutError).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BodyTimeout {
+    /// The longest time to wait for the next chunk of the body.
+    pub read_timeout: Option<Duration>,
+    pub min_throughput: Option<MinThroughput>,
+}
+
+impl BodyTimeout {
+    pub fn is_disabled(&self) -> bool {
+        self.read_timeout.is_none() && self.min_throughput.is_none()
+    }
+}
+
+/// The body must be received at `bytes_per_second` on average, counted from the first read of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MinThroughput {
+    pub bytes_per_second: u64,
+    /// How long the body may be received slower at the beginning, such as while the connection ramps up.
+    pub grace_period: Duration,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LocalAddr(pub SocketAddr);
 
@@ -73,6 +99,7 @@ impl Request {
                 headers,
                 extensions,
                 body_limit: BodyLimit(DEFAULT_BODY_LIMIT),
+                body_timeout: BodyTimeout::default(),
                 local_addr,
                 remote_addr,
                 original_uri: OriginalUri(uri),
@@ -85,10 +112,15 @@ impl Request {
         &mut self.head.body_limit
     }
 
+    pub fn body_timeout(&mut self) -> &mut BodyTimeout {
+        &mut self.head.body_timeout
+    }
+
     pub fn split(self) -> (Head, RequestBody) {
         let Self { head, body } = self;
 
         let BodyLimit(limit) = head.body_limit;
+        let body = TimeoutBody::new(body, head.body_timeout);
 
         (head, RequestBody::new(body, limit))
     }
@@ -114,6 +146,8 @@ pub struct Head {
 
     pub(crate) body_limit: BodyLimit,
 
+    pub(crate) body_timeout: BodyTimeout,
+
     pub(crate) local_addr: LocalAddr,
 
     pub(crate) remote_addr: RemoteAddr,
@@ -130,6 +164,7 @@ impl fmt::Debug for Head {
             .field("headers", &self.headers)
             // .field("extensions", &self.extensions)
             .field("body_limit", &self.body_limit)
+            .field("body_timeout", &self.body_timeout)
             .field("local_addr", &self.local_addr)
             .field("remote_addr", &self.remote_addr)
             .field("original_uri", &self.original_uri)
@@ -154,6 +189,10 @@ impl Head {
         self.body_limit
     }
 
+    pub fn body_timeout(&self) -> BodyTimeout {
+        self.body_timeout
+    }
+
     pub fn local_addr(&self) -> LocalAddr {
         self.local_addr
     }
@@ -170,6 +209,9 @@ impl Head {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct PrivateBodyLimit(usize);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PrivateBodyTimeout(BodyTimeout);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct PrivateLocalAddr(SocketAddr);
 
@@ -190,6 +232,7 @@ impl From<Request> for http::Request<Incoming> {
                     headers,
                     extensions,
                     body_limit: BodyLimit(body_limit),
+                    body_timeout,
                     local_addr: LocalAddr(local_addr),
                     remote_addr: RemoteAddr(remote_addr),
                     original_uri: OriginalUri(original_uri),
@@ -206,6 +249,8 @@ impl From<Request> for http::Request<Incoming> {
         *req.extensions_mut() = extensions;
 
         req.extensions_mut().insert(PrivateBodyLimit(body_limit));
+        req.extensions_mut()
+            .insert(PrivateBodyTimeout(body_timeout));
         req.extensions_mut().insert(PrivateLocalAddr(local_addr));
         req.extensions_mut().insert(PrivateRemoteAddr(remote_addr));
         req.extensions_mut()
@@ -232,6 +277,8 @@ impl TryFrom<http::Request<Incoming>> for Request {
         ) = request.into_parts();
 
         let PrivateBodyLimit(body_limit) = extensions.remove().context(NotFoundBodyLimitSnafu)?;
+        let PrivateBodyTimeout(body_timeout) =
+            extensions.remove().context(NotFoundBodyTimeoutSnafu)?;
         let PrivateLocalAddr(local_addr) = extensions.remove().context(NotFoundLocalAddrSnafu)?;
         let PrivateRemoteAddr(remote_addr) =
             extensions.remove().context(NotFoundRemoteAddrSnafu)?;
@@ -246,6 +293,7 @@ impl TryFrom<http::Request<Incoming>> for Request {
                 headers,
                 extensions,
                 body_limit: BodyLimit(body_limit),
+                body_timeout,
                 local_addr: LocalAddr(local_addr),
                 remote_addr: RemoteAddr(remote_addr),
                 original_uri: OriginalUri(original_uri),
@@ -263,6 +311,12 @@ pub enum ConvertRequestError {
         location: Location,
     },
 
+    #[snafu(display("not found `body timeout` in request extensions"))]
+    NotFoundBodyTimeout {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("not found `local address` in request extensions"))]
     NotFoundLocalAddr {
         #[snafu(implicit)]
@@ -286,6 +340,7 @@ impl ErrorExt for ConvertRequestError {
     fn entry(&self) -> (Location, NextError<'_>) {
         match self {
             Self::NotFoundBodyLimit { location }
+            | Self::NotFoundBodyTimeout { location }
             | Self::NotFoundLocalAddr { location }
             | Self::NotFoundRemoteAddr { location }
             | Self::NotFoundOriginalUri { location } => (*location, NextError::None),
@@ -1,18 +1,23 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     convert::Infallible,
+    io,
     string::FromUtf8Error,
+    time::Duration,
 };
 
 use error2::{ErrorExt, Location, NextError};
 use http::{HeaderValue, StatusCode, header::CONTENT_TYPE};
 use mime::TEXT_PLAIN_UTF_8;
+use predawn_schema::ToSchema;
 use snafu::Snafu;
 
 use crate::{
     error::BoxError,
+    into_response::IntoResponse,
     media_type::MultiResponseMediaType,
-    openapi::{self, Schema},
+    openapi::{self, ReferenceOr, Schema},
+    problem::{ProblemDetails, problem_details_enabled},
     response::Response,
 };
 
@@ -22,6 +27,13 @@ pub trait ResponseError: ErrorExt + Send + Sync + Sized + 'static {
     fn status_codes(codes: &mut BTreeSet<StatusCode>);
 
     fn as_response(&self) -> Response {
+        if problem_details_enabled() {
+            return self
+                .problem_details()
+                .into_response()
+                .unwrap_or_else(|a| match a {});
+        }
+
         Response::builder()
             .status(self.as_status())
             .header(
@@ -40,6 +52,12 @@ pub trait ResponseError: ErrorExt + Send + Sync + Sized + 'static {
 
         Self::status_codes(&mut codes);
 
+        let content = if problem_details_enabled() {
+            ProblemDetails::content(Self::problem_details_schema(schemas, schemas_in_progress))
+        } else {
+            <String as MultiResponseMediaType>::content(schemas, schemas_in_progress)
+        };
+
         codes
             .into_iter()
             .map(|status| {
@@ -47,10 +65,7 @@ pub trait ResponseError: ErrorExt + Send + Sync + Sized + 'static {
                     status,
                     openapi::Response {
                         description: status.canonical_reason().unwrap_or_default().to_string(),
-                        content: <String as MultiResponseMediaType>::content(
-                            schemas,
-                            schemas_in_progress,
-                        ),
+                        content: content.clone(),
                         ..Default::default()
                     },
                 )
@@ -58,6 +73,19 @@ pub trait ResponseError: ErrorExt + Send + Sync + Sized + 'static {
             .collect()
     }
 
+    /// The body used when [`problem_details_enabled`] is `true`.
+    fn problem_details(&self) -> ProblemDetails {
+        ProblemDetails::new(self.as_status()).detail(self.to_string())
+    }
+
+    /// The schema of [`ResponseError::problem_details`], override it together with extension members.
+    fn problem_details_schema(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> ReferenceOr<Schema> {
+        ProblemDetails::schema_ref(schemas, schemas_in_progress)
+    }
+
     #[doc(hidden)]
     fn inner(self) -> BoxError {
         Box::new(self)
@@ -108,6 +136,44 @@ impl ResponseError for LengthLimitError {
     }
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum BodyTimeoutError {
+    #[snafu(display("no part of the request body was received within `{timeout:?}`"))]
+    ReadTimeout {
+        #[snafu(implicit)]
+        location: Location,
+        timeout: Duration,
+    },
+    #[snafu(display(
+        "the request body was received slower than `{bytes_per_second}` bytes per second"
+    ))]
+    MinThroughput {
+        #[snafu(implicit)]
+        location: Location,
+        bytes_per_second: u64,
+    },
+}
+
+impl ErrorExt for BodyTimeoutError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            BodyTimeoutError::ReadTimeout { location, .. }
+            | BodyTimeoutError::MinThroughput { location, .. } => (*location, NextError::None),
+        }
+    }
+}
+
+impl ResponseError for BodyTimeoutError {
+    fn as_status(&self) -> StatusCode {
+        StatusCode::REQUEST_TIMEOUT
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::REQUEST_TIMEOUT);
+    }
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 #[snafu(module)]
@@ -118,12 +184,30 @@ pub enum ReadBytesError {
         location: Location,
         source: LengthLimitError,
     },
+    #[snafu(display("{source}"))]
+    TimeoutError {
+        #[snafu(implicit)]
+        location: Location,
+        source: BodyTimeoutError,
+    },
     #[snafu(display("failed to read bytes from request body"))]
     UnknownBodyError {
         #[snafu(implicit)]
         location: Location,
         source: BoxError,
     },
+    #[snafu(display("unsupported content encoding `{encoding}`"))]
+    UnsupportedEncodingError {
+        #[snafu(implicit)]
+        location: Location,
+        encoding: Box<str>,
+    },
+    #[snafu(display("failed to decode request body"))]
+    CorruptStreamError {
+        #[snafu(implicit)]
+        location: Location,
+        source: io::Error,
+    },
 }
 
 impl ErrorExt for ReadBytesError {
@@ -132,9 +216,18 @@ impl ErrorExt for ReadBytesError {
             ReadBytesError::LengthLimitError { location, source } => {
                 (*location, NextError::Ext(source))
             }
+            ReadBytesError::TimeoutError { location, source } => {
+                (*location, NextError::Ext(source))
+            }
             ReadBytesError::UnknownBodyError { location, source } => {
                 (*location, NextError::Std(source.as_ref()))
             }
+            ReadBytesError::UnsupportedEncodingError { location, .. } => {
+                (*location, NextError::None)
+            }
+            ReadBytesError::CorruptStreamError { location, source } => {
+                (*location, NextError::Std(source))
+            }
         }
     }
 }
@@ -143,13 +236,18 @@ impl ResponseError for ReadBytesError {
     fn as_status(&self) -> StatusCode {
         match self {
             ReadBytesError::LengthLimitError { source, .. } => source.as_status(),
+            ReadBytesError::TimeoutError { source, .. } => source.as_status(),
             ReadBytesError::UnknownBodyError { .. } => StatusCode::BAD_REQUEST,
+            ReadBytesError::UnsupportedEncodingError { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ReadBytesError::CorruptStreamError { .. } => StatusCode::BAD_REQUEST,
         }
     }
 
     fn status_codes(codes: &mut BTreeSet<StatusCode>) {
         LengthLimitError::status_codes(codes);
+        BodyTimeoutError::status_codes(codes);
         codes.insert(StatusCode::BAD_REQUEST);
+        codes.insert(StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
 }
 
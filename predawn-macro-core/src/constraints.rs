@@ -0,0 +1,211 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use quote_use::quote_use;
+use syn::{Expr, Generics, Ident, LitStr, Type, WherePredicate, parse_quote};
+
+use crate::util::get_crate_name;
+
+/// The validation constraints declared on a field by `#[schema(...)]`.
+pub struct Constraints {
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<LitStr>,
+    pub format: Option<String>,
+}
+
+impl Constraints {
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none()
+            && self.max.is_none()
+            && self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.pattern.is_none()
+            && self.format.is_none()
+    }
+
+    pub fn check_pattern(&self) -> syn::Result<()> {
+        let Some(pattern) = &self.pattern else {
+            return Ok(());
+        };
+
+        regex_syntax::Parser::new()
+            .parse(&pattern.value())
+            .map_err(|e| syn::Error::new(pattern.span(), format!("invalid pattern: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Generates a statement that writes the constraints into the mutable `schema` in scope.
+    pub fn generate_apply(&self) -> TokenStream {
+        let crate_name = get_crate_name();
+
+        let Self {
+            min,
+            max,
+            min_length,
+            max_length,
+            pattern,
+            format,
+        } = self;
+
+        let min = option_tokens(min.as_ref().map(|min| quote! { (#min) as f64 }));
+        let max = option_tokens(max.as_ref().map(|max| quote! { (#max) as f64 }));
+        let min_length = option_tokens(min_length.map(|len| quote! { #len }));
+        let max_length = option_tokens(max_length.map(|len| quote! { #len }));
+        let pattern = option_tokens(pattern.as_ref().map(|pattern| quote! { #pattern }));
+        let format = option_tokens(format.as_ref().map(|format| quote! { #format }));
+
+        quote_use! {
+            # use #crate_name::validate::Constraints;
+
+            Constraints {
+                min: #min,
+                max: #max,
+                min_length: #min_length,
+                max_length: #max_length,
+                pattern: #pattern,
+                format: #format,
+            }
+            .apply(&mut schema);
+        }
+    }
+
+    /// Generates statements that check the `value` in scope, pushing violations at `path` into `errors`.
+    pub fn generate_checks(&self) -> TokenStream {
+        let crate_name = get_crate_name();
+
+        let Self {
+            min,
+            max,
+            min_length,
+            max_length,
+            pattern,
+            format,
+        } = self;
+
+        let check_min = min.as_ref().map(|min| {
+            quote_use! {
+                # use #crate_name::validate::check_min;
+
+                check_min(value, (#min) as f64, &path, errors);
+            }
+        });
+
+        let check_max = max.as_ref().map(|max| {
+            quote_use! {
+                # use #crate_name::validate::check_max;
+
+                check_max(value, (#max) as f64, &path, errors);
+            }
+        });
+
+        let check_min_length = min_length.map(|len| {
+            quote_use! {
+                # use #crate_name::validate::check_min_length;
+
+                check_min_length(value, #len, &path, errors);
+            }
+        });
+
+        let check_max_length = max_length.map(|len| {
+            quote_use! {
+                # use #crate_name::validate::check_max_length;
+
+                check_max_length(value, #len, &path, errors);
+            }
+        });
+
+        let check_pattern = pattern.as_ref().map(|pattern| {
+            quote_use! {
+                # use std::sync::LazyLock;
+                # use #crate_name::__internal::regex::Regex;
+                # use #crate_name::validate::check_pattern;
+
+                {
+                    static REGEX: LazyLock<Regex> =
+                        LazyLock::new(|| Regex::new(#pattern).expect("pattern is checked at compile time"));
+
+                    check_pattern(value, &REGEX, &path, errors);
+                }
+            }
+        });
+
+        let check_format = format.as_ref().map(|format| {
+            quote_use! {
+                # use #crate_name::validate::check_format;
+
+                check_format(value, #format, &path, errors);
+            }
+        });
+
+        quote! {
+            #check_min
+            #check_max
+            #check_min_length
+            #check_max_length
+            #check_pattern
+            #check_format
+        }
+    }
+}
+
+/// Adds a `Validate` bound for each of `nested_types` that uses the type parameters of `generics`.
+pub fn add_validate_bounds(generics: &mut Generics, nested_types: &[Type]) {
+    let crate_name = get_crate_name();
+
+    let bounds = nested_types
+        .iter()
+        .filter(|ty| crate::util::uses_type_params(ty, generics))
+        .map(|ty| -> WherePredicate { parse_quote!(#ty: #crate_name::validate::Validate) })
+        .collect::<Vec<_>>();
+
+    generics.make_where_clause().predicates.extend(bounds);
+}
+
+/// `body` checks `self` at `path`, pushing violations into `errors`, an empty `body` generates an
+/// impl that accepts every value.
+///
+/// Nothing is generated without a `body`, so that types marked `#[schema(custom_validate)]` can
+/// implement `Validate` themselves.
+pub fn generate_validate_impl(
+    ident: &Ident,
+    generics: &Generics,
+    nested_types: &[Type],
+    body: Option<TokenStream>,
+) -> TokenStream {
+    let Some(body) = body else {
+        return TokenStream::new();
+    };
+
+    let body = if body.is_empty() {
+        quote! { let _ = (path, errors); }
+    } else {
+        body
+    };
+
+    let crate_name = get_crate_name();
+
+    let mut generics = generics.clone();
+    add_validate_bounds(&mut generics, nested_types);
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote_use! {
+        # use #crate_name::validate::{Validate, ValidationErrors};
+
+        impl #impl_generics Validate for #ident #ty_generics #where_clause {
+            fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+                #body
+            }
+        }
+    }
+}
+
+fn option_tokens(tokens: Option<TokenStream>) -> TokenStream {
+    match tokens {
+        Some(tokens) => quote! { ::core::option::Option::Some(#tokens) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
@@ -1,5 +1,12 @@
+mod constraints;
+mod rename_rule;
 mod schema_attr;
 mod serde_attr;
 pub mod util;
 
-pub use self::{schema_attr::SchemaAttr, serde_attr::SerdeAttr};
+pub use self::{
+    constraints::{Constraints, add_validate_bounds, generate_validate_impl},
+    rename_rule::RenameRule,
+    schema_attr::SchemaAttr,
+    serde_attr::SerdeAttr,
+};
@@ -1,5 +1,5 @@
 use from_attr::{FlagOrValue, FromAttr};
-use syn::Expr;
+use syn::{Expr, LitStr, Type};
 
 #[derive(FromAttr, Default)]
 #[attribute(idents = [schema])]
@@ -7,4 +7,14 @@ pub struct SchemaAttr {
     pub rename: Option<String>,
     pub flatten: bool,
     pub default: FlagOrValue<Expr>,
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<LitStr>,
+    pub format: Option<String>,
+    pub nested: bool,
+    pub example: Option<Expr>,
+    /// The type whose schema is used instead of the field type's.
+    pub value_type: Option<Type>,
 }
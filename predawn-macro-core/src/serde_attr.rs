@@ -4,17 +4,43 @@ use syn::{
     spanned::Spanned,
 };
 
+use crate::RenameRule;
+
 pub struct SerdeAttr {
     pub rename: Option<String>,
+    /// The other names a field or variant is deserialized from.
+    pub aliases: Vec<String>,
     pub flatten: bool,
     pub default: FlagOrValue<String>,
+    pub rename_all: Option<RenameRule>,
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub untagged: bool,
+    pub skip_serializing: bool,
+    pub skip_deserializing: bool,
+    pub skip_serializing_if: bool,
+    pub deny_unknown_fields: bool,
+    pub transparent: bool,
+    /// Whether `with`, `serialize_with` or `deserialize_with` is present.
+    pub with: bool,
 }
 
 impl SerdeAttr {
     pub fn new(attrs: &[Attribute]) -> Self {
         let mut rename = None;
+        let mut aliases = Vec::new();
         let mut flatten = false;
         let mut default = FlagOrValue::None;
+        let mut rename_all = None;
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = false;
+        let mut skip_serializing = false;
+        let mut skip_deserializing = false;
+        let mut skip_serializing_if = false;
+        let mut deny_unknown_fields = false;
+        let mut transparent = false;
+        let mut with = false;
 
         for attr in attrs {
             if !attr.path().is_ident("serde") {
@@ -43,19 +69,16 @@ impl SerdeAttr {
                 };
 
                 match ident.as_str() {
-                    "rename" => match &meta {
-                        Meta::NameValue(MetaNameValue {
-                            value:
-                                Expr::Lit(ExprLit {
-                                    lit: Lit::Str(lit_str),
-                                    ..
-                                }),
-                            ..
-                        }) => {
-                            rename = Some(lit_str.value());
+                    "rename" => {
+                        if let Some(value) = serialize_value(&meta) {
+                            rename = Some(value);
                         }
-                        _ => continue,
-                    },
+                    }
+                    "alias" => {
+                        if let Some(value) = lit_str_value(&meta) {
+                            aliases.push(value);
+                        }
+                    }
                     "flatten" => match &meta {
                         Meta::Path(_) => {
                             flatten = true;
@@ -83,6 +106,61 @@ impl SerdeAttr {
                             continue;
                         }
                     },
+                    "rename_all" => {
+                        if let Some(rule) = serialize_value(&meta)
+                            .as_deref()
+                            .and_then(RenameRule::parse)
+                        {
+                            rename_all = Some(rule);
+                        }
+                    }
+                    "tag" => {
+                        if let Some(value) = lit_str_value(&meta) {
+                            tag = Some(value);
+                        }
+                    }
+                    "content" => {
+                        if let Some(value) = lit_str_value(&meta) {
+                            content = Some(value);
+                        }
+                    }
+                    "untagged" => {
+                        if let Meta::Path(_) = &meta {
+                            untagged = true;
+                        }
+                    }
+                    "skip" => {
+                        if let Meta::Path(_) = &meta {
+                            skip_serializing = true;
+                            skip_deserializing = true;
+                        }
+                    }
+                    "skip_serializing" => {
+                        if let Meta::Path(_) = &meta {
+                            skip_serializing = true;
+                        }
+                    }
+                    "skip_deserializing" => {
+                        if let Meta::Path(_) = &meta {
+                            skip_deserializing = true;
+                        }
+                    }
+                    "deny_unknown_fields" => {
+                        if let Meta::Path(_) = &meta {
+                            deny_unknown_fields = true;
+                        }
+                    }
+                    "transparent" => {
+                        if let Meta::Path(_) = &meta {
+                            transparent = true;
+                        }
+                    }
+                    "skip_serializing_if" => {
+                        skip_serializing_if = true;
+                    }
+                    "with" | "serialize_with" | "deserialize_with" => {
+                        with = true;
+                    }
                     _ => continue,
                 }
             }
@@ -90,8 +168,85 @@ impl SerdeAttr {
 
         Self {
             rename,
+            aliases,
             flatten,
             default,
+            rename_all,
+            tag,
+            content,
+            untagged,
+            skip_serializing,
+            skip_deserializing,
+            skip_serializing_if,
+            deny_unknown_fields,
+            transparent,
+            with,
         }
     }
+
+    pub fn skip(&self) -> bool {
+        self.skip_serializing && self.skip_deserializing
+    }
+}
+
+/// The value of `name = "..."`, or of `serialize` in `name(serialize = "...", deserialize = "...")`.
+fn serialize_value(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(_) => lit_str_value(meta),
+        Meta::List(meta_list) => meta_list
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .ok()
+            .and_then(|nested| {
+                nested
+                    .iter()
+                    .find(|meta| meta.path().is_ident("serialize"))
+                    .and_then(lit_str_value)
+            }),
+        Meta::Path(_) => None,
+    }
+}
+
+fn lit_str_value(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(MetaNameValue {
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit_str),
+                    ..
+                }),
+            ..
+        }) => Some(lit_str.value()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{Attribute, parse_quote};
+
+    use super::SerdeAttr;
+    use crate::RenameRule;
+
+    #[test]
+    fn test_serde_attr() {
+        let attrs: Vec<Attribute> = parse_quote! {
+            #[serde(rename(serialize = "ser", deserialize = "de"), skip_serializing, alias = "a")]
+            #[serde(alias = "b")]
+            #[serde(rename_all = "camelCase", skip_serializing_if = "Option::is_none", with = "m")]
+        };
+
+        let attr = SerdeAttr::new(&attrs);
+        assert_eq!(attr.rename.as_deref(), Some("ser"));
+        assert_eq!(attr.aliases, ["a", "b"]);
+        assert_eq!(attr.rename_all, Some(RenameRule::CamelCase));
+        assert!(attr.skip_serializing && !attr.skip_deserializing && !attr.skip());
+        assert!(attr.skip_serializing_if && attr.with);
+
+        let attrs: Vec<Attribute> = parse_quote! {
+            #[serde(skip, transparent, deny_unknown_fields)]
+        };
+
+        let attr = SerdeAttr::new(&attrs);
+        assert!(attr.skip() && attr.transparent && attr.deny_unknown_fields);
+    }
 }
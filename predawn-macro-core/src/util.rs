@@ -1,8 +1,10 @@
 use from_attr::FlagOrValue;
-use proc_macro2::TokenStream;
-use quote::quote;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{ToTokens, quote};
 use quote_use::quote_use;
-use syn::{Attribute, Expr, ExprLit, Lit, Meta, MetaNameValue, Path, Type, parse_quote};
+use syn::{
+    Attribute, Expr, ExprLit, Generics, Ident, Lit, Meta, MetaNameValue, Path, Type, parse_quote,
+};
 
 #[doc(hidden)]
 pub fn get_crate_name() -> TokenStream {
@@ -51,6 +53,27 @@ pub fn extract_description(attrs: &[Attribute]) -> String {
     docs
 }
 
+/// Documents the names a field is also deserialized from, as serde accepts them silently.
+pub fn append_aliases(description: &mut String, aliases: &[String]) {
+    if aliases.is_empty() {
+        return;
+    }
+
+    if !description.is_empty() {
+        description.push_str("\n\n");
+    }
+
+    let aliases = aliases
+        .iter()
+        .map(|alias| format!("`{alias}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    description.push_str("Also accepted as ");
+    description.push_str(&aliases);
+    description.push('.');
+}
+
 pub fn remove_description(attrs: &mut Vec<Attribute>) {
     attrs.retain(|attr| !attr.path().is_ident("doc"));
 }
@@ -61,6 +84,24 @@ pub fn generate_string_expr(s: &str) -> Expr {
     }
 }
 
+/// Whether `ty` mentions one of the type parameters of `generics`.
+pub fn uses_type_params(ty: &Type, generics: &Generics) -> bool {
+    fn visit(tokens: TokenStream, params: &[&Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&&ident),
+            TokenTree::Group(group) => visit(group.stream(), params),
+            _ => false,
+        })
+    }
+
+    let params = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+
+    !params.is_empty() && visit(ty.to_token_stream(), &params)
+}
+
 pub fn generate_default_expr(
     ty: &Type,
     serde_default: FlagOrValue<String>,
@@ -108,3 +149,20 @@ pub fn generate_json_value(ty: &Type, expr: &Expr) -> TokenStream {
             ))
     }
 }
+
+/// Unlike [`generate_json_value`], `expr` may be of any type that implements `Serialize`, such as
+/// the output of `serde_json::json!`.
+pub fn generate_example_json_value(expr: &Expr) -> TokenStream {
+    let crate_name = get_crate_name();
+
+    quote_use! {
+        # use std::{concat, stringify, file, line, column};
+        # use #crate_name::__internal::serde_json;
+
+        serde_json::to_value(#expr)
+            .expect(concat!(
+                "failed to serialize example `", stringify!(#expr),
+                "`, at ", file!(), ":", line!(), ":", column!()
+            ))
+    }
+}
@@ -1,10 +1,11 @@
-use from_attr::{AttrsValue, FromAttr, Map};
+use from_attr::{AttrsValue, FlagOrValue, FromAttr, Map};
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use quote_use::quote_use;
 use syn::{
-    Expr, FnArg, ImplItem, ImplItemFn, ItemImpl, Label, PatType, Path, Receiver, ReturnType, Type,
-    parse_quote, spanned::Spanned,
+    Expr, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Label, LitStr, Pat, PatIdent,
+    PatTupleStruct, PatType, Path, Receiver, ReturnType, Type, TypePath, parse_quote,
+    spanned::Spanned,
 };
 
 use crate::{
@@ -19,6 +20,7 @@ pub(crate) struct ControllerAttr {
     middleware: Option<Path>,
     tags: Vec<Type>,
     security: Vec<Map<Type, Vec<String>>>,
+    client: bool,
 }
 
 #[derive(FromAttr)]
@@ -32,6 +34,14 @@ struct MethodAttr {
     middleware: Option<Path>,
     tags: Vec<Type>,
     security: Vec<Map<Type, Vec<String>>>,
+    examples: Vec<Type>,
+    #[attribute(default = true)]
+    client: bool,
+    operation_id: Option<String>,
+    deprecated: FlagOrValue<LitStr>,
+    sunset: Option<LitStr>,
+    timeout: Option<LitStr>,
+    external_docs: Option<String>,
 }
 
 fn default_paths() -> Vec<Expr> {
@@ -54,6 +64,7 @@ pub(crate) fn generate(
         middleware,
         tags,
         security,
+        client,
     } = controller_attr;
 
     let paths = if !paths.is_empty() {
@@ -66,6 +77,7 @@ pub(crate) fn generate(
 
     let mut errors = Vec::new();
     let mut insert_routes_impls = Vec::new();
+    let mut client_methods = Vec::new();
 
     item_impl.items.iter_mut().for_each(|impl_item| {
         let f = match impl_item {
@@ -90,10 +102,14 @@ pub(crate) fn generate(
             &tags,
             &security,
             self_ty,
+            client,
             f,
             method_attr,
         ) {
-            Ok(insert_routes_impl) => insert_routes_impls.push(insert_routes_impl),
+            Ok((insert_routes_impl, client_method)) => {
+                insert_routes_impls.push(insert_routes_impl);
+                client_methods.extend(client_method);
+            }
             Err(e) => errors.push(e),
         }
     });
@@ -105,6 +121,12 @@ pub(crate) fn generate(
         return Err(e);
     }
 
+    let client = if client {
+        generate_client(self_ty, &client_methods)?
+    } else {
+        TokenStream::new()
+    };
+
     #[cfg(not(feature = "auto-register"))]
     let auto_register = TokenStream::new();
 
@@ -137,7 +159,7 @@ pub(crate) fn generate(
         # use predawn::__internal::indexmap::IndexMap;
         # use predawn::http::Method;
         # use predawn::__internal::rudi::Context;
-        # use predawn::openapi::{SecurityScheme, Operation, Tag, Schema};
+        # use predawn::openapi::{Example, SecurityScheme, Operation, Tag, Schema};
 
         impl Controller for #self_ty {
             fn insert_routes(
@@ -149,6 +171,7 @@ pub(crate) fn generate(
                 schemas_in_progress: &mut Vec<String>,
                 security_schemes: &mut BTreeMap<&'static str, (&'static str, SecurityScheme)>,
                 tags: &mut BTreeMap<&'static str, (&'static str, Tag)>,
+                examples: &mut BTreeMap<&'static str, (&'static str, Example)>,
             ) {
                 let this = self;
 
@@ -159,20 +182,65 @@ pub(crate) fn generate(
         #item_impl
 
         #auto_register
+
+        #client
     };
 
     Ok(expand)
 }
 
+fn generate_client(self_ty: &Type, client_methods: &[TokenStream]) -> syn::Result<TokenStream> {
+    let self_ident = match self_ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last().map(|s| &s.ident),
+        _ => None,
+    };
+
+    let Some(self_ident) = self_ident else {
+        return Err(syn::Error::new(
+            self_ty.span(),
+            "`client` is only supported for controllers named by a path",
+        ));
+    };
+
+    let client_ident = format_ident!("{}Client", self_ident);
+    let doc = format!(" A client of the endpoints of [`{self_ident}`].");
+
+    let expand = quote_use! {
+        # use predawn::client::Client;
+
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #client_ident {
+            client: Client,
+        }
+
+        impl #client_ident {
+            pub fn new(client: Client) -> Self {
+                Self { client }
+            }
+
+            pub fn client(&self) -> &Client {
+                &self.client
+            }
+
+            #(#client_methods)*
+        }
+    };
+
+    Ok(expand)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn generate_single_fn_impl<'a>(
     controller_paths: &'a [Expr],
     controller_middeleware: Option<&'a Path>,
     controller_tags: &'a [Type],
     controller_security: &'a [Map<Type, Vec<String>>],
     self_ty: &'a Type,
+    controller_client: bool,
     f: &'a mut ImplItemFn,
     method_attr: MethodAttr,
-) -> syn::Result<TokenStream> {
+) -> syn::Result<(TokenStream, Option<TokenStream>)> {
     if f.sig.asyncness.is_none() {
         return Err(syn::Error::new(f.sig.span(), "the method must be async"));
     }
@@ -183,6 +251,13 @@ fn generate_single_fn_impl<'a>(
         middleware: method_middleware,
         tags: method_tags,
         security: method_security,
+        examples,
+        client: method_client,
+        operation_id,
+        deprecated,
+        sunset,
+        timeout,
+        external_docs,
     } = method_attr;
 
     let method_paths = if !paths.is_empty() {
@@ -225,19 +300,22 @@ fn generate_single_fn_impl<'a>(
     }
 
     let mut arg_idents = Vec::new();
+    let mut client_params = Vec::new();
 
     let mut heads_from_request_head = Vec::new();
     let mut heads_parameters = Vec::new();
     let mut heads_error_responses = Vec::new();
 
     args.filter_map(|arg| match arg {
-        FnArg::Typed(PatType { ty, .. }) => Some(ty),
+        FnArg::Typed(PatType { pat, ty, .. }) => Some((pat, ty)),
         _ => None,
     })
     .enumerate()
-    .for_each(|(idx, ty)| {
+    .for_each(|(idx, (pat, ty))| {
         let arg_ident = format_ident!("a{}", idx);
 
+        client_params.push((client_param_ident(pat, idx), ty.clone()));
+
         let from_request_head = quote_use! {
             # use predawn::from_request::FromRequestHead;
 
@@ -255,6 +333,10 @@ fn generate_single_fn_impl<'a>(
                     .parameters
                     .extend(transform_parameters(parameters));
             }
+
+            operation
+                .extensions
+                .extend(<#ty as ApiRequestHead>::extensions(schemas, schemas_in_progress));
         };
 
         let error_responses = quote_use! {
@@ -274,19 +356,37 @@ fn generate_single_fn_impl<'a>(
     });
 
     let last_from_request;
+    let last_body_limit;
     let last_parameters;
     let last_request_body;
     let last_error_responses;
 
-    if let Some(FnArg::Typed(PatType { attrs, ty, .. })) = last {
+    if let Some(FnArg::Typed(PatType { attrs, pat, ty, .. })) = last {
         arg_idents.push(format_ident!("last"));
 
+        client_params.push((client_param_ident(pat, client_params.len()), ty.clone()));
+
         last_from_request = quote_use! {
             # use predawn::from_request::FromRequest;
 
             let last = <#ty as FromRequest<_>>::from_request(&mut head, body).await?;
         };
 
+        last_body_limit = quote_use! {
+            # use predawn::from_request::FromRequest;
+            # use predawn::request::BodyLimit;
+
+            let req = {
+                let mut req = req;
+
+                if let Some(limit) = <#ty as FromRequest<_>>::BODY_LIMIT {
+                    *req.body_limit() = BodyLimit(limit);
+                }
+
+                req
+            };
+        };
+
         last_parameters = quote_use! {
             # use predawn::api_request::ApiRequest;
             # use predawn::openapi::transform_parameters;
@@ -296,6 +396,10 @@ fn generate_single_fn_impl<'a>(
                     .parameters
                     .extend(transform_parameters(parameters));
             }
+
+            operation
+                .extensions
+                .extend(<#ty as ApiRequest<_>>::extensions(schemas, schemas_in_progress));
         };
 
         let description = predawn_macro_core::util::extract_description(attrs);
@@ -308,6 +412,33 @@ fn generate_single_fn_impl<'a>(
 
         predawn_macro_core::util::remove_description(attrs);
 
+        let insert_examples = examples.iter().map(|ty| {
+            quote_use! {
+                # use core::any::type_name;
+                # use std::format;
+                # use predawn::Example;
+                # use predawn::openapi::{ReferenceOr, insert_example};
+
+                let example_type_name = type_name::<#ty>();
+                let example_name = <#ty as Example>::NAME;
+
+                if !examples.contains_key(example_type_name) {
+                    examples.insert(
+                        example_type_name,
+                        (example_name, <#ty as Example>::create())
+                    );
+                }
+
+                insert_example(
+                    &mut request_body.content,
+                    example_name,
+                    ReferenceOr::Reference {
+                        reference: format!("#/components/examples/{}", example_name),
+                    },
+                );
+            }
+        });
+
         last_request_body = quote_use! {
             # use predawn::api_request::ApiRequest;
             # use predawn::openapi::transform_request_body;
@@ -316,6 +447,8 @@ fn generate_single_fn_impl<'a>(
 
             if let Some(request_body) = request_body.as_mut() {
                 request_body.description = #description;
+
+                #({#insert_examples})*
             }
 
             operation.request_body = transform_request_body(request_body);
@@ -332,7 +465,15 @@ fn generate_single_fn_impl<'a>(
             );
         };
     } else {
+        if let Some(ty) = examples.first() {
+            return Err(syn::Error::new(
+                ty.span(),
+                "`examples` can only be used on endpoints with a request body",
+            ));
+        }
+
         last_from_request = TokenStream::new();
+        last_body_limit = TokenStream::new();
         last_parameters = TokenStream::new();
         last_request_body = TokenStream::new();
         last_error_responses = TokenStream::new();
@@ -377,6 +518,77 @@ fn generate_single_fn_impl<'a>(
         }
     });
 
+    // a bare `deprecated` only marks the operation, the headers need the date of the deprecation
+    let deprecation = match (&deprecated, &sunset) {
+        (FlagOrValue::None, None) | (FlagOrValue::Flag { .. }, None) => None,
+        (FlagOrValue::None, Some(sunset)) => {
+            return Err(syn::Error::new(
+                sunset.span(),
+                "`sunset` can only be used on deprecated endpoints",
+            ));
+        }
+        (FlagOrValue::Flag { path }, Some(_)) => {
+            return Err(syn::Error::new(
+                *path,
+                "`sunset` requires the date of the deprecation, like `deprecated = \"Sun, 01 Jun 2025 00:00:00 GMT\"`",
+            ));
+        }
+        (FlagOrValue::Value { value, .. }, _) => {
+            let date = system_time(value)?;
+
+            Some(quote_use! {
+                # use predawn::middleware::Deprecation;
+
+                Deprecation::new(#date)
+            })
+        }
+    };
+
+    let add_deprecation_middleware = match deprecation {
+        Some(deprecation) => {
+            let set_sunset = match &sunset {
+                Some(sunset) => {
+                    let sunset = system_time(sunset)?;
+                    quote!(.sunset(#sunset))
+                }
+                None => TokenStream::new(),
+            };
+
+            quote_use! {
+                # use predawn::handler::assert_handler;
+                # use predawn::middleware::Middleware;
+
+                let handler = Middleware::transform(#deprecation #set_sunset, handler);
+                assert_handler(&handler);
+            }
+        }
+        None => TokenStream::new(),
+    };
+
+    let timeout = timeout.as_ref().map(duration).transpose()?;
+
+    let add_timeout_middleware = timeout.map(|timeout| {
+        quote_use! {
+            # use predawn::handler::assert_handler;
+            # use predawn::middleware::{Middleware, Timeout};
+
+            let handler = Middleware::transform(Timeout::new(#timeout), handler);
+            assert_handler(&handler);
+        }
+    });
+
+    let timeout_error_responses = add_timeout_middleware.is_some().then(|| {
+        quote_use! {
+            # use predawn::response_error::{ResponseError, TimeoutError};
+            # use predawn::openapi::merge_responses;
+
+            merge_responses(
+                &mut responses,
+                <TimeoutError as ResponseError>::responses(schemas, schemas_in_progress),
+            );
+        }
+    });
+
     let add_method_middleware = method_middleware.map(|middleware| {
         quote_use! {
             # use predawn::handler::assert_handler;
@@ -486,6 +698,52 @@ fn generate_single_fn_impl<'a>(
         }
     };
 
+    let add_operation_id = match &operation_id {
+        Some(operation_id) => quote_use! {
+            # use std::string::ToString;
+
+            operation.operation_id = Some(ToString::to_string(#operation_id));
+        },
+        None => quote_use! {
+            # use core::stringify;
+            # use std::format;
+            # use std::any::type_name;
+
+            operation.operation_id = Some(format!("{}::{}", type_name::<#self_ty>(), stringify!(#fn_name)));
+        },
+    };
+
+    let add_external_docs = match &external_docs {
+        Some(url) => quote_use! {
+            # use core::default::Default;
+            # use std::string::ToString;
+            # use predawn::openapi::ExternalDocumentation;
+
+            operation.external_docs = Some(ExternalDocumentation {
+                description: None,
+                url: ToString::to_string(#url),
+                extensions: Default::default(),
+            });
+        },
+        None => TokenStream::new(),
+    };
+
+    let add_deprecation = match deprecated {
+        FlagOrValue::None => TokenStream::new(),
+        FlagOrValue::Flag { .. } => quote! {
+            operation.deprecated = true;
+        },
+        FlagOrValue::Value { .. } => {
+            let sunset = sunset.is_some();
+
+            quote_use! {
+                # use predawn::openapi::deprecate_operation;
+
+                deprecate_operation(&mut operation, #sunset, schemas, schemas_in_progress);
+            }
+        }
+    };
+
     let create_handler = quote_use! {
         # use std::sync::Arc;
         # use predawn::handler::{DynHandler, handler_fn};
@@ -497,6 +755,8 @@ fn generate_single_fn_impl<'a>(
                 let this = this.clone();
 
                 async move {
+                    #last_body_limit
+
                     #[allow(unused_variables, unused_mut)]
                     let (mut head, body) = req.split();
 
@@ -510,15 +770,14 @@ fn generate_single_fn_impl<'a>(
 
             #add_method_middleware
             #add_controller_middleware
+            #add_deprecation_middleware
+            #add_timeout_middleware
 
             DynHandler::new(handler)
         };
     };
 
     let create_operation = quote_use! {
-        # use core::stringify;
-        # use std::format;
-        # use std::any::type_name;
         # use std::collections::BTreeMap;
         # use predawn::openapi::Operation;
         # use predawn::openapi::transform_responses;
@@ -547,7 +806,12 @@ fn generate_single_fn_impl<'a>(
 
         #[doc = "add operation_id"]
         {
-            operation.operation_id = Some(format!("{}::{}", type_name::<#self_ty>(), stringify!(#fn_name)));
+            #add_operation_id
+        }
+
+        #[doc = "add external_docs"]
+        {
+            #add_external_docs
         }
 
         #[doc = "add request_body"]
@@ -569,6 +833,11 @@ fn generate_single_fn_impl<'a>(
             #last_error_responses
         }
 
+        #[doc = "add response from timeout error"]
+        {
+            #timeout_error_responses
+        }
+
         #[doc = "add response from write response error"]
         {
             #return_error_responses
@@ -580,12 +849,22 @@ fn generate_single_fn_impl<'a>(
         }
 
         operation.responses.responses.extend(transform_responses(responses));
+
+        #[doc = "add deprecation"]
+        {
+            #add_deprecation
+        }
     };
 
     let mut insert_fn_into_multi_path = Vec::new();
 
+    let mut path_count = 0;
+
     controller_paths.iter().for_each(|controller_path| {
         method_paths.iter().for_each(|method_path| {
+            let path_idx = path_count;
+            path_count += 1;
+
             let extract_single_path_map = quote_use! {
                 # use core::convert::AsRef;
                 # use std::clone::Clone;
@@ -600,16 +879,42 @@ fn generate_single_fn_impl<'a>(
                 let operations = paths.entry(Clone::clone(&path)).or_default();
             };
 
-            let insert_fn_into_multi_method = methods.iter().map(|method| {
-                let uppercase_method = method.as_uppercase_ident();
+            let insert_fn_into_multi_method =
+                methods.iter().enumerate().map(|(method_idx, method)| {
+                    let uppercase_method = method.as_uppercase_ident();
 
-                quote_use! {
-                    # use predawn::http::Method;
+                    // only the first path and method keep the operation id as is, to keep it unique
+                    let operation = if path_idx == 0 && method_idx == 0 {
+                        quote!(operation.clone())
+                    } else {
+                        let mut suffix = format!("_{}", method.to_string().to_lowercase());
 
-                    handlers.push((Method::#uppercase_method, #fn_name.clone()));
-                    operations.push((Method::#uppercase_method, operation.clone()));
-                }
-            });
+                        if path_idx > 0 {
+                            suffix.push_str(&format!("_{path_idx}"));
+                        }
+
+                        quote_use! {
+                            # use std::string::String;
+
+                            {
+                                let mut operation = operation.clone();
+
+                                if let Some(operation_id) = &mut operation.operation_id {
+                                    String::push_str(operation_id, #suffix);
+                                }
+
+                                operation
+                            }
+                        }
+                    };
+
+                    quote_use! {
+                        # use predawn::http::Method;
+
+                        handlers.push((Method::#uppercase_method, #fn_name.clone()));
+                        operations.push((Method::#uppercase_method, #operation));
+                    }
+                });
 
             let insert_fn_into_single_path = quote! {
                 #extract_single_path_map
@@ -620,6 +925,47 @@ fn generate_single_fn_impl<'a>(
         });
     });
 
+    let client_method = (controller_client && method_client).then(|| {
+        let summary = (!summary.is_empty()).then(|| {
+            let summary = format!(" {summary}");
+            quote!(#[doc = #summary])
+        });
+
+        let controller_path = &controller_paths[0];
+        let method_path = &method_paths[0];
+        let method = methods[0].as_uppercase_ident();
+
+        let request = Ident::new("request", Span::mixed_site());
+        let (param_idents, param_tys): (Vec<_>, Vec<_>) = client_params.into_iter().unzip();
+
+        quote_use! {
+            # use core::convert::AsRef;
+            # use core::result::Result;
+            # use predawn::client::{ClientArg, ClientError, ClientRequest, FromClientResponse};
+            # use predawn::http::Method;
+            # use predawn::normalized_path::NormalizedPath;
+
+            #summary
+            pub async fn #fn_name(
+                &self,
+                #(#param_idents: #param_tys,)*
+            ) -> Result<<#return_ty as FromClientResponse>::Output, ClientError> {
+                #[allow(unused_mut)]
+                let mut #request = ClientRequest::new(
+                    Method::#method,
+                    NormalizedPath::join(
+                        NormalizedPath::new(AsRef::<str>::as_ref(#controller_path)),
+                        NormalizedPath::new(AsRef::<str>::as_ref(#method_path)),
+                    ),
+                );
+
+                #(ClientArg::apply(#param_idents, &mut #request)?;)*
+
+                self.client.send::<#return_ty>(#request).await
+            }
+        }
+    });
+
     let label: Label = syn::parse_str(&format!("'{}:", fn_name))?;
 
     let expand = quote! {
@@ -635,5 +981,58 @@ fn generate_single_fn_impl<'a>(
         }
     };
 
-    Ok(expand)
+    Ok((expand, client_method))
+}
+
+/// Names a client method parameter after the binding of the argument, like `id` in `Path(id): Path<u32>`.
+fn client_param_ident(pat: &Pat, idx: usize) -> Ident {
+    match pat {
+        Pat::Ident(PatIdent { ident, .. }) => return ident.clone(),
+        Pat::TupleStruct(PatTupleStruct { elems, .. }) if elems.len() == 1 => {
+            if let Some(Pat::Ident(PatIdent { ident, .. })) = elems.first() {
+                return ident.clone();
+            }
+        }
+        _ => {}
+    }
+
+    format_ident!("arg{}", idx)
+}
+
+/// Converts an HTTP-date like `"Sun, 06 Nov 1994 08:49:37 GMT"` into a `SystemTime` expression.
+fn duration(duration: &LitStr) -> syn::Result<TokenStream> {
+    let duration = duration_str::parse(duration.value()).map_err(|_| {
+        syn::Error::new(
+            duration.span(),
+            "expected a duration, like `30s` or `1m 30s`",
+        )
+    })?;
+
+    let secs = duration.as_secs();
+    let nanos = duration.subsec_nanos();
+
+    Ok(quote_use! {
+        # use std::time::Duration;
+
+        Duration::new(#secs, #nanos)
+    })
+}
+
+fn system_time(date: &LitStr) -> syn::Result<TokenStream> {
+    let secs = httpdate::parse_http_date(&date.value())
+        .ok()
+        .and_then(|date| date.duration_since(std::time::UNIX_EPOCH).ok())
+        .ok_or_else(|| {
+            syn::Error::new(
+                date.span(),
+                "expected an HTTP-date, like `Sun, 06 Nov 1994 08:49:37 GMT`",
+            )
+        })?
+        .as_secs();
+
+    Ok(quote_use! {
+        # use std::time::{Duration, UNIX_EPOCH};
+
+        UNIX_EPOCH + Duration::from_secs(#secs)
+    })
 }
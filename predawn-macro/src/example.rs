@@ -0,0 +1,89 @@
+use from_attr::{AttrsValue, FromAttr};
+use proc_macro2::TokenStream;
+use quote::quote;
+use quote_use::quote_use;
+use syn::{DeriveInput, Expr};
+
+use crate::util;
+
+#[derive(FromAttr, Default)]
+#[attribute(idents = [example])]
+struct TypeAttr {
+    rename: Option<String>,
+    value: Option<Expr>,
+    external_value: Option<String>,
+}
+
+pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
+    let DeriveInput { attrs, ident, .. } = input;
+
+    let TypeAttr {
+        rename,
+        value,
+        external_value,
+    } = match TypeAttr::from_attributes(&attrs) {
+        Ok(Some(AttrsValue {
+            value: type_attr, ..
+        })) => type_attr,
+        Ok(None) => Default::default(),
+        Err(AttrsValue { value: e, .. }) => return Err(e),
+    };
+
+    let (value, external_value) = match (value, external_value) {
+        (Some(value), None) => {
+            let json_value = predawn_macro_core::util::generate_example_json_value(&value);
+            (quote! { Some(#json_value) }, quote! { None })
+        }
+        (None, Some(external_value)) => (
+            quote! { None },
+            quote! { Some(ToString::to_string(#external_value)) },
+        ),
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "exactly one of `#[example(value = ...)]` and `#[example(external_value = \"...\")]` is required",
+            ));
+        }
+    };
+
+    let ident_str = rename.unwrap_or_else(|| ident.to_string());
+
+    let (summary, description) = util::extract_summary_and_description(&attrs);
+
+    let summary = if summary.is_empty() {
+        quote! { None }
+    } else {
+        let summary = predawn_macro_core::util::generate_string_expr(&summary);
+        quote! { Some(#summary) }
+    };
+
+    let description = if description.is_empty() {
+        quote! { None }
+    } else {
+        let description = predawn_macro_core::util::generate_string_expr(&description);
+        quote! { Some(#description) }
+    };
+
+    let expand = quote_use! {
+        # use core::default::Default;
+        # use std::string::ToString;
+        # use predawn::Example;
+        # use predawn::openapi;
+
+        impl Example for #ident {
+            const NAME: &'static str = #ident_str;
+
+            fn create() -> openapi::Example {
+                openapi::Example {
+                    summary: #summary,
+                    description: #description,
+                    value: #value,
+                    external_value: #external_value,
+                    extensions: Default::default(),
+                }
+            }
+        }
+    };
+
+    Ok(expand)
+}
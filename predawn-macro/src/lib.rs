@@ -1,4 +1,5 @@
 mod controller;
+mod example;
 mod method;
 mod multi_request_media_type;
 mod multi_response;
@@ -67,7 +68,7 @@ pub fn single_response(input: TokenStream) -> TokenStream {
 }
 
 #[doc = include_str!("docs/multi_response.md")]
-#[proc_macro_derive(MultiResponse, attributes(multi_response, status))]
+#[proc_macro_derive(MultiResponse, attributes(multi_response, status, examples))]
 pub fn multi_response(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -77,7 +78,7 @@ pub fn multi_response(input: TokenStream) -> TokenStream {
 }
 
 #[doc = include_str!("docs/multipart.md")]
-#[proc_macro_derive(Multipart, attributes(schema))]
+#[proc_macro_derive(Multipart, attributes(schema, multipart))]
 pub fn multipart(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -96,6 +97,16 @@ pub fn tag(input: TokenStream) -> TokenStream {
         .into()
 }
 
+#[doc = include_str!("docs/example.md")]
+#[proc_macro_derive(Example, attributes(example))]
+pub fn example(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    example::generate(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
 #[doc = include_str!("docs/security_scheme.md")]
 #[proc_macro_derive(SecurityScheme, attributes(api_key, http))]
 pub fn security_scheme(input: TokenStream) -> TokenStream {
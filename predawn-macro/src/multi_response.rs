@@ -3,8 +3,12 @@ use std::collections::HashSet;
 use from_attr::{AttrsValue, FromAttr};
 use http::StatusCode;
 use proc_macro2::TokenStream;
+use quote::quote;
 use quote_use::quote_use;
-use syn::{Attribute, DeriveInput, Expr, ExprLit, Ident, Lit, Type, Variant, spanned::Spanned};
+use syn::{
+    Attribute, DeriveInput, Expr, ExprLit, Ident, Lit, Token, Type, Variant,
+    punctuated::Punctuated, spanned::Spanned,
+};
 
 use crate::util;
 
@@ -127,16 +131,25 @@ fn handle_single_variant<'a>(
         return Err(e);
     };
 
+    let examples = extract_examples(&attrs)?;
+
     let ty = util::extract_single_unnamed_field_type_from_variant(fields, variant_span)?;
 
+    let insert_examples =
+        crate::single_response::generate_insert_examples(quote!(&mut response.content), &examples);
+
     let responses_body = quote_use! {
         # use predawn::SingleResponse;
         # use predawn::http::StatusCode;
 
-        map.insert(
-            StatusCode::from_u16(#status_code).unwrap(),
-            <#ty as SingleResponse>::response(schemas, schemas_in_progress),
-        );
+        {
+            #[allow(unused_mut)]
+            let mut response = <#ty as SingleResponse>::response(schemas, schemas_in_progress);
+
+            #insert_examples
+
+            map.insert(StatusCode::from_u16(#status_code).unwrap(), response);
+        }
     };
 
     let into_response_arm = quote_use! {
@@ -222,3 +235,32 @@ fn extract_status_code<'a>(
 
     Ok(found)
 }
+
+fn extract_examples(attrs: &[Attribute]) -> syn::Result<Vec<Type>> {
+    let mut errors = Vec::new();
+    let mut examples = Vec::new();
+
+    for attr in attrs {
+        if !attr.path().is_ident("examples") {
+            continue;
+        }
+
+        let types = attr.meta.require_list().and_then(|meta_list| {
+            meta_list.parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)
+        });
+
+        match types {
+            Ok(types) => examples.extend(types),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if let Some(e) = errors.into_iter().reduce(|mut a, b| {
+        a.combine(b);
+        a
+    }) {
+        return Err(e);
+    }
+
+    Ok(examples)
+}
@@ -3,10 +3,23 @@ use predawn_macro_core::{SchemaAttr, SerdeAttr};
 use proc_macro2::TokenStream;
 use quote::quote;
 use quote_use::quote_use;
-use syn::{DeriveInput, Field, Ident};
+use syn::{DeriveInput, Expr, Field, Ident};
 
 use crate::util;
 
+#[derive(FromAttr, Default)]
+#[attribute(idents = [multipart])]
+struct ContainerAttr {
+    body_limit: Option<Expr>,
+}
+
+#[derive(FromAttr, Default)]
+#[attribute(idents = [multipart])]
+struct FieldAttr {
+    max_size: Option<Expr>,
+    content_types: Vec<String>,
+}
+
 pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let DeriveInput {
         attrs,
@@ -16,22 +29,33 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
         ..
     } = input;
 
+    let ContainerAttr { body_limit } = match ContainerAttr::from_attributes(&attrs) {
+        Ok(Some(AttrsValue {
+            value: container_attr,
+            ..
+        })) => container_attr,
+        Ok(None) => Default::default(),
+        Err(AttrsValue { value: e, .. }) => return Err(e),
+    };
+
     let named = util::extract_named_struct_fields(data, "Multipart")?;
 
     let mut struct_field_idents = Vec::new();
     let mut define_vars = Vec::new();
     let mut parse_fields = Vec::new();
     let mut extract_vars = Vec::new();
+    let mut size_limits = Vec::new();
     let mut errors = Vec::new();
 
     named
         .into_iter()
         .for_each(|field| match generate_single_field(field) {
-            Ok((struct_field, define_var, parse_field, extract_var)) => {
+            Ok((struct_field, define_var, parse_field, extract_var, size_limit)) => {
                 struct_field_idents.push(struct_field);
                 define_vars.push(define_var);
                 parse_fields.push(parse_field);
                 extract_vars.push(extract_var);
+                size_limits.extend(size_limit);
             }
             Err(e) => errors.push(e),
         });
@@ -53,6 +77,14 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
         quote! { Some(#description) }
     };
 
+    let body_limit = body_limit.map(|limit| {
+        quote_use! {
+            # use core::option::Option::{self, Some};
+
+            const BODY_LIMIT: Option<usize> = Some((#limit) as usize);
+        }
+    });
+
     let expand = quote_use! {
         # use core::default::Default;
         # use std::vec::Vec;
@@ -70,8 +102,10 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
         impl #impl_generics FromRequest for #ident #ty_generics #where_clause {
             type Error = MultipartError;
 
+            #body_limit
+
             async fn from_request(head: &mut Head, body: RequestBody) -> Result<Self, Self::Error> {
-                let mut multipart = <Multipart as FromRequest<_>>::from_request(head, body).await?;
+                let mut multipart = Multipart::with_size_limits(head, body, &[#(#size_limits),*])?;
 
                 #(#define_vars)*
 
@@ -128,21 +162,28 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
 
 fn generate_single_field(
     field: Field,
-) -> syn::Result<(Ident, TokenStream, TokenStream, TokenStream)> {
+) -> syn::Result<(
+    Ident,
+    TokenStream,
+    TokenStream,
+    TokenStream,
+    Option<TokenStream>,
+)> {
     let Field {
         attrs, ident, ty, ..
     } = field;
 
     let SerdeAttr {
         rename: serde_rename,
-        flatten: _,
+        aliases,
         default: serde_default,
+        ..
     } = SerdeAttr::new(&attrs);
 
     let SchemaAttr {
         rename: schema_rename,
-        flatten: _,
         default: schema_default,
+        ..
     } = match SchemaAttr::from_attributes(&attrs) {
         Ok(Some(AttrsValue {
             value: field_attr, ..
@@ -151,6 +192,17 @@ fn generate_single_field(
         Err(AttrsValue { value: e, .. }) => return Err(e),
     };
 
+    let FieldAttr {
+        max_size,
+        content_types,
+    } = match FieldAttr::from_attributes(&attrs) {
+        Ok(Some(AttrsValue {
+            value: field_attr, ..
+        })) => field_attr,
+        Ok(None) => Default::default(),
+        Err(AttrsValue { value: e, .. }) => return Err(e),
+    };
+
     let struct_field_ident = ident.expect("unreachable: named field must have an identifier");
 
     let multipart_field = schema_rename
@@ -172,11 +224,29 @@ fn generate_single_field(
         let mut #struct_field_ident = <#ty as ParseField>::default_holder(#multipart_field);
     };
 
+    let size_limit = max_size.map(|max_size| {
+        quote! { (#multipart_field, (#max_size) as u64) #(, (#aliases, (#max_size) as u64))* }
+    });
+
+    let check_content_type = (!content_types.is_empty()).then(|| {
+        quote_use! {
+            # use predawn::extract::multipart::check_field_content_type;
+
+            check_field_content_type(&field, #multipart_field, &[#(#content_types),*])?;
+        }
+    });
+
     let parse_field = quote_use! {
-        # use predawn::extract::multipart::ParseField;
+        # use predawn::extract::multipart::{FieldContext, ParseField};
+
+        if matches!(field.name(), Some(#multipart_field #(| #aliases)*)) {
+            #check_content_type
+            #struct_field_ident = <#ty as ParseField>::parse_field_in(#struct_field_ident, field, #multipart_field, FieldContext::new(head)).await?;
+
+            if <#ty as ParseField>::STREAMING {
+                break;
+            }
 
-        if field.name() == Some(#multipart_field) {
-            #struct_field_ident = <#ty as ParseField>::parse_field(#struct_field_ident, field, #multipart_field).await?;
             continue;
         }
     };
@@ -191,5 +261,11 @@ fn generate_single_field(
         };
     };
 
-    Ok((struct_field_ident, define_var, parse_field, extract_var))
+    Ok((
+        struct_field_ident,
+        define_var,
+        parse_field,
+        extract_var,
+        size_limit,
+    ))
 }
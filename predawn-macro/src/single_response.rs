@@ -17,6 +17,7 @@ use crate::util;
 #[attribute(idents = [single_response])]
 struct StructAttr {
     status: Option<LitInt>,
+    examples: Vec<Type>,
 }
 
 pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
@@ -30,6 +31,7 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
 
     let StructAttr {
         status: status_code,
+        examples,
     } = match StructAttr::from_attributes(&attrs) {
         Ok(Some(AttrsValue {
             value: struct_attr, ..
@@ -59,7 +61,10 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let fields = match fields {
         Fields::Named(FieldsNamed { named, .. }) if !named.is_empty() => named,
         Fields::Unnamed(FieldsUnnamed { unnamed, .. }) if !unnamed.is_empty() => unnamed,
-        _ => return Ok(generate_unit(&ident, status_code_value)),
+        _ => {
+            check_no_examples(&examples)?;
+            return Ok(generate_unit(&ident, status_code_value));
+        }
     };
 
     let fields_len = fields.len();
@@ -105,6 +110,8 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
                 return Err(e);
             }
 
+            check_no_examples(&examples)?;
+
             let expand = generate_only_headers(
                 &generics,
                 &ident,
@@ -126,6 +133,8 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
                 return Err(e);
             }
 
+            let insert_examples = generate_insert_examples(quote!(&mut content), &examples);
+
             let expand = if insert_api_headers.is_empty() {
                 generate_only_body(
                     &generics,
@@ -134,6 +143,7 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
                     description,
                     ty,
                     into_response_arg,
+                    insert_examples,
                 )
             } else {
                 generate_body_and_headers(
@@ -145,6 +155,7 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
                     insert_http_headers,
                     ty,
                     into_response_arg,
+                    insert_examples,
                 )
             };
 
@@ -276,6 +287,7 @@ fn generate_only_body(
     description: Expr,
     body_type: Type,
     into_response_arg: Expr,
+    insert_examples: TokenStream,
 ) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
@@ -294,10 +306,14 @@ fn generate_only_body(
             const STATUS_CODE: u16 = #status_code_value;
 
             fn response(schemas: &mut BTreeMap<String, Schema>, schemas_in_progress: &mut Vec<String>) -> openapi::Response {
+                let mut content = <#body_type as MultiResponseMediaType>::content(schemas, schemas_in_progress);
+
+                #insert_examples
+
                 openapi::Response {
                     description: #description,
                     headers: Default::default(),
-                    content: <#body_type as MultiResponseMediaType>::content(schemas, schemas_in_progress),
+                    content,
                     links: Default::default(),
                     extensions: Default::default(),
                 }
@@ -332,6 +348,7 @@ fn generate_body_and_headers(
     insert_http_headers: Vec<TokenStream>,
     body_type: Type,
     into_response_arg: Expr,
+    insert_examples: TokenStream,
 ) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
@@ -359,10 +376,14 @@ fn generate_body_and_headers(
 
                 #(#insert_api_headers)*
 
+                let mut content = <#body_type as MultiResponseMediaType>::content(schemas, schemas_in_progress);
+
+                #insert_examples
+
                 openapi::Response {
                     description: #description,
                     headers,
-                    content: <#body_type as MultiResponseMediaType>::content(schemas, schemas_in_progress),
+                    content,
                     links: Default::default(),
                     extensions: Default::default(),
                 }
@@ -393,6 +414,36 @@ fn generate_body_and_headers(
     }
 }
 
+fn check_no_examples(examples: &[Type]) -> syn::Result<()> {
+    match examples.first() {
+        Some(ty) => Err(syn::Error::new(
+            ty.span(),
+            "`examples` can only be used on responses with a body",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Examples of responses are inlined into `content`, an expression of type `&mut IndexMap<String, MediaType>`.
+pub(crate) fn generate_insert_examples(content: TokenStream, examples: &[Type]) -> TokenStream {
+    let insert_examples = examples.iter().map(|ty| {
+        quote_use! {
+            # use predawn::Example;
+            # use predawn::openapi::{ReferenceOr, insert_example};
+
+            insert_example(
+                #content,
+                <#ty as Example>::NAME,
+                ReferenceOr::Item(<#ty as Example>::create()),
+            );
+        }
+    });
+
+    quote! {
+        #(#insert_examples)*
+    }
+}
+
 fn handle_single_field(
     field: Field,
     idx: usize,
@@ -1,34 +1,60 @@
-use from_attr::{AttrsValue, FromAttr};
-use predawn_macro_core::{SchemaAttr, SerdeAttr};
+use from_attr::{AttrsValue, FlagOrValue, FromAttr};
+use predawn_macro_core::{Constraints, RenameRule, SchemaAttr, SerdeAttr};
 use proc_macro2::TokenStream;
 use quote::quote;
 use quote_use::quote_use;
-use syn::{DeriveInput, Field};
+use syn::{DeriveInput, Field, Type};
 
 use crate::util;
 
 pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let DeriveInput {
+        attrs,
         ident,
         generics,
         data,
         ..
     } = input;
 
-    let named = util::extract_named_struct_fields(data, "ToParameters")?;
+    let SerdeAttr {
+        default,
+        rename_all,
+        transparent,
+        ..
+    } = SerdeAttr::new(&attrs);
+
+    let container_default = !matches!(default, FlagOrValue::None);
+
+    // parameters are only deserialized
+    let named = util::extract_named_struct_fields(data, "ToParameters")?
+        .into_iter()
+        .filter(|field| !SerdeAttr::new(&field.attrs).skip_deserializing)
+        .collect::<Vec<_>>();
+
+    // the only field of a transparent struct holds all the parameters
+    if transparent && named.len() != 1 {
+        return Err(syn::Error::new(
+            ident.span(),
+            "`#[serde(transparent)]` requires exactly one field that is not skipped",
+        ));
+    }
 
     let fields_len = named.len();
     let mut push_params = Vec::new();
+    let mut validate_fields = Vec::new();
+    let mut nested_types = Vec::new();
     let mut errors = Vec::new();
 
-    named
-        .into_iter()
-        .for_each(|field| match generate_single_field(field) {
-            Ok(push_param) => {
+    named.into_iter().for_each(|field| {
+        match generate_single_field(field, rename_all, container_default, transparent) {
+            Ok((push_param, validate_field, nested_type)) => {
                 push_params.push(push_param);
+                validate_fields.extend(validate_field);
+                nested_types.extend(nested_type);
             }
             Err(e) => errors.push(e),
-        });
+        }
+    });
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
         a.combine(b);
@@ -37,6 +63,20 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
         return Err(e);
     }
 
+    // a method of `ToParameters` rather than a `Validate` impl, which `#[derive(ToSchema)]` generates
+    let validate_parameters = (!validate_fields.is_empty()).then(|| {
+        quote_use! {
+            # use predawn::validate::ValidationErrors;
+
+            fn validate_parameters(&self, path: &str, errors: &mut ValidationErrors) {
+                #(#validate_fields)*
+            }
+        }
+    });
+
+    let mut generics = generics;
+    predawn_macro_core::add_validate_bounds(&mut generics, &nested_types);
+
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let expand = quote_use! {
@@ -51,27 +91,48 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
                 #(#push_params)*
                 params
             }
+
+            #validate_parameters
         }
     };
 
     Ok(expand)
 }
 
-fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
+/// Returns the statements that push the parameter into `params`, the ones that validate it if it
+/// has constraints, and its type if it is `nested`.
+fn generate_single_field(
+    field: Field,
+    rename_all: Option<RenameRule>,
+    container_default: bool,
+    transparent: bool,
+) -> syn::Result<(TokenStream, Option<TokenStream>, Option<Type>)> {
     let Field {
         attrs, ident, ty, ..
     } = field;
 
     let SerdeAttr {
         rename: serde_rename,
+        aliases,
         flatten: serde_flatten,
         default: serde_default,
+        with,
+        ..
     } = SerdeAttr::new(&attrs);
 
     let SchemaAttr {
         rename: schema_rename,
         flatten: schema_flatten,
         default: schema_default,
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+        nested,
+        example,
+        value_type,
     } = match SchemaAttr::from_attributes(&attrs) {
         Ok(Some(AttrsValue {
             value: field_attr, ..
@@ -80,30 +141,87 @@ fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
         Err(AttrsValue { value: e, .. }) => return Err(e),
     };
 
-    if serde_flatten || schema_flatten {
-        return Ok(quote_use! {
+    let constraints = Constraints {
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+    };
+
+    constraints.check_pattern()?;
+
+    let ident = ident.expect("unreachable: named field must have an identifier");
+
+    if with && value_type.is_none() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the schema of a field deserialized `with` a function can not be inferred, specify it with `#[schema(value_type = Type)]`",
+        ));
+    }
+
+    let nested_type = nested.then(|| ty.clone());
+
+    if serde_flatten || schema_flatten || transparent {
+        if !constraints.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "constraints can not be used on flattened fields, use `nested` instead",
+            ));
+        }
+
+        let ty = value_type.as_ref().unwrap_or(&ty);
+
+        let push_params = quote_use! {
             # use predawn::ToParameters;
 
-            params.extend(<#ty as ToParameters>::parameters(schemas));
+            params.extend(<#ty as ToParameters>::parameters(schemas, schemas_in_progress));
+        };
+
+        // flattened parameters are at the same path, and checked by their own `ToParameters` impl
+        let validate_field = value_type.is_none().then(|| {
+            quote_use! {
+                # use predawn::ToParameters;
+
+                ToParameters::validate_parameters(&self.#ident, path, errors);
+            }
         });
+
+        return Ok((push_params, validate_field, None));
     }
 
-    let ident = schema_rename.unwrap_or_else(|| {
-        serde_rename.unwrap_or_else(|| {
-            ident
-                .expect("unreachable: named field must have an identifier")
-                .to_string()
+    let name = schema_rename.unwrap_or_else(|| {
+        serde_rename.unwrap_or_else(|| match rename_all {
+            Some(rule) => rule.apply_to_field(&ident.to_string()),
+            None => ident.to_string(),
         })
     });
 
     let default_expr =
         predawn_macro_core::util::generate_default_expr(&ty, serde_default, schema_default)?;
 
-    let default_json_value = default_expr
-        .as_ref()
-        .map(|expr| predawn_macro_core::util::generate_json_value(&ty, expr));
+    // the default value is written in the shape of the field type, which `value_type` replaces
+    let default_json_value = match value_type {
+        Some(_) => None,
+        None => default_expr
+            .as_ref()
+            .map(|expr| predawn_macro_core::util::generate_json_value(&ty, expr)),
+    };
+
+    let ty = value_type.unwrap_or(ty);
+
+    let example = match example {
+        Some(example) => {
+            let json_value = predawn_macro_core::util::generate_example_json_value(&example);
+            quote! { Some(#json_value) }
+        }
+        None => quote! { None },
+    };
+
+    let mut description = predawn_macro_core::util::extract_description(&attrs);
+    predawn_macro_core::util::append_aliases(&mut description, &aliases);
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
     let description = if description.is_empty() {
         quote! { None }
     } else {
@@ -111,7 +229,28 @@ fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
         quote! { Some(#description) }
     };
 
-    let generate_schema = if default_json_value.is_none() {
+    let generate_schema = if !constraints.is_empty() {
+        let apply_constraints = constraints.generate_apply();
+
+        let add_default = default_json_value.as_ref().map(|json_value| {
+            quote! {
+                schema.schema_data.default = Some(#json_value);
+            }
+        });
+
+        quote_use! {
+            # use predawn::ToSchema;
+            # use predawn::openapi::ReferenceOr;
+
+            {
+                let mut schema = <#ty as ToSchema>::schema(schemas, schemas_in_progress);
+                #add_default
+                #apply_constraints
+
+                ReferenceOr::Item(schema)
+            }
+        }
+    } else if default_json_value.is_none() {
         quote_use! {
             # use predawn::ToSchema;
 
@@ -142,7 +281,7 @@ fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
         }
     };
 
-    let required = if default_json_value.is_none() {
+    let required = if default_expr.is_none() && !container_default {
         quote! { <#ty as ::predawn::ToSchema>::REQUIRED }
     } else {
         quote! { false }
@@ -157,12 +296,12 @@ fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
         let schema = #generate_schema;
 
         let param = ParameterData {
-            name: ToString::to_string(#ident),
+            name: ToString::to_string(#name),
             description: #description,
             required: #required,
             deprecated: Default::default(),
             format: ParameterSchemaOrContent::Schema(schema),
-            example: Default::default(),
+            example: #example,
             examples: Default::default(),
             explode: Default::default(),
             extensions: Default::default(),
@@ -170,5 +309,29 @@ fn generate_single_field(field: Field) -> syn::Result<TokenStream> {
         params.push(param);
     };
 
-    Ok(expand)
+    let validate_field = (!constraints.is_empty() || nested).then(|| {
+        let checks = constraints.generate_checks();
+
+        let validate_nested = nested.then(|| {
+            quote_use! {
+                # use predawn::validate::Validate;
+
+                Validate::validate_at(value, &path, errors);
+            }
+        });
+
+        quote_use! {
+            # use predawn::validate::join_path;
+
+            {
+                let value = &self.#ident;
+                let path = join_path(path, #name);
+
+                #checks
+                #validate_nested
+            }
+        }
+    });
+
+    Ok((expand, validate_field, nested_type))
 }
@@ -1,13 +1,24 @@
-use from_attr::{AttrsValue, FromAttr};
-use predawn_macro_core::{SchemaAttr, SerdeAttr};
+use from_attr::{AttrsValue, FlagOrValue, FromAttr};
+use predawn_macro_core::{Constraints, RenameRule, SchemaAttr, SerdeAttr};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use quote_use::quote_use;
 use syn::{
-    Attribute, DeriveInput, Field, GenericParam, Generics, Ident, Token, punctuated::Punctuated,
+    Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Expr, Field, GenericParam,
+    Generics, Ident, Index, Member, Token, Type, punctuated::Punctuated, spanned::Spanned,
 };
 
-use crate::types::{SchemaFields, SchemaProperties, SchemaVariant, UnitVariant};
+use crate::types::{
+    SchemaFields, SchemaProperties, SchemaVariant, SerdeContainer, Tagging, UnitVariant,
+};
+
+#[derive(FromAttr, Default)]
+#[attribute(idents = [schema])]
+struct TypeAttr {
+    example: Option<Expr>,
+    /// The type implements `Validate` itself.
+    custom_validate: bool,
+}
 
 pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let DeriveInput {
@@ -20,16 +31,107 @@ pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
 
     let crate_name = predawn_macro_core::util::get_crate_name();
 
-    match crate::util::extract_schema_properties(data)? {
-        SchemaProperties::NamedStruct(fields) => {
-            generate_named_struct(&crate_name, attrs, ident, generics, fields)
+    let SerdeAttr {
+        rename: serde_rename,
+        default,
+        rename_all,
+        tag,
+        content,
+        untagged,
+        deny_unknown_fields,
+        transparent,
+        ..
+    } = SerdeAttr::new(&attrs);
+
+    if transparent {
+        if tag.is_some() || content.is_some() || untagged {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`#[serde(transparent)]` can not be used together with `tag`, `content` or `untagged`",
+            ));
+        }
+
+        return generate_transparent(&crate_name, attrs, ident, generics, data);
+    }
+
+    let container = SerdeContainer {
+        rename_all,
+        default: !matches!(default, FlagOrValue::None),
+        deny_unknown_fields,
+    };
+
+    let tagging = match (tag, content, untagged) {
+        (None, None, false) => Tagging::External,
+        (Some(tag), None, false) => Tagging::Internal { tag },
+        (Some(tag), Some(content), false) => Tagging::Adjacent { tag, content },
+        (None, None, true) => Tagging::Untagged,
+        (None, Some(_), _) => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`#[serde(content = \"...\")]` can only be used together with `tag`",
+            ));
         }
-        SchemaProperties::OnlyUnitEnum(variants) => {
-            generate_only_unit(&crate_name, attrs, ident, variants)
+        (Some(_), _, true) => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`#[serde(untagged)]` can not be used together with `tag`",
+            ));
         }
-        SchemaProperties::NormalEnum(variants) => {
-            generate_normal_enum(&crate_name, attrs, ident, generics, variants)
+    };
+
+    match crate::util::extract_schema_properties(data)? {
+        SchemaProperties::NamedStruct(fields) => {
+            let tag = match tagging {
+                Tagging::External => None,
+                Tagging::Internal { tag } => {
+                    let name = serde_rename.unwrap_or_else(|| ident.to_string());
+                    Some((tag, name))
+                }
+                Tagging::Adjacent { .. } | Tagging::Untagged => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`#[serde(content = \"...\")]` and `#[serde(untagged)]` can only be used on enums",
+                    ));
+                }
+            };
+
+            generate_named_struct(&crate_name, attrs, ident, generics, fields, container, tag)
         }
+        SchemaProperties::OnlyUnitEnum(variants) => match tagging {
+            Tagging::External => {
+                generate_only_unit(&crate_name, attrs, ident, variants, rename_all)
+            }
+            // tagged unit variants are objects, or `null` if untagged
+            _ => {
+                let variants = variants
+                    .into_iter()
+                    .map(|UnitVariant { attrs, ident }| SchemaVariant {
+                        attrs,
+                        ident,
+                        fields: SchemaFields::Unit,
+                    })
+                    .collect();
+
+                generate_normal_enum(
+                    &crate_name,
+                    attrs,
+                    ident,
+                    generics,
+                    variants,
+                    tagging,
+                    container,
+                )
+            }
+        },
+        SchemaProperties::NormalEnum(variants) => generate_normal_enum(
+            &crate_name,
+            attrs,
+            ident,
+            generics,
+            variants,
+            tagging,
+            container,
+        ),
     }
 }
 
@@ -39,19 +141,34 @@ fn generate_named_struct(
     ident: Ident,
     generics: Generics,
     fields: Punctuated<Field, Token![,]>,
+    container: SerdeContainer,
+    tag: Option<(String, String)>,
 ) -> syn::Result<TokenStream> {
     let mut errors = Vec::new();
 
-    let add_properties = fields
-        .into_iter()
-        .filter_map(|field| match generate_single_field(crate_name, field) {
-            Ok(o) => Some(o),
-            Err(e) => {
-                errors.push(e);
-                None
+    let mut add_properties = Vec::with_capacity(fields.len() + 1);
+    let mut validate_fields = Vec::new();
+    let mut nested_types = Vec::new();
+
+    if let Some((tag, name)) = tag {
+        add_properties.push(generate_add_tag(crate_name, &tag, &name));
+    }
+
+    fields.into_iter().for_each(|field| {
+        match generate_single_field(
+            crate_name,
+            field,
+            container,
+            |ident| quote! { &self.#ident },
+        ) {
+            Ok((add_property, validate_field, nested_type)) => {
+                add_properties.push(add_property);
+                validate_fields.extend(validate_field);
+                nested_types.extend(nested_type);
             }
-        })
-        .collect::<Vec<_>>();
+            Err(e) => errors.push(e),
+        }
+    });
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
         a.combine(b);
@@ -60,8 +177,23 @@ fn generate_named_struct(
         return Err(e);
     }
 
+    if container.deny_unknown_fields {
+        add_properties.push(generate_deny_unknown_fields(crate_name));
+    }
+
     let title_fn = generate_title_fn(crate_name, ident.to_string(), &generics);
 
+    let validate_impl = predawn_macro_core::generate_validate_impl(
+        &ident,
+        &generics,
+        &nested_types,
+        validate_body(&attrs, || {
+            quote! {
+                #(#validate_fields)*
+            }
+        })?,
+    );
+
     let description = predawn_macro_core::util::extract_description(&attrs);
     let add_description = if description.is_empty() {
         TokenStream::new()
@@ -72,6 +204,8 @@ fn generate_named_struct(
         }
     };
 
+    let add_example = generate_type_example(&attrs)?;
+
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let expand = quote_use! {
@@ -87,6 +221,7 @@ fn generate_named_struct(
                 data.title = Some(Self::title().into());
 
                 #add_description
+                #add_example
 
                 let mut obj = ObjectType::default();
 
@@ -98,26 +233,227 @@ fn generate_named_struct(
                 }
             }
         }
+
+        #validate_impl
+    };
+
+    Ok(expand)
+}
+
+/// `#[serde(transparent)]` structs have the schema of their only field that is not skipped.
+fn generate_transparent(
+    crate_name: &TokenStream,
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    generics: Generics,
+    data: Data,
+) -> syn::Result<TokenStream> {
+    let fields = match data {
+        Data::Struct(DataStruct { fields, .. }) => fields,
+        Data::Enum(DataEnum { enum_token, .. }) => {
+            return Err(syn::Error::new(
+                enum_token.span,
+                "`#[serde(transparent)]` can only be used on structs",
+            ));
+        }
+        Data::Union(DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new(
+                union_token.span,
+                "`ToSchema` can not be derived for unions",
+            ));
+        }
+    };
+
+    let mut fields = fields
+        .into_iter()
+        .enumerate()
+        .filter(|(_, field)| !SerdeAttr::new(&field.attrs).skip());
+
+    let (idx, field) = match (fields.next(), fields.next()) {
+        (Some(field), None) => field,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`#[serde(transparent)]` requires exactly one field that is not skipped",
+            ));
+        }
+    };
+
+    let member = match field.ident {
+        Some(ident) => Member::Named(ident),
+        None => Member::Unnamed(Index::from(idx)),
+    };
+
+    let SerdeAttr { with, .. } = SerdeAttr::new(&field.attrs);
+
+    let SchemaAttr {
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+        nested,
+        value_type,
+        ..
+    } = match SchemaAttr::from_attributes(&field.attrs) {
+        Ok(Some(AttrsValue {
+            value: field_attr, ..
+        })) => field_attr,
+        Ok(None) => Default::default(),
+        Err(AttrsValue { value: e, .. }) => return Err(e),
+    };
+
+    if with && value_type.is_none() {
+        return Err(syn::Error::new(
+            member.span(),
+            "the schema of a field serialized `with` a function can not be inferred, specify it with `#[schema(value_type = Type)]`",
+        ));
+    }
+
+    let nested_types = nested
+        .then(|| field.ty.clone())
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let ty = value_type.unwrap_or(field.ty);
+
+    let constraints = Constraints {
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+    };
+
+    constraints.check_pattern()?;
+
+    let apply_constraints = (!constraints.is_empty()).then(|| constraints.generate_apply());
+
+    let validate_impl = predawn_macro_core::generate_validate_impl(
+        &ident,
+        &generics,
+        &nested_types,
+        validate_body(&attrs, || {
+            if constraints.is_empty() && !nested {
+                return TokenStream::new();
+            }
+
+            let checks = constraints.generate_checks();
+
+            let validate_nested = nested.then(|| {
+                quote_use! {
+                    # use #crate_name::validate::Validate;
+
+                    Validate::validate_at(value, path, errors);
+                }
+            });
+
+            quote! {
+                let value = &self.#member;
+
+                #checks
+                #validate_nested
+            }
+        })?,
+    );
+
+    let title_fn = generate_title_fn(crate_name, ident.to_string(), &generics);
+
+    let description = predawn_macro_core::util::extract_description(&attrs);
+    let add_description = if description.is_empty() {
+        TokenStream::new()
+    } else {
+        let description = predawn_macro_core::util::generate_string_expr(&description);
+        quote! {
+            data.description = Some(#description);
+        }
+    };
+
+    let add_example = generate_type_example(&attrs)?;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let expand = quote_use! {
+        # use std::collections::BTreeMap;
+        # use #crate_name::ToSchema;
+        # use #crate_name::openapi::Schema;
+
+        impl #impl_generics ToSchema for #ident #ty_generics #where_clause {
+            const REQUIRED: bool = <#ty as ToSchema>::REQUIRED;
+
+            #title_fn
+
+            fn schema(schemas: &mut BTreeMap<String, Schema>, schemas_in_progress: &mut Vec<String>) -> Schema {
+                let mut schema = <#ty as ToSchema>::schema(schemas, schemas_in_progress);
+                schema.schema_data.title = Some(Self::title().into());
+
+                {
+                    let data = &mut schema.schema_data;
+                    #add_description
+                    #add_example
+                }
+
+                #apply_constraints
+
+                schema
+            }
+        }
+
+        #validate_impl
     };
 
     Ok(expand)
 }
 
-fn generate_single_field(crate_name: &TokenStream, field: Field) -> syn::Result<TokenStream> {
+/// Returns the statements that add the field to `obj`, the ones that validate it if it has
+/// constraints, and its type if it is `nested`.
+///
+/// `access` turns the field identifier into an expression of type `&FieldType`.
+fn generate_single_field(
+    crate_name: &TokenStream,
+    field: Field,
+    container: SerdeContainer,
+    access: impl FnOnce(&Ident) -> TokenStream,
+) -> syn::Result<(TokenStream, Option<TokenStream>, Option<Type>)> {
     let Field {
         attrs, ident, ty, ..
     } = field;
 
+    let ident = ident.expect("unreachable: named field must have an identifier");
+
+    let serde_attr = SerdeAttr::new(&attrs);
+
+    if serde_attr.skip() {
+        return Ok((TokenStream::new(), None, None));
+    }
+
     let SerdeAttr {
         rename: serde_rename,
+        aliases,
         flatten: serde_flatten,
         default: serde_default,
-    } = SerdeAttr::new(&attrs);
+        skip_serializing,
+        skip_deserializing,
+        skip_serializing_if,
+        with,
+        ..
+    } = serde_attr;
 
     let SchemaAttr {
         rename: schema_rename,
         flatten: schema_flatten,
         default: schema_default,
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+        nested,
+        example,
+        value_type,
     } = match SchemaAttr::from_attributes(&attrs) {
         Ok(Some(AttrsValue {
             value: field_attr, ..
@@ -126,8 +462,46 @@ fn generate_single_field(crate_name: &TokenStream, field: Field) -> syn::Result<
         Err(AttrsValue { value: e, .. }) => return Err(e),
     };
 
+    if with && value_type.is_none() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the schema of a field serialized `with` a function can not be inferred, specify it with `#[schema(value_type = Type)]`",
+        ));
+    }
+
+    let constraints = Constraints {
+        min,
+        max,
+        min_length,
+        max_length,
+        pattern,
+        format,
+    };
+
+    constraints.check_pattern()?;
+
+    let value = access(&ident);
+
+    let nested_type = nested.then(|| ty.clone());
+
     if serde_flatten || schema_flatten {
-        return Ok(quote_use! {
+        if !constraints.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                "constraints can not be used on flattened fields, use `nested` instead",
+            ));
+        }
+
+        if container.deny_unknown_fields {
+            return Err(syn::Error::new(
+                ident.span(),
+                "flattened fields can not be used together with `#[serde(deny_unknown_fields)]`",
+            ));
+        }
+
+        let ty = value_type.as_ref().unwrap_or(&ty);
+
+        let add_property = quote_use! {
             # use #crate_name::ToSchema;
             # use #crate_name::openapi::{AnySchema, ObjectType, SchemaKind, Type};
 
@@ -147,58 +521,113 @@ fn generate_single_field(crate_name: &TokenStream, field: Field) -> syn::Result<
                 }
                 _ => {},
             };
+        };
+
+        let validate_field = nested.then(|| {
+            quote_use! {
+                # use #crate_name::validate::Validate;
+
+                Validate::validate_at(#value, path, errors);
+            }
         });
+
+        return Ok((add_property, validate_field, nested_type));
     }
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
+    let mut description = predawn_macro_core::util::extract_description(&attrs);
+    predawn_macro_core::util::append_aliases(&mut description, &aliases);
 
     let default_expr =
         predawn_macro_core::util::generate_default_expr(&ty, serde_default, schema_default)?;
 
-    let default_json_value = default_expr
-        .as_ref()
-        .map(|expr| predawn_macro_core::util::generate_json_value(&ty, expr));
+    // the default value is written in the shape of the field type, which `value_type` replaces
+    let default_json_value = match value_type {
+        Some(_) => None,
+        None => default_expr
+            .as_ref()
+            .map(|expr| predawn_macro_core::util::generate_json_value(&ty, expr)),
+    };
+
+    let ty = value_type.unwrap_or(ty);
 
-    let ident = schema_rename.unwrap_or_else(|| {
-        serde_rename.unwrap_or_else(|| {
-            ident
-                .expect("unreachable: named field must have an identifier")
-                .to_string()
+    let name = schema_rename.unwrap_or_else(|| {
+        serde_rename.unwrap_or_else(|| match container.rename_all {
+            Some(rule) => rule.apply_to_field(&ident.to_string()),
+            None => ident.to_string(),
         })
     });
 
-    let generate_schema = if description.is_empty() && default_json_value.is_none() {
+    let add_description = if description.is_empty() {
+        TokenStream::new()
+    } else {
+        let description = predawn_macro_core::util::generate_string_expr(&description);
+        quote! {
+            data.description = Some(#description);
+        }
+    };
+
+    let add_default = default_json_value.as_ref().map(|json_value| {
+        quote! {
+            data.default = Some(#json_value);
+        }
+    });
+
+    let add_example = generate_add_example(example.as_ref());
+
+    // a field that is only serialized or only deserialized
+    let add_access = match (skip_serializing, skip_deserializing) {
+        (false, true) => quote! { data.read_only = true; },
+        (true, false) => quote! { data.write_only = true; },
+        _ => TokenStream::new(),
+    };
+
+    let generate_schema = if !constraints.is_empty() {
+        // constraints can only be written into an inlined schema
+        let apply_constraints = constraints.generate_apply();
+
         quote_use! {
+            # use std::boxed::Box;
             # use #crate_name::ToSchema;
+            # use #crate_name::openapi::ReferenceOr;
 
-            <#ty as ToSchema>::schema_ref_box(schemas, schemas_in_progress)
-        }
-    } else {
-        let add_description = if description.is_empty() {
-            TokenStream::new()
-        } else {
-            let description = predawn_macro_core::util::generate_string_expr(&description);
-            quote! {
-                data.description = Some(#description);
-            }
-        };
+            {
+                let mut schema = <#ty as ToSchema>::schema(schemas, schemas_in_progress);
 
-        let add_default = default_json_value.as_ref().map(|json_value| {
-            quote! {
-                data.default = Some(#json_value);
+                {
+                    let data = &mut schema.schema_data;
+                    #add_description
+                    #add_default
+                    #add_example
+                    #add_access
+                }
+
+                #apply_constraints
+
+                ReferenceOr::Item(Box::new(schema))
             }
-        });
+        }
+    } else if description.is_empty()
+        && default_json_value.is_none()
+        && add_example.is_empty()
+        && add_access.is_empty()
+    {
+        quote_use! {
+            # use #crate_name::ToSchema;
 
+            <#ty as ToSchema>::schema_ref_box(schemas, schemas_in_progress)
+        }
+    } else {
         quote_use! {
             # use std::boxed::Box;
             # use #crate_name::ToSchema;
             # use predawn::openapi::{ReferenceOr, Schema, SchemaData, SchemaKind};
 
             {
-                // TODO: add example
                 let mut data = SchemaData::default();
                 #add_description
                 #add_default
+                #add_example
+                #add_access
 
                 ReferenceOr::Item(Box::new(Schema {
                     schema_data: data,
@@ -210,33 +639,60 @@ fn generate_single_field(crate_name: &TokenStream, field: Field) -> syn::Result<
         }
     };
 
-    let push_required = if default_json_value.is_none() {
+    // the field may be missing when it has a default, or when it is skipped while serializing
+    let optional = default_expr.is_some() || container.default || skip_serializing_if;
+
+    let push_required = if !optional {
         quote_use! {
             # use std::string::ToString;
             # use #crate_name::ToSchema;
 
             if <#ty as ToSchema>::REQUIRED {
-                obj.required.push(ToString::to_string(#ident));
+                obj.required.push(ToString::to_string(#name));
             }
         }
     } else {
         TokenStream::new()
     };
 
-    let expand = quote_use! {
+    let add_property = quote_use! {
         # use std::string::ToString;
         # use #crate_name::ToSchema;
 
         {
             let schema = #generate_schema;
 
-            obj.properties.insert(ToString::to_string(#ident), schema);
+            obj.properties.insert(ToString::to_string(#name), schema);
 
             #push_required
         }
     };
 
-    Ok(expand)
+    let validate_field = (!constraints.is_empty() || nested).then(|| {
+        let checks = constraints.generate_checks();
+
+        let validate_nested = nested.then(|| {
+            quote_use! {
+                # use #crate_name::validate::Validate;
+
+                Validate::validate_at(value, &path, errors);
+            }
+        });
+
+        quote_use! {
+            # use #crate_name::validate::join_path;
+
+            {
+                let value = #value;
+                let path = join_path(path, #name);
+
+                #checks
+                #validate_nested
+            }
+        }
+    });
+
+    Ok((add_property, validate_field, nested_type))
 }
 
 fn generate_title_fn(crate_name: &TokenStream, ident: String, generics: &Generics) -> TokenStream {
@@ -305,55 +761,197 @@ fn generate_title_fn(crate_name: &TokenStream, ident: String, generics: &Generic
     }
 }
 
-// {
-//   "title": "SomeOne",
-//   "type": "string",
-//   "enum": [
-//     "Alice",
-//     "Bob"
-//   ]
-// }
+/// Adds the property holding the tag `name` of an internally or adjacently tagged value to `obj`.
+fn generate_add_tag(crate_name: &TokenStream, tag: &str, name: &str) -> TokenStream {
+    quote_use! {
+        # use std::boxed::Box;
+        # use std::string::ToString;
+        # use #crate_name::openapi::{ReferenceOr, Schema, SchemaData, SchemaKind, StringType, Type};
 
-fn generate_only_unit(
-    crate_name: &TokenStream,
-    attrs: Vec<Attribute>,
-    ident: Ident,
-    variants: Vec<UnitVariant>,
-) -> syn::Result<TokenStream> {
-    let title_literal = ident.to_string();
+        {
+            let mut ty = StringType::default();
+            ty.enumeration.push(Some(ToString::to_string(#name)));
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
-    let add_description = if description.is_empty() {
-        TokenStream::new()
-    } else {
-        let description = predawn_macro_core::util::generate_string_expr(&description);
-        quote! {
-            data.description = Some(#description);
+            let schema = Schema {
+                schema_data: SchemaData::default(),
+                schema_kind: SchemaKind::Type(Type::String(ty)),
+            };
+
+            obj.properties.insert(ToString::to_string(#tag), ReferenceOr::Item(Box::new(schema)));
+            obj.required.push(ToString::to_string(#tag));
         }
-    };
+    }
+}
 
-    let mut errors = Vec::new();
+fn parse_type_attr(attrs: &[Attribute]) -> syn::Result<TypeAttr> {
+    match TypeAttr::from_attributes(attrs) {
+        Ok(Some(AttrsValue {
+            value: type_attr, ..
+        })) => Ok(type_attr),
+        Ok(None) => Ok(Default::default()),
+        Err(AttrsValue { value: e, .. }) => Err(e),
+    }
+}
 
-    let add_enumeration = variants
-        .into_iter()
-        .filter_map(|field| match generate_single_unit_variant(field) {
-            Ok(o) => Some(o),
-            Err(e) => {
-                errors.push(e);
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+fn generate_type_example(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let TypeAttr { example, .. } = parse_type_attr(attrs)?;
 
-    if let Some(e) = errors.into_iter().reduce(|mut a, b| {
-        a.combine(b);
-        a
-    }) {
-        return Err(e);
-    }
+    Ok(generate_add_example(example.as_ref()))
+}
 
-    let expand = quote_use! {
-        # use std::collections::BTreeMap;
+/// `None` if the type implements `Validate` itself, otherwise `validate`, possibly empty.
+fn validate_body(
+    attrs: &[Attribute],
+    validate: impl FnOnce() -> TokenStream,
+) -> syn::Result<Option<TokenStream>> {
+    let TypeAttr {
+        custom_validate, ..
+    } = parse_type_attr(attrs)?;
+
+    Ok((!custom_validate).then(validate))
+}
+
+fn generate_add_example(example: Option<&Expr>) -> TokenStream {
+    match example {
+        Some(example) => {
+            let json_value = predawn_macro_core::util::generate_example_json_value(example);
+
+            quote! {
+                data.example = Some(#json_value);
+            }
+        }
+        None => TokenStream::new(),
+    }
+}
+
+/// Forbids properties other than the ones in `obj`.
+fn generate_deny_unknown_fields(crate_name: &TokenStream) -> TokenStream {
+    quote_use! {
+        # use #crate_name::openapi::AdditionalProperties;
+
+        obj.additional_properties = Some(AdditionalProperties::Any(false));
+    }
+}
+
+/// Returns `None` if the variant is skipped.
+fn variant_name(
+    attrs: &[Attribute],
+    ident: &Ident,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<Option<String>> {
+    let SerdeAttr {
+        rename: serde_rename,
+        aliases,
+        skip_serializing,
+        skip_deserializing,
+        with,
+        ..
+    } = SerdeAttr::new(attrs);
+
+    if skip_serializing && skip_deserializing {
+        return Ok(None);
+    }
+
+    if !aliases.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the aliases of a variant can not be documented, rename it with `#[serde(rename(deserialize = \"...\"))]` instead",
+        ));
+    }
+
+    if with {
+        return Err(syn::Error::new(
+            ident.span(),
+            "the schema of a variant serialized `with` a function can not be inferred",
+        ));
+    }
+
+    let SchemaAttr {
+        rename: schema_rename,
+        ..
+    } = match SchemaAttr::from_attributes(attrs) {
+        Ok(Some(AttrsValue {
+            value: field_attr, ..
+        })) => field_attr,
+        Ok(None) => Default::default(),
+        Err(AttrsValue { value: e, .. }) => return Err(e),
+    };
+
+    let name = schema_rename.unwrap_or_else(|| {
+        serde_rename.unwrap_or_else(|| match rename_all {
+            Some(rule) => rule.apply_to_variant(&ident.to_string()),
+            None => ident.to_string(),
+        })
+    });
+
+    Ok(Some(name))
+}
+
+// {
+//   "title": "SomeOne",
+//   "type": "string",
+//   "enum": [
+//     "Alice",
+//     "Bob"
+//   ]
+// }
+
+fn generate_only_unit(
+    crate_name: &TokenStream,
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    variants: Vec<UnitVariant>,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<TokenStream> {
+    let title_literal = ident.to_string();
+
+    let description = predawn_macro_core::util::extract_description(&attrs);
+    let add_description = if description.is_empty() {
+        TokenStream::new()
+    } else {
+        let description = predawn_macro_core::util::generate_string_expr(&description);
+        quote! {
+            data.description = Some(#description);
+        }
+    };
+
+    let add_example = generate_type_example(&attrs)?;
+
+    let validate_impl = predawn_macro_core::generate_validate_impl(
+        &ident,
+        &Generics::default(),
+        &[],
+        validate_body(&attrs, TokenStream::new)?,
+    );
+
+    let mut errors = Vec::new();
+
+    let add_enumeration = variants
+        .into_iter()
+        .filter_map(
+            |UnitVariant { attrs, ident }| match variant_name(&attrs, &ident, rename_all) {
+                Ok(name) => name.map(|name| {
+                    quote! {
+                        ty.enumeration.push(Some(#name.to_string()));
+                    }
+                }),
+                Err(e) => {
+                    errors.push(e);
+                    None
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    if let Some(e) = errors.into_iter().reduce(|mut a, b| {
+        a.combine(b);
+        a
+    }) {
+        return Err(e);
+    }
+
+    let expand = quote_use! {
+        # use std::collections::BTreeMap;
         # use std::borrow::Cow;
         # use #crate_name::ToSchema;
         # use #crate_name::openapi::{Schema, StringType, SchemaData, SchemaKind, Type};
@@ -368,6 +966,7 @@ fn generate_only_unit(
                 data.title = Some(Self::title().into());
 
                 #add_description
+                #add_example
 
                 let mut ty = StringType::default();
 
@@ -379,37 +978,11 @@ fn generate_only_unit(
                 }
             }
         }
-    };
 
-    Ok(expand)
-}
-
-fn generate_single_unit_variant(variant: UnitVariant) -> syn::Result<TokenStream> {
-    let UnitVariant { attrs, ident } = variant;
-
-    let SerdeAttr {
-        rename: serde_rename,
-        flatten: _,
-        default: _,
-    } = SerdeAttr::new(&attrs);
-
-    let SchemaAttr {
-        rename: schema_rename,
-        flatten: _,
-        default: _,
-    } = match SchemaAttr::from_attributes(&attrs) {
-        Ok(Some(AttrsValue {
-            value: field_attr, ..
-        })) => field_attr,
-        Ok(None) => Default::default(),
-        Err(AttrsValue { value: e, .. }) => return Err(e),
+        #validate_impl
     };
 
-    let ident = schema_rename.unwrap_or_else(|| serde_rename.unwrap_or_else(|| ident.to_string()));
-
-    Ok(quote! {
-        ty.enumeration.push(Some(#ident.to_string()));
-    })
+    Ok(expand)
 }
 
 fn generate_normal_enum(
@@ -418,12 +991,16 @@ fn generate_normal_enum(
     ident: Ident,
     generics: Generics,
     variants: Vec<SchemaVariant>,
+    tagging: Tagging,
+    container: SerdeContainer,
 ) -> syn::Result<TokenStream> {
     let variants_len = variants.len();
 
     let mut errors = Vec::new();
+    let mut validate_arms = Vec::new();
+    let mut nested_types = Vec::new();
 
-    let one_of_schema = variants
+    let push_variants = variants
         .into_iter()
         .filter_map(|variant| {
             let SchemaVariant {
@@ -432,23 +1009,78 @@ fn generate_normal_enum(
                 fields,
             } = variant;
 
+            let name = match variant_name(&attrs, &ident, container.rename_all) {
+                Ok(Some(o)) => o,
+                Ok(None) => return None,
+                Err(e) => {
+                    errors.push(e);
+                    return None;
+                }
+            };
+
             let result = match fields {
-                SchemaFields::Unit => generate_unit_variant(crate_name, attrs, ident),
-                SchemaFields::Unnamed(field) => {
-                    generate_unnamed_variant(crate_name, attrs, ident, *field)
+                SchemaFields::Unit => {
+                    Ok(generate_unit_variant(crate_name, &attrs, &name, &tagging))
                 }
-                SchemaFields::Named(fields) => {
-                    generate_named_variant(crate_name, attrs, ident, fields)
+                SchemaFields::Unnamed(field) => {
+                    generate_unnamed_variant(crate_name, &attrs, &name, *field, &tagging)
                 }
+                SchemaFields::Named(fields) => generate_named_variant(
+                    crate_name,
+                    &attrs,
+                    &ident,
+                    &name,
+                    fields,
+                    &tagging,
+                    container.deny_unknown_fields,
+                )
+                .map(|(schema, validate_arm, variant_nested_types)| {
+                    validate_arms.extend(validate_arm);
+                    nested_types.extend(variant_nested_types);
+                    schema
+                }),
             };
 
-            match result {
-                Ok(o) => Some(o),
+            let schema = match result {
+                Ok(o) => o,
                 Err(e) => {
                     errors.push(e);
-                    None
+                    return None;
                 }
-            }
+            };
+
+            let push_variant = match tagging {
+                Tagging::External | Tagging::Untagged => quote_use! {
+                    # use #crate_name::openapi::ReferenceOr;
+
+                    one_of.push(ReferenceOr::Item(#schema));
+                },
+                // the discriminator maps tags to schemas in the components
+                Tagging::Internal { .. } | Tagging::Adjacent { .. } => {
+                    let variant = ident.to_string();
+
+                    quote_use! {
+                        # use std::format;
+                        # use std::clone::Clone;
+                        # use std::string::ToString;
+                        # use #crate_name::ToSchema;
+                        # use #crate_name::openapi::ReferenceOr;
+
+                        {
+                            let key = format!("{}.{}", <Self as ToSchema>::key(), #variant);
+                            let reference = format!("#/components/schemas/{}", key);
+
+                            let schema = #schema;
+                            schemas.insert(key, schema);
+
+                            discriminator.mapping.insert(ToString::to_string(#name), Clone::clone(&reference));
+                            one_of.push(ReferenceOr::Reference { reference });
+                        }
+                    }
+                }
+            };
+
+            Some(push_variant)
         })
         .collect::<Vec<_>>();
 
@@ -471,6 +1103,61 @@ fn generate_normal_enum(
         }
     };
 
+    let add_example = generate_type_example(&attrs)?;
+
+    let (create_discriminator, add_discriminator) = match &tagging {
+        Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } => (
+            quote_use! {
+                # use std::default::Default;
+                # use std::string::ToString;
+                # use #crate_name::openapi::Discriminator;
+
+                let mut discriminator = Discriminator {
+                    property_name: ToString::to_string(#tag),
+                    mapping: Default::default(),
+                    extensions: Default::default(),
+                };
+            },
+            quote! {
+                data.discriminator = Some(discriminator);
+            },
+        ),
+        Tagging::External | Tagging::Untagged => (TokenStream::new(), TokenStream::new()),
+    };
+
+    // serde picks the first variant an untagged value matches, so more than one may match
+    let schema_kind = match tagging {
+        Tagging::Untagged => quote_use! {
+            # use #crate_name::openapi::SchemaKind;
+
+            SchemaKind::AnyOf { any_of: one_of }
+        },
+        _ => quote_use! {
+            # use #crate_name::openapi::SchemaKind;
+
+            SchemaKind::OneOf { one_of }
+        },
+    };
+
+    let validate_impl = predawn_macro_core::generate_validate_impl(
+        &ident,
+        &generics,
+        &nested_types,
+        validate_body(&attrs, || {
+            if validate_arms.is_empty() {
+                return TokenStream::new();
+            }
+
+            quote! {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#validate_arms)*
+                    _ => {}
+                }
+            }
+        })?,
+    );
+
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let expand = quote_use! {
@@ -486,162 +1173,322 @@ fn generate_normal_enum(
                 data.title = Some(Self::title().into());
 
                 #add_description
+                #add_example
+
+                #create_discriminator
 
                 let mut one_of = Vec::with_capacity(#variants_len);
 
-                #(one_of.push(#one_of_schema);)*
+                #(#push_variants)*
+
+                #add_discriminator
 
                 Schema {
                     schema_data: data,
-                    schema_kind: SchemaKind::OneOf {
-                        one_of,
-                    },
+                    schema_kind: #schema_kind,
                 }
             }
         }
+
+        #validate_impl
     };
 
     Ok(expand)
 }
 
-fn generate_unit_variant(
-    crate_name: &TokenStream,
-    attrs: Vec<Attribute>,
-    ident: Ident,
-) -> syn::Result<TokenStream> {
-    let SerdeAttr {
-        rename: serde_rename,
-        flatten: _,
-        default: _,
-    } = SerdeAttr::new(&attrs);
-
-    let SchemaAttr {
-        rename: schema_rename,
-        flatten: _,
-        default: _,
-    } = match SchemaAttr::from_attributes(&attrs) {
-        Ok(Some(AttrsValue {
-            value: field_attr, ..
-        })) => field_attr,
-        Ok(None) => Default::default(),
-        Err(AttrsValue { value: e, .. }) => return Err(e),
-    };
-
-    let ident = schema_rename.unwrap_or_else(|| serde_rename.unwrap_or_else(|| ident.to_string()));
+fn generate_variant_description(attrs: &[Attribute]) -> TokenStream {
+    let description = predawn_macro_core::util::extract_description(attrs);
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
-    let add_description = if description.is_empty() {
+    if description.is_empty() {
         TokenStream::new()
     } else {
         let description = predawn_macro_core::util::generate_string_expr(&description);
         quote! {
             data.description = Some(#description);
         }
+    }
+}
+
+/// Returns an expression of the `Schema` of the variant.
+fn generate_unit_variant(
+    crate_name: &TokenStream,
+    attrs: &[Attribute],
+    name: &str,
+    tagging: &Tagging,
+) -> TokenStream {
+    let add_description = generate_variant_description(attrs);
+
+    let schema_kind = match tagging {
+        // "Name"
+        Tagging::External => quote_use! {
+            # use #crate_name::openapi::{SchemaKind, StringType, Type};
+
+            {
+                let mut ty = StringType::default();
+                ty.enumeration.push(Some(#name.to_string()));
+
+                SchemaKind::Type(Type::String(ty))
+            }
+        },
+        // { "tag": "Name" }
+        Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } => {
+            let add_tag = generate_add_tag(crate_name, tag, name);
+
+            quote_use! {
+                # use #crate_name::openapi::{ObjectType, SchemaKind, Type};
+
+                {
+                    let mut obj = ObjectType::default();
+                    #add_tag
+
+                    SchemaKind::Type(Type::Object(obj))
+                }
+            }
+        }
+        // null
+        Tagging::Untagged => quote_use! {
+            # use std::vec;
+            # use std::default::Default;
+            # use #crate_name::__internal::serde_json::Value;
+            # use #crate_name::openapi::{AnySchema, SchemaKind};
+
+            {
+                data.nullable = true;
+
+                SchemaKind::Any(AnySchema {
+                    enumeration: vec![Value::Null],
+                    ..Default::default()
+                })
+            }
+        },
     };
 
-    let expand = quote_use! {
-        # use #crate_name::openapi::{Schema, StringType, SchemaData, SchemaKind, Type, ReferenceOr};
+    quote_use! {
+        # use #crate_name::openapi::{Schema, SchemaData};
 
         {
             let mut data = SchemaData::default();
             #add_description
 
-            let mut ty = StringType::default();
-            ty.enumeration.push(Some(#ident.to_string()));
+            let schema_kind = #schema_kind;
 
-            let schema = Schema {
+            Schema {
                 schema_data: data,
-                schema_kind: SchemaKind::Type(Type::String(ty)),
-            };
-
-            ReferenceOr::Item(schema)
+                schema_kind,
+            }
         }
-    };
+    }
+}
 
-    Ok(expand)
+/// Whether `ty` may serialize as a JSON object, judged by its syntax: primitives, strings,
+/// sequences, tuples and `Option` never do.
+fn may_be_object(ty: &Type) -> bool {
+    const NON_OBJECTS: [&str; 27] = [
+        "bool",
+        "char",
+        "str",
+        "String",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "f32",
+        "f64",
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "HashSet",
+        "BTreeSet",
+        "BinaryHeap",
+        "Option",
+        "Bytes",
+        "PathBuf",
+    ];
+
+    match ty {
+        Type::Paren(ty) => may_be_object(&ty.elem),
+        Type::Group(ty) => may_be_object(&ty.elem),
+        Type::Reference(ty) => may_be_object(&ty.elem),
+        Type::Tuple(_) | Type::Array(_) | Type::Slice(_) => false,
+        Type::Path(ty) => ty.path.segments.last().is_none_or(|segment| {
+            !NON_OBJECTS
+                .iter()
+                .any(|non_object| segment.ident == non_object)
+        }),
+        _ => true,
+    }
 }
 
+/// Returns an expression of the `Schema` of the variant.
 fn generate_unnamed_variant(
     crate_name: &TokenStream,
-    attrs: Vec<Attribute>,
-    ident: Ident,
+    attrs: &[Attribute],
+    name: &str,
     field: Field,
+    tagging: &Tagging,
 ) -> syn::Result<TokenStream> {
     let ty = field.ty;
 
-    let SerdeAttr {
-        rename: serde_rename,
-        flatten: _,
-        default: _,
-    } = SerdeAttr::new(&attrs);
+    if matches!(tagging, Tagging::Internal { .. }) && !may_be_object(&ty) {
+        return Err(syn::Error::new(
+            ty.span(),
+            "a newtype variant of an internally tagged enum must hold a struct or a map",
+        ));
+    }
 
-    let SchemaAttr {
-        rename: schema_rename,
-        flatten: _,
-        default: _,
-    } = match SchemaAttr::from_attributes(&attrs) {
-        Ok(Some(AttrsValue {
-            value: field_attr, ..
-        })) => field_attr,
-        Ok(None) => Default::default(),
-        Err(AttrsValue { value: e, .. }) => return Err(e),
-    };
+    let add_description = generate_variant_description(attrs);
 
-    let ident = schema_rename.unwrap_or_else(|| serde_rename.unwrap_or_else(|| ident.to_string()));
+    let schema_kind = match tagging {
+        // { "Name": value }
+        Tagging::External => quote_use! {
+            # use std::string::ToString;
+            # use #crate_name::ToSchema;
+            # use #crate_name::openapi::{ObjectType, SchemaKind, Type};
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
-    let add_description = if description.is_empty() {
-        TokenStream::new()
-    } else {
-        let description = predawn_macro_core::util::generate_string_expr(&description);
-        quote! {
-            data.description = Some(#description);
+            {
+                let mut obj = ObjectType::default();
+                obj.required.push(ToString::to_string(#name));
+                obj.properties.insert(ToString::to_string(#name), <#ty as ToSchema>::schema_ref_box(schemas, schemas_in_progress));
+
+                SchemaKind::Type(Type::Object(obj))
+            }
+        },
+        // { "tag": "Name", ...value }
+        Tagging::Internal { tag } => {
+            let add_tag = generate_add_tag(crate_name, tag, name);
+
+            quote_use! {
+                # use std::vec;
+                # use #crate_name::ToSchema;
+                # use #crate_name::openapi::{ObjectType, ReferenceOr, Schema, SchemaData, SchemaKind, Type};
+
+                {
+                    let mut obj = ObjectType::default();
+                    #add_tag
+
+                    let tag_schema = Schema {
+                        schema_data: SchemaData::default(),
+                        schema_kind: SchemaKind::Type(Type::Object(obj)),
+                    };
+
+                    SchemaKind::AllOf {
+                        all_of: vec![
+                            ReferenceOr::Item(tag_schema),
+                            <#ty as ToSchema>::schema_ref(schemas, schemas_in_progress),
+                        ],
+                    }
+                }
+            }
+        }
+        // { "tag": "Name", "content": value }
+        Tagging::Adjacent { tag, content } => {
+            let add_tag = generate_add_tag(crate_name, tag, name);
+
+            quote_use! {
+                # use std::string::ToString;
+                # use #crate_name::ToSchema;
+                # use #crate_name::openapi::{ObjectType, SchemaKind, Type};
+
+                {
+                    let mut obj = ObjectType::default();
+                    #add_tag
+
+                    obj.properties.insert(ToString::to_string(#content), <#ty as ToSchema>::schema_ref_box(schemas, schemas_in_progress));
+
+                    if <#ty as ToSchema>::REQUIRED {
+                        obj.required.push(ToString::to_string(#content));
+                    }
+
+                    SchemaKind::Type(Type::Object(obj))
+                }
+            }
         }
+        // value
+        Tagging::Untagged => quote_use! {
+            # use std::vec;
+            # use #crate_name::ToSchema;
+            # use #crate_name::openapi::SchemaKind;
+
+            SchemaKind::AllOf {
+                all_of: vec![<#ty as ToSchema>::schema_ref(schemas, schemas_in_progress)],
+            }
+        },
     };
 
-    let expand = quote_use! {
-        # use std::string::ToString;
-        # use #crate_name::ToSchema;
-        # use #crate_name::openapi::{Schema, ObjectType, SchemaData, SchemaKind, Type, ReferenceOr};
+    Ok(quote_use! {
+        # use #crate_name::openapi::{Schema, SchemaData};
 
         {
             let mut data = SchemaData::default();
             #add_description
 
-            let mut obj = ObjectType::default();
-            obj.required.push(ToString::to_string(#ident));
-            obj.properties.insert(ToString::to_string(#ident), <#ty as ToSchema>::schema_ref_box(schemas, schemas_in_progress));
+            let schema_kind = #schema_kind;
 
-            let schema = Schema {
+            Schema {
                 schema_data: data,
-                schema_kind: SchemaKind::Type(Type::Object(obj)),
-            };
-
-            ReferenceOr::Item(schema)
+                schema_kind,
+            }
         }
-    };
-
-    Ok(expand)
+    })
 }
 
+/// Returns an expression of the `Schema` of the variant, the match arm that validates it, and the
+/// types of its `nested` fields.
 fn generate_named_variant(
     crate_name: &TokenStream,
-    attrs: Vec<Attribute>,
-    ident: Ident,
+    attrs: &[Attribute],
+    variant_ident: &Ident,
+    name: &str,
     fields: Punctuated<Field, Token![,]>,
-) -> syn::Result<TokenStream> {
+    tagging: &Tagging,
+    deny_unknown_fields: bool,
+) -> syn::Result<(TokenStream, Option<TokenStream>, Vec<Type>)> {
+    let SerdeAttr { rename_all, .. } = SerdeAttr::new(attrs);
+
+    let container = SerdeContainer {
+        rename_all,
+        default: false,
+        deny_unknown_fields,
+    };
+
     let mut errors = Vec::new();
 
-    let add_properties = fields
-        .into_iter()
-        .filter_map(|field| match generate_single_field(crate_name, field) {
-            Ok(o) => Some(o),
-            Err(e) => {
-                errors.push(e);
-                None
+    let mut add_properties = Vec::with_capacity(fields.len());
+    let mut bindings = Vec::new();
+    let mut validate_fields = Vec::new();
+    let mut nested_types = Vec::new();
+
+    fields.into_iter().for_each(|field| {
+        let field_ident = field.ident.clone();
+
+        // bind fields to prefixed names, so they can not shadow `path` or `errors`
+        let binding = |ident: &Ident| {
+            let binding = format_ident!("__{}", ident);
+            quote! { #binding }
+        };
+
+        match generate_single_field(crate_name, field, container, binding) {
+            Ok((add_property, validate_field, nested_type)) => {
+                add_properties.push(add_property);
+                nested_types.extend(nested_type);
+
+                if let (Some(validate_field), Some(field_ident)) = (validate_field, field_ident) {
+                    let binding = format_ident!("__{}", field_ident);
+                    bindings.push(quote! { #field_ident: #binding });
+                    validate_fields.push(validate_field);
+                }
             }
-        })
-        .collect::<Vec<_>>();
+            Err(e) => errors.push(e),
+        }
+    });
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
         a.combine(b);
@@ -650,70 +1497,97 @@ fn generate_named_variant(
         return Err(e);
     }
 
-    let SerdeAttr {
-        rename: serde_rename,
-        flatten: _,
-        default: _,
-    } = SerdeAttr::new(&attrs);
+    if deny_unknown_fields {
+        add_properties.push(generate_deny_unknown_fields(crate_name));
+    }
 
-    let SchemaAttr {
-        rename: schema_rename,
-        flatten: _,
-        default: _,
-    } = match SchemaAttr::from_attributes(&attrs) {
-        Ok(Some(AttrsValue {
-            value: field_attr, ..
-        })) => field_attr,
-        Ok(None) => Default::default(),
-        Err(AttrsValue { value: e, .. }) => return Err(e),
-    };
+    let add_description = generate_variant_description(attrs);
 
-    let ident = schema_rename.unwrap_or_else(|| serde_rename.unwrap_or_else(|| ident.to_string()));
+    let fields_schema = quote_use! {
+        # use std::boxed::Box;
+        # use #crate_name::openapi::{ObjectType, ReferenceOr, Schema, SchemaData, SchemaKind, Type};
 
-    let description = predawn_macro_core::util::extract_description(&attrs);
-    let add_description = if description.is_empty() {
-        TokenStream::new()
-    } else {
-        let description = predawn_macro_core::util::generate_string_expr(&description);
-        quote! {
-            data.description = Some(#description);
+        {
+            let mut obj = ObjectType::default();
+            #(#add_properties)*
+
+            let schema = Schema {
+                schema_data: SchemaData::default(),
+                schema_kind: SchemaKind::Type(Type::Object(obj)),
+            };
+
+            ReferenceOr::Item(Box::new(schema))
         }
     };
 
+    let (add_tag, add_fields, fields_path) = match tagging {
+        // { "Name": { fields } }
+        Tagging::External => (
+            TokenStream::new(),
+            quote_use! {
+                # use std::string::ToString;
+
+                obj.required.push(ToString::to_string(#name));
+                obj.properties.insert(ToString::to_string(#name), #fields_schema);
+            },
+            Some(name),
+        ),
+        // { "tag": "Name", fields }
+        Tagging::Internal { tag } => (
+            generate_add_tag(crate_name, tag, name),
+            quote! { #(#add_properties)* },
+            None,
+        ),
+        // { "tag": "Name", "content": { fields } }
+        Tagging::Adjacent { tag, content } => (
+            generate_add_tag(crate_name, tag, name),
+            quote_use! {
+                # use std::string::ToString;
+
+                obj.required.push(ToString::to_string(#content));
+                obj.properties.insert(ToString::to_string(#content), #fields_schema);
+            },
+            Some(content.as_str()),
+        ),
+        // { fields }
+        Tagging::Untagged => (TokenStream::new(), quote! { #(#add_properties)* }, None),
+    };
+
     let expand = quote_use! {
-        # use std::string::ToString;
-        # use #crate_name::ToSchema;
-        # use #crate_name::openapi::{Schema, ObjectType, SchemaData, SchemaKind, Type, ReferenceOr};
+        # use #crate_name::openapi::{ObjectType, Schema, SchemaData, SchemaKind, Type};
 
         {
             let mut data = SchemaData::default();
             #add_description
 
             let mut obj = ObjectType::default();
-            obj.required.push(ToString::to_string(#ident));
-            obj.properties.insert(
-                ToString::to_string(#ident),
-                {
-                    let mut obj = ObjectType::default();
-                    #(#add_properties)*
+            #add_tag
+            #add_fields
 
-                    let schema = Schema {
-                        schema_data: SchemaData::default(),
-                        schema_kind: SchemaKind::Type(Type::Object(obj)),
-                    };
-
-                    ReferenceOr::Item(Box::new(schema))
-                },
-            );
-
-            let schema = Schema {
+            Schema {
                 schema_data: data,
                 schema_kind: SchemaKind::Type(Type::Object(obj)),
-            };
-
-            ReferenceOr::Item(schema)
+            }
         }
     };
 
-    Ok(expand)
+    let validate_arm = (!validate_fields.is_empty()).then(|| {
+        let join_fields_path = fields_path.map(|fields_path| {
+            quote_use! {
+                # use #crate_name::validate::join_path;
+
+                let path = &join_path(path, #fields_path);
+            }
+        });
+
+        quote! {
+            Self::#variant_ident { #(#bindings,)* .. } => {
+                #join_fields_path
+
+                #(#validate_fields)*
+            }
+        }
+    });
+
+    Ok((expand, validate_arm, nested_types))
 }
@@ -1,3 +1,4 @@
+use predawn_macro_core::RenameRule;
 use syn::{Attribute, Field, Ident, Token, punctuated::Punctuated};
 
 pub(crate) struct UnitVariant {
@@ -22,3 +23,20 @@ pub(crate) enum SchemaProperties {
     OnlyUnitEnum(Vec<UnitVariant>),
     NormalEnum(Vec<SchemaVariant>),
 }
+
+/// How an enum is represented in JSON, following `#[serde(tag, content, untagged)]`.
+pub(crate) enum Tagging {
+    External,
+    Internal { tag: String },
+    Adjacent { tag: String, content: String },
+    Untagged,
+}
+
+/// The serde attributes of a struct or enum that change the names and the presence of its fields or variants.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct SerdeContainer {
+    pub(crate) rename_all: Option<RenameRule>,
+    /// Missing fields are taken from the container's `Default`.
+    pub(crate) default: bool,
+    pub(crate) deny_unknown_fields: bool,
+}
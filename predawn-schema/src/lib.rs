@@ -8,6 +8,7 @@ mod schemars_transform;
 
 #[doc(hidden)]
 pub mod to_schema;
+pub mod validate;
 
 pub mod openapi {
     pub use openapiv3::*;
@@ -19,9 +20,10 @@ pub use predawn_schema_macro::ToSchema;
 #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
 #[cfg(feature = "schemars")]
 pub use self::schemars_transform::schemars_transform;
-pub use self::to_schema::ToSchema;
+pub use self::{to_schema::ToSchema, validate::Validate};
 
 #[doc(hidden)]
 pub mod __internal {
+    pub use regex;
     pub use serde_json;
 }
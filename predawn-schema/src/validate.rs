@@ -0,0 +1,465 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    rc::Rc,
+    sync::Arc,
+};
+
+use openapiv3::{ObjectType, Schema, SchemaData, SchemaKind, Type, VariantOrUnknownOrEmpty};
+use regex::Regex;
+
+use crate::ToSchema;
+
+/// Checks the constraints declared by `#[schema(min = .., max = .., min_length = .., max_length = .., pattern = "..", format = "..")]`.
+///
+/// Implemented by `#[derive(ToSchema)]` for every type, `#[schema(nested)]` fields are validated with
+/// their own [`Validate`] impl. A type marked `#[schema(custom_validate)]` implements it itself.
+///
+/// Values are only validated when extracted through `Valid<E>`.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        self.validate_at("", &mut errors);
+        errors.into_result()
+    }
+
+    /// Collects every violation into `errors`, `path` is where `self` is located in the outermost value.
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors);
+}
+
+impl<T: Validate + ?Sized> Validate for &T {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        T::validate_at(self, path, errors)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        T::validate_at(self, path, errors)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Arc<T> {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        T::validate_at(self, path, errors)
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        if let Some(value) = self {
+            value.validate_at(path, errors);
+        }
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        self.iter()
+            .enumerate()
+            .for_each(|(idx, value)| value.validate_at(&format!("{path}[{idx}]"), errors));
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+        self.as_slice().validate_at(path, errors)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Path of the failing field, e.g. `address.zip` or `items[2].name`.
+    pub path: String,
+    /// The OpenAPI keyword of the violated constraint, e.g. `minLength`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` {}", self.path, self.message)
+    }
+}
+
+impl ToSchema for ValidationError {
+    fn title() -> Cow<'static, str> {
+        "ValidationError".into()
+    }
+
+    fn schema(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Schema {
+        let mut obj = ObjectType::default();
+
+        for field in ["path", "keyword", "message"] {
+            obj.properties.insert(
+                field.to_string(),
+                String::schema_ref_box(schemas, schemas_in_progress),
+            );
+            obj.required.push(field.to_string());
+        }
+
+        Schema {
+            schema_data: SchemaData {
+                title: Some(Self::title().into()),
+                ..Default::default()
+            },
+            schema_kind: SchemaKind::Type(Type::Object(obj)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn push(&mut self, path: String, keyword: &'static str, message: String) {
+        self.0.push(ValidationError {
+            path,
+            keyword,
+            message,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationError> {
+        self.0.iter()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, error) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            fmt::Display::fmt(error, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl IntoIterator for ValidationErrors {
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+    type Item = ValidationError;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type IntoIter = std::slice::Iter<'a, ValidationError>;
+    type Item = &'a ValidationError;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[doc(hidden)]
+pub fn join_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{path}.{field}")
+    }
+}
+
+/// Values that `min` and `max` can be applied to.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(message = "`min` and `max` can not be applied to `{Self}`")]
+pub trait Number {
+    fn number(&self) -> Option<f64>;
+}
+
+macro_rules! number_impl {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl Number for $ty {
+                fn number(&self) -> Option<f64> {
+                    Some(*self as f64)
+                }
+            }
+        )+
+    };
+}
+
+number_impl![
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64,
+];
+
+impl<T: Number> Number for Option<T> {
+    fn number(&self) -> Option<f64> {
+        self.as_ref().and_then(Number::number)
+    }
+}
+
+/// Values that `min_length`, `max_length`, `pattern` and `format` can be applied to.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`min_length`, `max_length`, `pattern` and `format` can not be applied to `{Self}`"
+)]
+pub trait Text {
+    fn text(&self) -> Option<&str>;
+}
+
+macro_rules! text_impl {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl Text for $ty {
+                fn text(&self) -> Option<&str> {
+                    Some(self)
+                }
+            }
+        )+
+    };
+}
+
+text_impl![str, String, Box<str>, Arc<str>, Rc<str>, Cow<'_, str>];
+
+impl<T: Text> Text for Option<T> {
+    fn text(&self) -> Option<&str> {
+        self.as_ref().and_then(Text::text)
+    }
+}
+
+#[doc(hidden)]
+pub fn check_min<T: Number + ?Sized>(
+    value: &T,
+    min: f64,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    if value.number().is_some_and(|n| n < min) {
+        errors.push(
+            path.to_string(),
+            "minimum",
+            format!("must be greater than or equal to {min}"),
+        );
+    }
+}
+
+#[doc(hidden)]
+pub fn check_max<T: Number + ?Sized>(
+    value: &T,
+    max: f64,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    if value.number().is_some_and(|n| n > max) {
+        errors.push(
+            path.to_string(),
+            "maximum",
+            format!("must be less than or equal to {max}"),
+        );
+    }
+}
+
+#[doc(hidden)]
+pub fn check_min_length<T: Text + ?Sized>(
+    value: &T,
+    min_length: usize,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    // JSON Schema counts characters, not bytes
+    if value.text().is_some_and(|s| s.chars().count() < min_length) {
+        errors.push(
+            path.to_string(),
+            "minLength",
+            format!("must be at least {min_length} characters long"),
+        );
+    }
+}
+
+#[doc(hidden)]
+pub fn check_max_length<T: Text + ?Sized>(
+    value: &T,
+    max_length: usize,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    if value.text().is_some_and(|s| s.chars().count() > max_length) {
+        errors.push(
+            path.to_string(),
+            "maxLength",
+            format!("must be at most {max_length} characters long"),
+        );
+    }
+}
+
+#[doc(hidden)]
+pub fn check_pattern<T: Text + ?Sized>(
+    value: &T,
+    regex: &Regex,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    if value.text().is_some_and(|s| !regex.is_match(s)) {
+        errors.push(
+            path.to_string(),
+            "pattern",
+            format!("must match the pattern `{}`", regex.as_str()),
+        );
+    }
+}
+
+/// Only `email`, `uuid`, `ipv4` and `ipv6` are checked, other formats are just documented.
+#[doc(hidden)]
+pub fn check_format<T: Text + ?Sized>(
+    value: &T,
+    format: &str,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    let Some(s) = value.text() else {
+        return;
+    };
+
+    let valid = match format {
+        "email" => is_email(s),
+        "uuid" => is_uuid(s),
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        _ => true,
+    };
+
+    if !valid {
+        errors.push(
+            path.to_string(),
+            "format",
+            format!("must be a valid {format}"),
+        );
+    }
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && local.len() <= 64
+        && !local.contains(char::is_whitespace)
+        && !local.contains('@')
+        && domain.len() <= 255
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.char_indices().all(|(idx, c)| match idx {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// The constraints of a field, applied to its inlined schema.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct Constraints {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<&'static str>,
+    pub format: Option<&'static str>,
+}
+
+impl Constraints {
+    pub fn apply(self, schema: &mut Schema) {
+        let Self {
+            min,
+            max,
+            min_length,
+            max_length,
+            pattern,
+            format,
+        } = self;
+
+        match &mut schema.schema_kind {
+            SchemaKind::Type(Type::Integer(ty)) => {
+                if let Some(min) = min {
+                    ty.minimum = Some(min.ceil() as i64);
+                }
+
+                if let Some(max) = max {
+                    ty.maximum = Some(max.floor() as i64);
+                }
+            }
+            SchemaKind::Type(Type::Number(ty)) => {
+                if min.is_some() {
+                    ty.minimum = min;
+                }
+
+                if max.is_some() {
+                    ty.maximum = max;
+                }
+            }
+            SchemaKind::Type(Type::String(ty)) => {
+                if min_length.is_some() {
+                    ty.min_length = min_length;
+                }
+
+                if max_length.is_some() {
+                    ty.max_length = max_length;
+                }
+
+                if let Some(pattern) = pattern {
+                    ty.pattern = Some(pattern.to_string());
+                }
+
+                if let Some(format) = format {
+                    ty.format = VariantOrUnknownOrEmpty::Unknown(format.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_email, is_uuid};
+
+    #[test]
+    fn test_formats() {
+        assert!(is_email("alice@example.com"));
+        assert!(is_email("a.b+c@mail.example.org"));
+        assert!(!is_email("alice"));
+        assert!(!is_email("alice@localhost"));
+        assert!(!is_email("@example.com"));
+        assert!(!is_email("alice@exa mple.com"));
+        assert!(!is_email("alice@-example.com"));
+
+        assert!(is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!is_uuid("67e55044-10b1-426f-9247-bb680e5fe0c"));
+        assert!(!is_uuid("67e55044x10b1-426f-9247-bb680e5fe0c8"));
+    }
+}
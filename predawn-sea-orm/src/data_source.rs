@@ -28,6 +28,11 @@ impl DataSource {
         }
     }
 
+    #[cfg(feature = "session")]
+    pub(crate) fn connection(&self) -> &DatabaseConnection {
+        &self.connection
+    }
+
     pub async fn current_txn(&self) -> Result<Transaction, Error> {
         {
             let transactions = self.transactions.lock().await;
@@ -66,6 +66,14 @@ pub enum Error {
         nested_transaction_hierarchy: usize,
         txn: Transaction,
     },
+
+    #[cfg(feature = "session")]
+    #[snafu(display("failed to encode or decode session record"))]
+    SessionRecord {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_json::Error,
+    },
 }
 
 impl ErrorExt for Error {
@@ -73,6 +81,9 @@ impl ErrorExt for Error {
         match self {
             Error::DbErr { location, source } => (*location, NextError::Std(source)),
 
+            #[cfg(feature = "session")]
+            Error::SessionRecord { location, source } => (*location, NextError::Std(source)),
+
             Error::NotFoundDataSource { location, .. }
             | Error::NotSetDataSources { location }
             | Error::InconsistentDataSourceAndTransaction { location, .. }
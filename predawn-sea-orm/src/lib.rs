@@ -5,6 +5,8 @@ mod error;
 mod function;
 mod inner;
 mod middleware;
+#[cfg(feature = "session")]
+mod session;
 mod transaction;
 
 pub const DEFAULT_DATA_SOURCE: &str = "default";
@@ -13,6 +15,8 @@ tokio::task_local! {
     pub static DATA_SOURCES: std::sync::Arc<DataSources>;
 }
 
+#[cfg(feature = "session")]
+pub use self::session::SeaOrmSessionStore;
 pub use self::{
     config::{ConnectOptions, DataSourcesConfig, SlowStatementsLoggingSettings},
     data_source::DataSource,
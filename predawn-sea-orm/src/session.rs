@@ -0,0 +1,196 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use predawn::{
+    error::Error as PredawnError,
+    session::{Record, SessionId, SessionStore},
+};
+use rudi::Singleton;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, StatementBuilder,
+    sea_query::{Alias, ColumnDef, Expr, OnConflict, Query, Table},
+};
+use snafu::{OptionExt, ResultExt};
+
+use crate::{
+    DEFAULT_DATA_SOURCE, DataSources, Error,
+    error::{DbErrSnafu, NotFoundDataSourceSnafu, SessionRecordSnafu},
+    inner::Inner,
+};
+
+const DEFAULT_TABLE: &str = "predawn_sessions";
+
+const ID: &str = "id";
+const DATA: &str = "data";
+const EXPIRES_AT: &str = "expires_at";
+
+/// Keeps sessions in a table of a data source.
+///
+/// When the `session` feature is enabled, a store using the default data source is registered as
+/// `Arc<dyn SessionStore>`, so it replaces the configured store while `session.enabled` is set.
+/// Without a default data source, every operation of it fails.
+///
+/// The table is not created automatically, create it with a migration or by calling
+/// [`SeaOrmSessionStore::create_table`], e.g. in `Hooks::before_run`.
+#[derive(Debug, Clone)]
+pub struct SeaOrmSessionStore {
+    connection: Option<DatabaseConnection>,
+    data_source: Arc<str>,
+    table: Arc<str>,
+}
+
+impl SeaOrmSessionStore {
+    pub fn new(data_sources: &DataSources, name: &str) -> Result<Self, Error> {
+        let source = data_sources
+            .get(name)
+            .context(NotFoundDataSourceSnafu { name })?;
+
+        Ok(Self {
+            connection: Some(source.connection().clone()),
+            data_source: name.into(),
+            table: DEFAULT_TABLE.into(),
+        })
+    }
+
+    pub fn with_default(data_sources: &DataSources) -> Result<Self, Error> {
+        Self::new(data_sources, DEFAULT_DATA_SOURCE)
+    }
+
+    pub fn table(mut self, table: &str) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let stmt = Table::create()
+            .table(self.table_ref())
+            .if_not_exists()
+            .col(
+                ColumnDef::new(Alias::new(ID))
+                    .string_len(64)
+                    .not_null()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Alias::new(DATA)).text().not_null())
+            .col(
+                ColumnDef::new(Alias::new(EXPIRES_AT))
+                    .big_integer()
+                    .not_null(),
+            )
+            .to_owned();
+
+        self.execute(&stmt).await
+    }
+
+    fn table_ref(&self) -> Alias {
+        Alias::new(self.table.as_ref())
+    }
+
+    fn connection(&self) -> Result<&DatabaseConnection, Error> {
+        self.connection.as_ref().context(NotFoundDataSourceSnafu {
+            name: &*self.data_source,
+        })
+    }
+
+    async fn execute<S: StatementBuilder>(&self, stmt: &S) -> Result<(), Error> {
+        let connection = self.connection()?;
+        let backend = connection.get_database_backend();
+
+        connection
+            .execute(backend.build(stmt))
+            .await
+            .context(DbErrSnafu)?;
+
+        Ok(())
+    }
+}
+
+#[Singleton(binds = [Self::into_dyn])]
+impl SeaOrmSessionStore {
+    #[di]
+    async fn inject(Inner(map): Inner) -> Self {
+        Self::with_default(&DataSources::new(&map)).unwrap_or_else(|_| Self {
+            connection: None,
+            data_source: DEFAULT_DATA_SOURCE.into(),
+            table: DEFAULT_TABLE.into(),
+        })
+    }
+
+    fn into_dyn(self) -> Arc<dyn SessionStore> {
+        Arc::new(self)
+    }
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[async_trait]
+impl SessionStore for SeaOrmSessionStore {
+    async fn load(&self, id: &SessionId) -> Result<Option<Record>, PredawnError> {
+        let stmt = Query::select()
+            .column(Alias::new(DATA))
+            .from(self.table_ref())
+            .and_where(Expr::col(Alias::new(ID)).eq(id.as_str()))
+            .to_owned();
+
+        let connection = self.connection()?;
+        let backend = connection.get_database_backend();
+
+        let Some(row) = connection
+            .query_one(backend.build(&stmt))
+            .await
+            .context(DbErrSnafu)?
+        else {
+            return Ok(None);
+        };
+
+        let data = row.try_get::<String>("", DATA).context(DbErrSnafu)?;
+        let record = serde_json::from_str(&data).context(SessionRecordSnafu)?;
+
+        Ok(Some(record))
+    }
+
+    async fn save(&self, id: &SessionId, record: &Record) -> Result<(), PredawnError> {
+        let data = serde_json::to_string(record).context(SessionRecordSnafu)?;
+
+        let stmt = Query::insert()
+            .into_table(self.table_ref())
+            .columns([Alias::new(ID), Alias::new(DATA), Alias::new(EXPIRES_AT)])
+            .values_panic([
+                id.as_str().into(),
+                data.into(),
+                unix_secs(record.expires_at).into(),
+            ])
+            .on_conflict(
+                OnConflict::column(Alias::new(ID))
+                    .update_columns([Alias::new(DATA), Alias::new(EXPIRES_AT)])
+                    .to_owned(),
+            )
+            .to_owned();
+
+        Ok(self.execute(&stmt).await?)
+    }
+
+    async fn delete(&self, id: &SessionId) -> Result<(), PredawnError> {
+        let stmt = Query::delete()
+            .from_table(self.table_ref())
+            .and_where(Expr::col(Alias::new(ID)).eq(id.as_str()))
+            .to_owned();
+
+        Ok(self.execute(&stmt).await?)
+    }
+
+    async fn delete_expired(&self) -> Result<(), PredawnError> {
+        let stmt = Query::delete()
+            .from_table(self.table_ref())
+            .and_where(Expr::col(Alias::new(EXPIRES_AT)).lte(unix_secs(SystemTime::now())))
+            .to_owned();
+
+        Ok(self.execute(&stmt).await?)
+    }
+}
@@ -11,10 +11,11 @@ use http::Method;
 use indexmap::IndexMap;
 use predawn_core::{
     openapi::{self, Components, Info, OpenAPI, PathItem, Paths, ReferenceOr, SecurityRequirement},
-    request::BodyLimit,
+    problem::{scope_problem_details, with_problem_details},
+    request::{BodyLimit, BodyTimeout},
+    response_error::ResponseError,
 };
 use rudi::Context;
-use tokio::net::TcpListener;
 
 use crate::{
     any_map::AnyMap,
@@ -22,9 +23,14 @@ use crate::{
     controller::Controller,
     environment::Environment,
     handler::{Handler, HandlerExt},
+    middleware::{Cors, Timeout},
+    openapi::transform_responses,
     plugin::Plugin,
+    pubsub::Hub,
+    response::resolve_download,
+    response_error::TimeoutError,
     route::{MethodRouter, Router},
-    server::{Server, shutdown_signal},
+    server::{self, Server, shutdown_signal},
 };
 
 pub trait Hooks {
@@ -87,11 +93,35 @@ pub trait Hooks {
 
         let socket_addr = SocketAddr::new(cfg.ip, cfg.port);
 
-        let listener = TcpListener::bind(socket_addr).await?;
+        let listener = server::bind(socket_addr, cfg.reuse_port)?;
 
-        Server::new(listener)
-            .run_with_graceful_shutdown(router, shutdown_signal())
-            .await
+        let mut server = Server::new(listener)
+            .protocol(cfg.protocol)
+            .http1(&cfg.http1)
+            .http2(&cfg.http2)
+            .tcp_nodelay(cfg.tcp_nodelay);
+
+        if let Some(max) = cfg.max_connections {
+            server = server.max_connections(max);
+        }
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = cfg.tls.clone() {
+            server = server.tls(crate::server::tls::RustlsConfig::load(tls, cfg.protocol)?);
+        }
+
+        match cx.get_single_option::<Hub>() {
+            Some(hub) => {
+                server
+                    .run_with_graceful_shutdown(router, hub.drain_on(shutdown_signal()))
+                    .await
+            }
+            None => {
+                server
+                    .run_with_graceful_shutdown(router, shutdown_signal())
+                    .await
+            }
+        }
     }
 }
 
@@ -113,10 +143,32 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
     let config = H::load_config(&env).unwrap();
 
     let server_cfg = ServerConfig::new(&config);
+
+    let problem_details = server_cfg.problem_details;
+
+    #[cfg(feature = "fs")]
+    let upload_temp_dir = server_cfg
+        .upload_temp_dir
+        .clone()
+        .map(crate::extract::multipart::UploadTempDir);
+
     let request_body_limit = server_cfg.request_body_limit;
+    let request_timeout = server_cfg.request_timeout;
+    let body_timeout = BodyTimeout::from(&server_cfg.request_body_timeout);
     let root_path = server_cfg.root_path.clone();
+    let cors = server_cfg.cors.clone();
+    #[cfg(feature = "compression")]
+    let compression = server_cfg.compression.clone();
     let full_non_application_root_path = server_cfg.full_non_application_root_path();
 
+    #[cfg(feature = "session")]
+    let session = crate::config::session::SessionConfig::new(&config);
+
+    #[cfg(feature = "cookie")]
+    let cookie_keys = crate::extract::cookie::CookieKeys::from(
+        &crate::config::cookie::CookieConfig::new(&config),
+    );
+
     let mut cx = H::create_context(config, env).await;
     cx.insert_single_owner(map);
 
@@ -126,11 +178,13 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
     let mut schemas_in_progress = Vec::with_capacity(16);
     let mut security_schemes = BTreeMap::new();
     let mut tags = BTreeMap::new();
+    let mut examples = BTreeMap::new();
 
-    cx.resolve_by_type_async::<Arc<dyn Controller>>()
-        .await
-        .into_iter()
-        .for_each(|c| {
+    let controllers = cx.resolve_by_type_async::<Arc<dyn Controller>>().await;
+
+    // the error responses of the endpoints are documented according to `server.problem_details`
+    with_problem_details(problem_details, || {
+        controllers.into_iter().for_each(|c| {
             c.insert_routes(
                 &mut cx,
                 &mut route_table,
@@ -139,13 +193,34 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
                 &mut schemas_in_progress,
                 &mut security_schemes,
                 &mut tags,
+                &mut examples,
             );
-        });
+        })
+    });
 
     let info = H::openapi_info(&mut cx);
     let servers = H::openapi_servers(&mut cx);
     let security = H::openapi_security_requirements(&mut cx);
 
+    // documented on every endpoint, as any of them may time out
+    let timeout_responses = request_timeout.map(|_| {
+        with_problem_details(problem_details, || {
+            transform_responses(TimeoutError::responses(
+                &mut schemas,
+                &mut schemas_in_progress,
+            ))
+        })
+    });
+
+    // documented on the responses which may be compressed
+    #[cfg(feature = "compression")]
+    let content_encoding = if compression.enabled {
+        crate::middleware::Compression::from(&compression)
+            .content_encoding_header(&mut schemas, &mut schemas_in_progress)
+    } else {
+        None
+    };
+
     let schemas = schemas
         .into_iter()
         .map(|(name, schema)| (name, ReferenceOr::Item(schema)))
@@ -153,6 +228,7 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
 
     let mut duplicate_endpoints: HashMap<String, Vec<Method>> = HashMap::new();
     let mut appeared_method_cache: Vec<Method> = Vec::new();
+    let mut operation_ids: HashMap<String, Vec<String>> = HashMap::new();
 
     let paths = paths
         .into_iter()
@@ -163,7 +239,29 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
 
             appeared_method_cache.clear();
 
-            operations.into_iter().for_each(|(method, operation)| {
+            operations.into_iter().for_each(|(method, mut operation)| {
+                if let Some(timeout_responses) = &timeout_responses {
+                    timeout_responses.iter().for_each(|(status, response)| {
+                        operation
+                            .responses
+                            .responses
+                            .entry(status.clone())
+                            .or_insert_with(|| response.clone());
+                    });
+                }
+
+                #[cfg(feature = "compression")]
+                if let Some(header) = &content_encoding {
+                    crate::middleware::document_content_encoding(&mut operation.responses, header);
+                }
+
+                if let Some(operation_id) = &operation.operation_id {
+                    operation_ids
+                        .entry(operation_id.clone())
+                        .or_default()
+                        .push(format!("{method} {path}"));
+                }
+
                 match method {
                     Method::GET => path_item.get = Some(operation),
                     Method::POST => path_item.post = Some(operation),
@@ -200,6 +298,15 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
         panic!("duplicate endpoints: {:#?}", duplicate_endpoints);
     }
 
+    let duplicate_operation_ids = operation_ids
+        .into_iter()
+        .filter(|(_, endpoints)| endpoints.len() > 1)
+        .collect::<BTreeMap<_, _>>();
+
+    if !duplicate_operation_ids.is_empty() {
+        panic!("duplicate operation ids: {:#?}", duplicate_operation_ids);
+    }
+
     let mut tag_name_to_type_names: BTreeMap<_, Vec<_>> = BTreeMap::new();
 
     let tags = tags
@@ -228,6 +335,30 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
         );
     }
 
+    let mut example_name_to_type_names: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+    let examples = examples
+        .into_iter()
+        .map(|(example_type_name, (example_name, example))| {
+            example_name_to_type_names
+                .entry(example_name)
+                .or_default()
+                .push(example_type_name);
+
+            (example_name.to_string(), ReferenceOr::Item(example))
+        })
+        .collect::<IndexMap<_, _>>();
+
+    // retains only the example types with the same example name
+    example_name_to_type_names.retain(|_, v| v.len() > 1);
+
+    if !example_name_to_type_names.is_empty() {
+        panic!(
+            "multiple examples with the same name: {:#?}",
+            example_name_to_type_names
+        );
+    }
+
     let mut schemes_name_to_type_names: BTreeMap<_, Vec<_>> = BTreeMap::new();
 
     let mut security_schemes = security_schemes
@@ -284,7 +415,7 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
         schemas,
         responses: Default::default(),
         parameters: Default::default(),
-        examples: Default::default(),
+        examples,
         request_bodies: Default::default(),
         headers: Default::default(),
         security_schemes,
@@ -328,17 +459,17 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
     }
 
     for plugin in cx.resolve_by_type_async::<Arc<dyn Plugin>>().await {
-        let (path, map) = plugin.create_route(&mut cx);
+        for (path, map) in plugin.create_routes(&mut cx) {
+            let path = full_non_application_root_path.clone().join(path);
 
-        let path = full_non_application_root_path.clone().join(path);
+            tracing::info!("registering plugin: {}", path);
 
-        tracing::info!("registering plugin: {}", path);
+            let path = path.into_inner();
+            let path_cloned = path.clone();
 
-        let path = path.into_inner();
-        let path_cloned = path.clone();
-
-        if let Err(e) = router.insert(path, MethodRouter::from(map)) {
-            insert_errors.push((e, path_cloned));
+            if let Err(e) = router.insert(path, MethodRouter::from(map)) {
+                insert_errors.push((e, path_cloned));
+            }
         }
     }
 
@@ -348,11 +479,73 @@ pub async fn create_app<H: Hooks>(env: Environment) -> (Context, impl Handler) {
 
     H::after_routes(&router);
 
-    let (cx, router) = H::before_run(cx, router).await;
+    let cors_enabled = cors.enabled;
+    let cors = Cors::from(&cors).routes(&router);
+
+    #[allow(unused_mut)]
+    let (mut cx, router) = H::before_run(cx, router).await;
+
+    let router = router.around(resolve_download);
+
+    // a store registered in the context takes precedence over the configured one
+    #[cfg(feature = "session")]
+    let router = {
+        use crate::session::{SessionMiddleware, SessionStore};
+
+        // only resolved when enabled, as creating a store may connect to its backend
+        let store = if session.enabled {
+            cx.resolve_option_async::<Arc<dyn SessionStore>>().await
+        } else {
+            None
+        };
+
+        let middleware = match store {
+            Some(store) => SessionMiddleware::from_config(store, &session),
+            None => SessionMiddleware::from(&session),
+        };
+
+        router.with_if(session.enabled, middleware)
+    };
+
+    #[cfg(feature = "compression")]
+    let router = router.with_if(
+        compression.enabled,
+        crate::middleware::Compression::from(&compression),
+    );
 
-    let router = router.before(move |mut req| async move {
-        *req.body_limit() = BodyLimit(request_body_limit);
-        Ok(req)
+    // inside `Cors`, so that browsers can read the `504`
+    let router = router.with_if(
+        request_timeout.is_some(),
+        Timeout::new(request_timeout.unwrap_or_default()),
+    );
+
+    let router = router.with_if(cors_enabled, cors);
+
+    let router = router.before(move |mut req| {
+        #[cfg(feature = "cookie")]
+        req.head.extensions.insert(cookie_keys.clone());
+
+        #[cfg(feature = "fs")]
+        if let Some(dir) = &upload_temp_dir {
+            req.head.extensions.insert(dir.clone());
+        }
+
+        async move {
+            *req.body_limit() = BodyLimit(request_body_limit);
+            *req.body_timeout() = body_timeout;
+            Ok(req)
+        }
+    });
+
+    // errors are rendered according to `server.problem_details`, only in the requests of this app
+    let router = router.around(move |inner, req| {
+        scope_problem_details(problem_details, async move {
+            // an error created outside of the scope, such as in a spawned task, is rendered here
+            inner
+                .call(req)
+                .await
+                .map_err(|e| e.resolve_problem_details(problem_details))
+        })
     });
 
     (cx, router)
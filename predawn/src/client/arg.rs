@@ -0,0 +1,92 @@
+use bytes::Bytes;
+use headers::{Header, HeaderMapExt};
+use predawn_core::media_type::MediaType;
+use serde::Serialize;
+use snafu::ResultExt;
+
+use super::{
+    ClientError, ClientRequest,
+    error::{SerializeFormSnafu, SerializeJsonSnafu},
+};
+use crate::{
+    extract::{Path, Query, TypedHeader},
+    payload::{Form, Json},
+    validate::Valid,
+};
+
+/// An argument of an endpoint, which the generated client puts into the request.
+///
+/// Endpoints that take extractors without a counterpart on the client side, like [`Method`](http::Method)
+/// or [`RemoteAddr`](predawn_core::request::RemoteAddr), need `#[endpoint(client = false)]`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be sent by a generated client",
+    note = "skip the endpoint with `#[endpoint(client = false)]`"
+)]
+pub trait ClientArg {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError>;
+}
+
+impl<T: Serialize> ClientArg for Path<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        request.path_params(&self.0)
+    }
+}
+
+impl<T: Serialize> ClientArg for Query<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        request.query(&self.0)
+    }
+}
+
+impl<T: Header> ClientArg for TypedHeader<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        request.headers_mut().typed_insert(self.0);
+        Ok(())
+    }
+}
+
+impl<T: Serialize> ClientArg for Json<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        let body = crate::util::serialize_json(&self.0).context(SerializeJsonSnafu)?;
+        request.body(<Self as MediaType>::MEDIA_TYPE, body);
+        Ok(())
+    }
+}
+
+impl<T: Serialize> ClientArg for Form<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        let body = crate::util::serialize_form(&self.0).context(SerializeFormSnafu)?;
+        request.body(<Self as MediaType>::MEDIA_TYPE, body);
+        Ok(())
+    }
+}
+
+macro_rules! some_impl {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl ClientArg for $ty {
+                fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+                    request.body(<Self as MediaType>::MEDIA_TYPE, self);
+                    Ok(())
+                }
+            }
+        )+
+    };
+}
+
+some_impl![String, Bytes, Vec<u8>];
+
+impl<T: ClientArg> ClientArg for Option<T> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        match self {
+            Some(arg) => arg.apply(request),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<E: ClientArg> ClientArg for Valid<E> {
+    fn apply(self, request: &mut ClientRequest) -> Result<(), ClientError> {
+        self.0.apply(request)
+    }
+}
@@ -0,0 +1,164 @@
+use std::string::FromUtf8Error;
+
+use bytes::Bytes;
+use error2::{ErrorExt, Location, NextError};
+use http::StatusCode;
+use predawn_core::problem::ProblemDetails;
+use serde::de::DeserializeOwned;
+use snafu::Snafu;
+
+use crate::response_error::DeserializeJsonError;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ClientError {
+    #[snafu(display("failed to serialize path parameters"))]
+    SerializePathError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_json::Error,
+    },
+    #[snafu(display("path parameters must be strings, numbers or booleans"))]
+    UnsupportedPathParam {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("missing path parameter `{name}`"))]
+    MissingPathParam {
+        #[snafu(implicit)]
+        location: Location,
+        name: Box<str>,
+    },
+    #[snafu(display("failed to serialize query"))]
+    SerializeQueryError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_path_to_error::Error<serde_html_form::ser::Error>,
+    },
+    #[snafu(display("failed to serialize json body"))]
+    SerializeJsonError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_path_to_error::Error<serde_json::Error>,
+    },
+    #[snafu(display("failed to serialize form body"))]
+    SerializeFormError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_path_to_error::Error<serde_html_form::ser::Error>,
+    },
+    #[snafu(display("failed to send request"))]
+    SendError {
+        #[snafu(implicit)]
+        location: Location,
+        source: reqwest::Error,
+    },
+    #[snafu(display("failed to read response body"))]
+    ReadBodyError {
+        #[snafu(implicit)]
+        location: Location,
+        source: reqwest::Error,
+    },
+    #[snafu(display("unexpected response status `{status}`"))]
+    UnexpectedStatus {
+        #[snafu(implicit)]
+        location: Location,
+        status: StatusCode,
+        body: Bytes,
+    },
+    #[snafu(display("endpoint failed with status `{status}`"))]
+    EndpointError {
+        #[snafu(implicit)]
+        location: Location,
+        status: StatusCode,
+        /// The error of the endpoint, read from its problem details or its message.
+        problem: Box<ProblemDetails>,
+        body: Bytes,
+    },
+    #[snafu(display("failed to deserialize json body"))]
+    DeserializeJsonBodyError {
+        #[snafu(implicit)]
+        location: Location,
+        source: DeserializeJsonError,
+    },
+    #[snafu(display("failed to deserialize form body"))]
+    DeserializeFormBodyError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_path_to_error::Error<serde_html_form::de::Error>,
+    },
+    #[snafu(display("response body is not valid utf-8"))]
+    InvalidUtf8Error {
+        #[snafu(implicit)]
+        location: Location,
+        source: FromUtf8Error,
+    },
+}
+
+impl ClientError {
+    /// Return the status of a response whose status is not successful.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::UnexpectedStatus { status, .. }
+            | ClientError::EndpointError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Return the body of a response whose status is not successful.
+    pub fn body(&self) -> Option<&Bytes> {
+        match self {
+            ClientError::UnexpectedStatus { body, .. }
+            | ClientError::EndpointError { body, .. } => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Return the error of an endpoint that returns a `Result`.
+    pub fn problem(&self) -> Option<&ProblemDetails> {
+        match self {
+            ClientError::EndpointError { problem, .. } => Some(problem),
+            _ => None,
+        }
+    }
+
+    /// Deserializes the JSON body of a response whose status is not successful, such as the
+    /// problem details of an error.
+    pub fn json_body<T: DeserializeOwned>(&self) -> Option<Result<T, DeserializeJsonError>> {
+        self.body().map(|body| crate::util::deserialize_json(body))
+    }
+}
+
+impl ErrorExt for ClientError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            ClientError::SerializePathError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            ClientError::UnsupportedPathParam { location }
+            | ClientError::MissingPathParam { location, .. }
+            | ClientError::UnexpectedStatus { location, .. }
+            | ClientError::EndpointError { location, .. } => (*location, NextError::None),
+            ClientError::SerializeQueryError { location, source }
+            | ClientError::SerializeFormError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            ClientError::SerializeJsonError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            ClientError::SendError { location, source }
+            | ClientError::ReadBodyError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            ClientError::DeserializeJsonBodyError { location, source } => {
+                (*location, NextError::Ext(source))
+            }
+            ClientError::DeserializeFormBodyError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            ClientError::InvalidUtf8Error { location, source } => {
+                (*location, NextError::Std(source))
+            }
+        }
+    }
+}
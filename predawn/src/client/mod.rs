@@ -0,0 +1,284 @@
+mod arg;
+mod error;
+mod response;
+
+use std::collections::{BTreeMap, VecDeque};
+
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderValue, Method,
+    header::{CONTENT_TYPE, IntoHeaderName},
+};
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use serde::Serialize;
+use serde_json::Value;
+use snafu::{OptionExt, ResultExt};
+
+use self::error::{
+    MissingPathParamSnafu, SendSnafu, SerializePathSnafu, SerializeQuerySnafu,
+    UnsupportedPathParamSnafu,
+};
+pub use self::{arg::ClientArg, error::ClientError, response::FromClientResponse};
+use crate::normalized_path::NormalizedPath;
+
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+const CATCH_ALL: &AsciiSet = &PATH_SEGMENT.remove(b'/');
+
+/// The HTTP client behind the clients generated by `#[controller(client)]`.
+#[derive(Debug, Clone)]
+pub struct Client {
+    client: reqwest::Client,
+    base_url: Box<str>,
+}
+
+impl Client {
+    /// `base_url` is the scheme and authority of the server, like `http://localhost:9612`, with an
+    /// optional path prefix.
+    pub fn new<U: Into<String>>(base_url: U) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_client<U: Into<String>>(client: reqwest::Client, base_url: U) -> Self {
+        let mut base_url = base_url.into();
+
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+
+        Self {
+            client,
+            base_url: base_url.into(),
+        }
+    }
+
+    #[inline]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    #[inline]
+    pub fn reqwest_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub async fn send<R: FromClientResponse>(
+        &self,
+        request: ClientRequest,
+    ) -> Result<R::Output, ClientError> {
+        let mut url = format!("{}{}", self.base_url, request.render_path()?);
+
+        let ClientRequest {
+            method,
+            query,
+            headers,
+            body,
+            ..
+        } = request;
+
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+
+        let mut builder = self.client.request(method, url).headers(headers);
+
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await.context(SendSnafu)?;
+
+        R::from_client_response(response).await
+    }
+}
+
+/// A request of an endpoint, built up by the [`ClientArg`]s of the endpoint.
+#[derive(Debug)]
+pub struct ClientRequest {
+    method: Method,
+    path: NormalizedPath,
+    named_params: BTreeMap<String, String>,
+    positional_params: VecDeque<String>,
+    query: String,
+    headers: HeaderMap,
+    body: Option<Bytes>,
+}
+
+impl ClientRequest {
+    /// `path` is the route of the endpoint, whose parameters are filled in from the
+    /// [`path_params`](Self::path_params).
+    pub fn new(method: Method, path: NormalizedPath) -> Self {
+        Self {
+            method,
+            path,
+            named_params: BTreeMap::new(),
+            positional_params: VecDeque::new(),
+            query: String::new(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// Structs and maps fill in the parameters by name, tuples, sequences and single values by
+    /// position.
+    pub fn path_params<T: Serialize + ?Sized>(&mut self, params: &T) -> Result<(), ClientError> {
+        match serde_json::to_value(params).context(SerializePathSnafu)? {
+            Value::Object(object) => {
+                for (name, value) in object {
+                    let value = path_param_to_string(value)?;
+                    self.named_params.insert(name, value);
+                }
+            }
+            Value::Array(array) => {
+                for value in array {
+                    let value = path_param_to_string(value)?;
+                    self.positional_params.push_back(value);
+                }
+            }
+            value => {
+                let value = path_param_to_string(value)?;
+                self.positional_params.push_back(value);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn query<T: Serialize + ?Sized>(&mut self, query: &T) -> Result<(), ClientError> {
+        let query = crate::util::serialize_form(query).context(SerializeQuerySnafu)?;
+
+        if query.is_empty() {
+            return Ok(());
+        }
+
+        if !self.query.is_empty() {
+            self.query.push('&');
+        }
+
+        self.query.push_str(&query);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    pub fn header<K: IntoHeaderName>(&mut self, name: K, value: HeaderValue) {
+        self.headers.insert(name, value);
+    }
+
+    pub fn body<B: Into<Bytes>>(&mut self, content_type: &'static str, body: B) {
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        self.body = Some(body.into());
+    }
+
+    fn render_path(&self) -> Result<String, ClientError> {
+        let template = self.path.as_str();
+
+        let mut positional_params = self.positional_params.iter();
+        let mut path = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find(['{', '}']) {
+            path.push_str(&rest[..start]);
+
+            // `{{` and `}}` are escaped braces
+            if rest[start + 1..].starts_with(&rest[start..=start]) {
+                path.push_str(&rest[start..=start]);
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            let Some(len) = rest[start..].find('}') else {
+                path.push_str(&rest[start..]);
+                rest = "";
+                break;
+            };
+
+            let param = &rest[start + 1..start + len];
+            rest = &rest[start + len + 1..];
+
+            let (name, ascii_set) = match param.strip_prefix('*') {
+                Some(name) => (name, CATCH_ALL),
+                None => (param, PATH_SEGMENT),
+            };
+
+            let value = self
+                .named_params
+                .get(name)
+                .or_else(|| positional_params.next())
+                .context(MissingPathParamSnafu { name })?;
+
+            path.extend(utf8_percent_encode(value, ascii_set));
+        }
+
+        path.push_str(rest);
+        Ok(path)
+    }
+}
+
+fn path_param_to_string(value: Value) -> Result<String, ClientError> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null | Value::Array(_) | Value::Object(_) => UnsupportedPathParamSnafu.fail(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Method;
+    use serde::Serialize;
+
+    use super::ClientRequest;
+    use crate::normalized_path::NormalizedPath;
+
+    #[derive(Serialize)]
+    struct Person {
+        name: &'static str,
+        age: u8,
+    }
+
+    fn render<T: Serialize>(path: &str, params: &T) -> String {
+        let mut request = ClientRequest::new(Method::GET, NormalizedPath::new(path));
+        request.path_params(params).unwrap();
+        request.render_path().unwrap()
+    }
+
+    #[test]
+    fn test_render_path() {
+        assert_eq!(
+            render(
+                "/{age}/{name}",
+                &Person {
+                    name: "a b/c",
+                    age: 18
+                }
+            ),
+            "/18/a%20b%2Fc"
+        );
+
+        assert_eq!(render("/users/{id}", &42), "/users/42");
+        assert_eq!(render("/{a}/{b}", &("x", true)), "/x/true");
+        assert_eq!(render("/files/{*path}", &"a/b c.txt"), "/files/a/b%20c.txt");
+        assert_eq!(render("/{{literal}}/{id}", &1), "/{literal}/1");
+
+        let request = ClientRequest::new(Method::GET, NormalizedPath::new("/{id}"));
+        assert!(request.render_path().is_err());
+    }
+}
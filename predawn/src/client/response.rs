@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+
+use bytes::{Bytes, BytesMut};
+use http::{StatusCode, header::CONTENT_TYPE};
+use predawn_core::{problem::ProblemDetails, response_error::ResponseError};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use snafu::ResultExt;
+
+use super::{
+    ClientError,
+    error::{
+        DeserializeFormBodySnafu, DeserializeJsonBodySnafu, EndpointSnafu, InvalidUtf8Snafu,
+        ReadBodySnafu, UnexpectedStatusSnafu,
+    },
+};
+use crate::{
+    payload::{Form, Json},
+    response::Download,
+};
+
+/// The return type of an endpoint, which the generated client reads from the response.
+///
+/// Responses whose status is not successful are returned as [`ClientError::UnexpectedStatus`], or as
+/// [`ClientError::EndpointError`] for an endpoint that returns a `Result`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be read by a generated client",
+    note = "skip the endpoint with `#[endpoint(client = false)]`"
+)]
+pub trait FromClientResponse {
+    type Output;
+
+    fn from_client_response(
+        response: reqwest::Response,
+    ) -> impl Future<Output = Result<Self::Output, ClientError>> + Send;
+}
+
+async fn success_bytes(response: reqwest::Response) -> Result<Bytes, ClientError> {
+    let status = response.status();
+    let body = response.bytes().await.context(ReadBodySnafu)?;
+
+    if !status.is_success() {
+        return UnexpectedStatusSnafu { status, body }.fail();
+    }
+
+    Ok(body)
+}
+
+impl FromClientResponse for () {
+    type Output = ();
+
+    async fn from_client_response(response: reqwest::Response) -> Result<(), ClientError> {
+        success_bytes(response).await?;
+        Ok(())
+    }
+}
+
+impl FromClientResponse for StatusCode {
+    type Output = StatusCode;
+
+    async fn from_client_response(response: reqwest::Response) -> Result<StatusCode, ClientError> {
+        let status = response.status();
+        success_bytes(response).await?;
+        Ok(status)
+    }
+}
+
+/// The response as is, for endpoints that build their response by hand.
+impl<B> FromClientResponse for http::Response<B> {
+    type Output = reqwest::Response;
+
+    async fn from_client_response(
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, ClientError> {
+        Ok(response)
+    }
+}
+
+macro_rules! impl_for_str {
+    ($output:ty; $($ty:ty),+ $(,)?) => {
+        $(
+            impl FromClientResponse for $ty {
+                type Output = $output;
+
+                async fn from_client_response(response: reqwest::Response) -> Result<$output, ClientError> {
+                    let bytes = success_bytes(response).await?;
+                    let string = String::from_utf8(bytes.into()).context(InvalidUtf8Snafu)?;
+                    Ok(string.into())
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_for_bytes {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl FromClientResponse for $ty {
+                type Output = Bytes;
+
+                async fn from_client_response(response: reqwest::Response) -> Result<Bytes, ClientError> {
+                    success_bytes(response).await
+                }
+            }
+        )+
+    };
+}
+
+impl_for_str!(String; &'static str, Cow<'static, str>, String);
+impl_for_str!(Box<str>; Box<str>);
+impl_for_bytes![
+    &'static [u8],
+    Cow<'static, [u8]>,
+    Vec<u8>,
+    Bytes,
+    BytesMut,
+    Box<[u8]>
+];
+
+impl<T: DeserializeOwned> FromClientResponse for Json<T> {
+    type Output = Json<T>;
+
+    async fn from_client_response(response: reqwest::Response) -> Result<Json<T>, ClientError> {
+        let bytes = success_bytes(response).await?;
+        let json = crate::util::deserialize_json(&bytes).context(DeserializeJsonBodySnafu)?;
+        Ok(Json(json))
+    }
+}
+
+impl<T: DeserializeOwned> FromClientResponse for Form<T> {
+    type Output = Form<T>;
+
+    async fn from_client_response(response: reqwest::Response) -> Result<Form<T>, ClientError> {
+        let bytes = success_bytes(response).await?;
+        let form = crate::util::deserialize_form(&bytes).context(DeserializeFormBodySnafu)?;
+        Ok(Form(form))
+    }
+}
+
+/// The error of an endpoint is a response whose status is not successful, returned as
+/// [`ClientError::EndpointError`] with the [`ProblemDetails`] it was rendered as, or its message.
+impl<T: FromClientResponse, E: ResponseError> FromClientResponse for Result<T, E> {
+    type Output = T::Output;
+
+    async fn from_client_response(response: reqwest::Response) -> Result<T::Output, ClientError> {
+        let status = response.status();
+
+        if status.is_success() {
+            return T::from_client_response(response).await;
+        }
+
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| content_type.contains("json"));
+
+        let body = response.bytes().await.context(ReadBodySnafu)?;
+
+        let problem = is_json
+            .then(|| serde_json::from_slice::<Value>(&body).ok())
+            .flatten()
+            .and_then(|value| error_problem(status, value))
+            .unwrap_or_else(|| ProblemDetails::new(status).detail(String::from_utf8_lossy(&body)));
+
+        EndpointSnafu {
+            status,
+            problem: Box::new(problem),
+            body,
+        }
+        .fail()
+    }
+}
+
+/// Problem details, or the `errors` of a validation error rendered without them.
+fn error_problem(status: StatusCode, value: Value) -> Option<ProblemDetails> {
+    if value.get("status").is_some() {
+        return ProblemDetails::from_json(value);
+    }
+
+    let Value::Object(mut map) = value else {
+        return None;
+    };
+
+    match map.remove("errors") {
+        Some(Value::Array(errors)) => Some(
+            errors
+                .into_iter()
+                .fold(ProblemDetails::new(status), ProblemDetails::error),
+        ),
+        _ => None,
+    }
+}
+
+impl<T: FromClientResponse> FromClientResponse for Download<T> {
+    type Output = T::Output;
+
+    fn from_client_response(
+        response: reqwest::Response,
+    ) -> impl Future<Output = Result<T::Output, ClientError>> + Send {
+        T::from_client_response(response)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[cfg(feature = "cookie")]
+impl<T: FromClientResponse> FromClientResponse for crate::response::SetCookie<T> {
+    type Output = T::Output;
+
+    fn from_client_response(
+        response: reqwest::Response,
+    ) -> impl Future<Output = Result<T::Output, ClientError>> + Send {
+        T::from_client_response(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use predawn_core::{error::BoxError, problem::ProblemDetails};
+
+    use super::FromClientResponse;
+    use crate::response_error::TimeoutError;
+
+    fn response(status: StatusCode, content_type: &str, body: &str) -> reqwest::Response {
+        http::Response::builder()
+            .status(status)
+            .header("content-type", content_type)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_error() -> Result<(), BoxError> {
+        let ok = response(StatusCode::OK, "text/plain", "hello");
+        let text = <Result<String, TimeoutError>>::from_client_response(ok).await?;
+        assert_eq!(text, "hello");
+
+        let plain = response(StatusCode::GATEWAY_TIMEOUT, "text/plain", "timed out");
+        let error = <Result<String, TimeoutError>>::from_client_response(plain)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status(), Some(StatusCode::GATEWAY_TIMEOUT));
+        assert_eq!(
+            error.problem(),
+            Some(&ProblemDetails::new(StatusCode::GATEWAY_TIMEOUT).detail("timed out"))
+        );
+
+        let problem = ProblemDetails::new(StatusCode::GATEWAY_TIMEOUT).detail("timed out");
+        let json = response(
+            StatusCode::GATEWAY_TIMEOUT,
+            "application/problem+json",
+            &problem.to_json().to_string(),
+        );
+        let error = <Result<String, TimeoutError>>::from_client_response(json)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.problem(), Some(&problem));
+
+        let not_found = response(StatusCode::NOT_FOUND, "text/plain", "");
+        let error = StatusCode::from_client_response(not_found)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+        assert!(error.problem().is_none());
+
+        Ok(())
+    }
+}
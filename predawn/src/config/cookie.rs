@@ -0,0 +1,48 @@
+use rudi::Singleton;
+use serde::{Deserialize, Serialize};
+
+use super::{Config, ConfigPrefix};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CookieConfig {
+    /// The key used to sign and encrypt cookies, at least 32 bytes long.
+    ///
+    /// If not set, a random key is generated on startup.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Keys used before `key`, cookies signed or encrypted with them are still accepted.
+    #[serde(default)]
+    pub previous_keys: Vec<String>,
+}
+
+#[Singleton(eager_create)]
+impl CookieConfig {
+    #[di]
+    pub fn new(#[di(ref)] config: &Config) -> Self {
+        config.get().expect("failed to load `CookieConfig`")
+    }
+}
+
+impl ConfigPrefix for CookieConfig {
+    const PREFIX: &'static str = "cookie";
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    None,
+}
+
+impl From<SameSite> for cookie::SameSite {
+    fn from(same_site: SameSite) -> Self {
+        match same_site {
+            SameSite::Strict => cookie::SameSite::Strict,
+            SameSite::Lax => cookie::SameSite::Lax,
+            SameSite::None => cookie::SameSite::None,
+        }
+    }
+}
@@ -1,6 +1,13 @@
+#[cfg(feature = "cookie")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+pub mod cookie;
 pub mod logger;
 pub mod openapi;
+pub mod pubsub;
 pub mod server;
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub mod session;
 
 use std::{
     env,
@@ -2,13 +2,21 @@ use rudi::Singleton;
 use serde::{Deserialize, Serialize};
 
 use super::{Config, ConfigPrefix};
-use crate::normalized_path::NormalizedPath;
+use crate::{normalized_path::NormalizedPath, openapi::OpenAPIVersion};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct OpenAPIConfig {
+    /// `"3.0"` or `"3.1"`.
+    #[serde(default)]
+    pub version: OpenAPIVersion,
+    /// Whether the JSON document is pretty-printed.
+    #[serde(default)]
+    pub pretty: bool,
     #[serde(default = "default_json_path")]
     pub json_path: NormalizedPath,
+    #[serde(default = "default_yaml_path")]
+    pub yaml_path: NormalizedPath,
     #[serde(default = "default_swagger_ui_path")]
     pub swagger_ui_path: NormalizedPath,
     #[serde(default = "default_rapidoc_path")]
@@ -33,6 +41,10 @@ fn default_json_path() -> NormalizedPath {
     "/openapi.json".into()
 }
 
+fn default_yaml_path() -> NormalizedPath {
+    "/openapi.yaml".into()
+}
+
 fn default_swagger_ui_path() -> NormalizedPath {
     "/swagger-ui".into()
 }
@@ -56,7 +68,10 @@ fn default_openapi_explorer_path() -> NormalizedPath {
 impl Default for OpenAPIConfig {
     fn default() -> Self {
         Self {
+            version: Default::default(),
+            pretty: false,
             json_path: default_json_path(),
+            yaml_path: default_yaml_path(),
             swagger_ui_path: default_swagger_ui_path(),
             rapidoc_path: default_rapidoc_path(),
             scalar_path: default_scalar_path(),
@@ -0,0 +1,52 @@
+use rudi::Singleton;
+use serde::{Deserialize, Serialize};
+
+use super::{Config, ConfigPrefix};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PubSubConfig {
+    /// How many messages are buffered for each subscriber.
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+    /// What happens when the buffer of a subscriber is full.
+    #[serde(default)]
+    pub lag_policy: LagPolicy,
+}
+
+#[Singleton(eager_create)]
+impl PubSubConfig {
+    #[di]
+    pub fn new(#[di(ref)] config: &Config) -> Self {
+        config.get().expect("failed to load `PubSubConfig`")
+    }
+}
+
+impl ConfigPrefix for PubSubConfig {
+    const PREFIX: &'static str = "pubsub";
+}
+
+const fn default_capacity() -> usize {
+    64
+}
+
+impl Default for PubSubConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_capacity(),
+            lag_policy: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LagPolicy {
+    /// Drops the oldest buffered message to make room for the new one.
+    #[default]
+    DropOldest,
+    /// Drops the new message.
+    DropNewest,
+    /// Unsubscribes the subscriber.
+    Disconnect,
+}
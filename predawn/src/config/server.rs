@@ -1,6 +1,11 @@
-use std::net::{IpAddr, Ipv4Addr};
+#[cfg(any(feature = "tls", feature = "fs"))]
+use std::path::PathBuf;
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
 
-use predawn_core::request::DEFAULT_BODY_LIMIT;
+use predawn_core::request::{BodyTimeout, DEFAULT_BODY_LIMIT, MinThroughput};
 use rudi::Singleton;
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +25,43 @@ pub struct ServerConfig {
     pub non_application_root_path: NormalizedPath,
     #[serde(default = "default_request_body_limit")]
     pub request_body_limit: usize,
+    /// Requests not handled within this time are answered with `504 Gateway Timeout`, endpoints
+    /// can override it with `#[endpoint(timeout = "30s")]`.
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub request_timeout: Option<Duration>,
+    #[serde(default)]
+    pub request_body_timeout: BodyTimeoutConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Renders errors as RFC 9457 `application/problem+json` instead of `text/plain`.
+    #[serde(default)]
+    pub problem_details: bool,
+    /// Forces the protocol of connections, `auto` serves both HTTP/1 and HTTP/2.
+    #[serde(default)]
+    pub protocol: HttpProtocol,
+    #[serde(default)]
+    pub http1: Http1Config,
+    #[serde(default)]
+    pub http2: Http2Config,
+    /// Connections beyond this are not accepted until others are closed.
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    #[serde(default)]
+    pub tcp_nodelay: bool,
+    /// Sets `SO_REUSEPORT` on the listener, so that several processes can listen on the same port,
+    /// only supported on Unix.
+    #[serde(default)]
+    pub reuse_port: bool,
+    /// The directory `TempFileUpload`s are spooled to, defaults to the system temporary directory.
+    #[cfg(feature = "fs")]
+    #[serde(default)]
+    pub upload_temp_dir: Option<PathBuf>,
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsConfig>,
+    #[cfg(feature = "compression")]
+    #[serde(default)]
+    pub compression: CompressionConfig,
 }
 
 #[Singleton(eager_create)]
@@ -54,6 +96,10 @@ const fn default_request_body_limit() -> usize {
     DEFAULT_BODY_LIMIT
 }
 
+const fn default_true() -> bool {
+    true
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -62,6 +108,22 @@ impl Default for ServerConfig {
             root_path: default_root_path(),
             non_application_root_path: default_non_application_root_path(),
             request_body_limit: default_request_body_limit(),
+            request_timeout: None,
+            request_body_timeout: Default::default(),
+            cors: Default::default(),
+            problem_details: false,
+            protocol: Default::default(),
+            http1: Default::default(),
+            http2: Default::default(),
+            max_connections: None,
+            tcp_nodelay: false,
+            reuse_port: false,
+            #[cfg(feature = "fs")]
+            upload_temp_dir: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "compression")]
+            compression: Default::default(),
         }
     }
 }
@@ -69,3 +131,248 @@ impl Default for ServerConfig {
 impl ConfigPrefix for ServerConfig {
     const PREFIX: &'static str = "server";
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Exact origins, `*` for any origin, or wildcard patterns like `https://*.example.com`.
+    #[serde(default)]
+    pub allow_origins: Vec<String>,
+    /// Regexes that must match the whole origin.
+    #[serde(default)]
+    pub allow_origin_regexes: Vec<String>,
+    /// If empty, preflight requests are answered with the methods registered for the path.
+    #[serde(default)]
+    pub allow_methods: Vec<String>,
+    /// `*` allows any request header.
+    #[serde(default)]
+    pub allow_headers: Vec<String>,
+    #[serde(default)]
+    pub expose_headers: Vec<String>,
+    /// Can not be combined with the `*` origin.
+    #[serde(default)]
+    pub allow_credentials: bool,
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub max_age: Option<Duration>,
+}
+
+/// Requests whose body is received too slowly are answered with `408 Request Timeout`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BodyTimeoutConfig {
+    /// The longest time to wait for the next chunk of the body.
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub read_timeout: Option<Duration>,
+    /// The fewest bytes per second the body must be received at on average.
+    #[serde(default)]
+    pub min_throughput: Option<u64>,
+    /// How long the body may be received slower than `min_throughput` at the beginning.
+    #[serde(default = "default_min_throughput_grace_period")]
+    #[serde(deserialize_with = "duration_str::deserialize_duration")]
+    pub min_throughput_grace_period: Duration,
+}
+
+const fn default_min_throughput_grace_period() -> Duration {
+    Duration::from_secs(5)
+}
+
+impl Default for BodyTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            read_timeout: None,
+            min_throughput: None,
+            min_throughput_grace_period: default_min_throughput_grace_period(),
+        }
+    }
+}
+
+impl From<&BodyTimeoutConfig> for BodyTimeout {
+    fn from(config: &BodyTimeoutConfig) -> Self {
+        Self {
+            read_timeout: config.read_timeout,
+            min_throughput: config.min_throughput.map(|bytes_per_second| MinThroughput {
+                bytes_per_second,
+                grace_period: config.min_throughput_grace_period,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpProtocol {
+    #[default]
+    Auto,
+    Http1,
+    /// Without TLS, this is HTTP/2 with prior knowledge (h2c).
+    Http2,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http1Config {
+    #[serde(default = "default_true")]
+    pub keep_alive: bool,
+    /// Closes connections whose request headers are not received within this time.
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub header_read_timeout: Option<Duration>,
+    #[serde(default)]
+    pub max_headers: Option<usize>,
+    #[serde(default)]
+    pub max_buf_size: Option<usize>,
+    /// Whether to keep serving the response after the client shuts down its write side.
+    #[serde(default)]
+    pub half_close: bool,
+}
+
+impl Default for Http1Config {
+    fn default() -> Self {
+        Self {
+            keep_alive: default_true(),
+            header_read_timeout: None,
+            max_headers: None,
+            max_buf_size: None,
+            half_close: false,
+        }
+    }
+}
+
+/// Options left unset keep the defaults of `hyper`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http2Config {
+    #[serde(default)]
+    pub max_concurrent_streams: Option<u32>,
+    #[serde(default)]
+    pub initial_stream_window_size: Option<u32>,
+    #[serde(default)]
+    pub initial_connection_window_size: Option<u32>,
+    /// Adjusts the window sizes to the measured bandwidth, overriding the initial sizes.
+    #[serde(default)]
+    pub adaptive_window: bool,
+    #[serde(default)]
+    pub max_frame_size: Option<u32>,
+    #[serde(default)]
+    pub max_header_list_size: Option<u32>,
+    /// Sends pings at this interval, to keep the connection alive and detect dead peers.
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub keep_alive_interval: Option<Duration>,
+    /// Closes the connection if a ping is not acknowledged within this time.
+    #[serde(default = "default_keep_alive_timeout")]
+    #[serde(deserialize_with = "duration_str::deserialize_duration")]
+    pub keep_alive_timeout: Duration,
+}
+
+const fn default_keep_alive_timeout() -> Duration {
+    Duration::from_secs(20)
+}
+
+impl Default for Http2Config {
+    fn default() -> Self {
+        Self {
+            max_concurrent_streams: None,
+            initial_stream_window_size: None,
+            initial_connection_window_size: None,
+            adaptive_window: false,
+            max_frame_size: None,
+            max_header_list_size: None,
+            keep_alive_interval: None,
+            keep_alive_timeout: default_keep_alive_timeout(),
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM encoded certificate chain.
+    pub cert: PathBuf,
+    /// PEM encoded private key.
+    pub key: PathBuf,
+    /// PEM encoded CA certificates used to verify client certificates.
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+    /// Whether clients may connect without a certificate when `client_ca` is set.
+    #[serde(default)]
+    pub client_auth_optional: bool,
+    /// Whether to reload the certificates when the files change.
+    #[serde(default = "default_watch")]
+    pub watch: bool,
+    #[serde(default = "default_watch_interval")]
+    #[serde(deserialize_with = "duration_str::deserialize_duration")]
+    pub watch_interval: Duration,
+}
+
+#[cfg(feature = "tls")]
+const fn default_watch() -> bool {
+    true
+}
+
+#[cfg(feature = "tls")]
+const fn default_watch_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompressionConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub gzip: bool,
+    #[serde(default = "default_true")]
+    pub deflate: bool,
+    #[serde(default = "default_true")]
+    pub br: bool,
+    #[serde(default = "default_true")]
+    pub zstd: bool,
+    /// Responses whose body is known to be smaller than this are not compressed.
+    #[serde(default = "default_min_size")]
+    pub min_size: u64,
+    #[serde(default)]
+    pub level: CompressionLevel,
+}
+
+#[cfg(feature = "compression")]
+const fn default_min_size() -> u64 {
+    256
+}
+
+#[cfg(feature = "compression")]
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            gzip: default_true(),
+            deflate: default_true(),
+            br: default_true(),
+            zstd: default_true(),
+            min_size: default_min_size(),
+            level: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionLevel {
+    Fastest,
+    #[default]
+    Default,
+    Best,
+    /// An algorithm specific quality, e.g. `level = 6`.
+    #[serde(untagged)]
+    Precise(i32),
+}
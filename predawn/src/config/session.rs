@@ -0,0 +1,99 @@
+use std::{path::PathBuf, time::Duration};
+
+use rudi::Singleton;
+use serde::{Deserialize, Serialize};
+
+use super::{Config, ConfigPrefix, cookie::SameSite};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_cookie_name")]
+    pub cookie_name: String,
+    #[serde(default = "default_cookie_path")]
+    pub cookie_path: String,
+    #[serde(default)]
+    pub cookie_domain: Option<String>,
+    #[serde(default = "default_true")]
+    pub secure: bool,
+    #[serde(default = "default_true")]
+    pub http_only: bool,
+    #[serde(default)]
+    pub same_site: SameSite,
+    /// A session expires if it is not accessed within this duration.
+    #[serde(default = "default_idle_timeout")]
+    #[serde(deserialize_with = "duration_str::deserialize_duration")]
+    pub idle_timeout: Duration,
+    /// A session expires after this duration since it was created, no matter how often it is accessed.
+    #[serde(default)]
+    #[serde(deserialize_with = "duration_str::deserialize_option_duration")]
+    pub absolute_timeout: Option<Duration>,
+    /// How often expired sessions are removed from the store.
+    #[serde(default = "default_cleanup_interval")]
+    #[serde(deserialize_with = "duration_str::deserialize_duration")]
+    pub cleanup_interval: Duration,
+    #[serde(default)]
+    pub store: SessionStoreConfig,
+}
+
+#[Singleton(eager_create)]
+impl SessionConfig {
+    #[di]
+    pub fn new(#[di(ref)] config: &Config) -> Self {
+        config.get().expect("failed to load `SessionConfig`")
+    }
+}
+
+impl ConfigPrefix for SessionConfig {
+    const PREFIX: &'static str = "session";
+}
+
+fn default_cookie_name() -> String {
+    "session_id".into()
+}
+
+fn default_cookie_path() -> String {
+    "/".into()
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+const fn default_idle_timeout() -> Duration {
+    Duration::from_secs(30 * 60)
+}
+
+const fn default_cleanup_interval() -> Duration {
+    Duration::from_secs(5 * 60)
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cookie_name: default_cookie_name(),
+            cookie_path: default_cookie_path(),
+            cookie_domain: None,
+            secure: default_true(),
+            http_only: default_true(),
+            same_site: Default::default(),
+            idle_timeout: default_idle_timeout(),
+            absolute_timeout: None,
+            cleanup_interval: default_cleanup_interval(),
+            store: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SessionStoreConfig {
+    #[default]
+    Memory,
+    File {
+        dir: PathBuf,
+    },
+}
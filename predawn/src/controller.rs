@@ -2,7 +2,7 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use http::Method;
 use indexmap::IndexMap;
-use predawn_core::openapi::{Operation, Schema, SecurityScheme, Tag};
+use predawn_core::openapi::{Example, Operation, Schema, SecurityScheme, Tag};
 use rudi::Context;
 
 use crate::{handler::DynHandler, normalized_path::NormalizedPath};
@@ -19,5 +19,6 @@ pub trait Controller {
         schemas_in_progress: &mut Vec<String>,
         security_schemes: &mut BTreeMap<&'static str, (&'static str, SecurityScheme)>,
         tags: &mut BTreeMap<&'static str, (&'static str, Tag)>,
+        examples: &mut BTreeMap<&'static str, (&'static str, Example)>,
     );
 }
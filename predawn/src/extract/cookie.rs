@@ -0,0 +1,347 @@
+use std::{collections::BTreeMap, convert::Infallible, fmt, sync::Arc};
+
+pub use cookie::{Cookie, Expiration, Key, SameSite};
+use http::{HeaderMap, header::COOKIE};
+use predawn_core::{
+    api_request::ApiRequestHead,
+    from_request::FromRequestHead,
+    impl_deref,
+    openapi::{CookieStyle, Parameter, Schema},
+    request::Head,
+};
+use serde::de::DeserializeOwned;
+use snafu::{OptionExt, ResultExt};
+
+use crate::{
+    ToParameters,
+    config::cookie::CookieConfig,
+    response_error::{
+        CookieParamsError, CookieParamsSnafu, MissingCookieKeysError, MissingCookieKeysSnafu,
+    },
+};
+
+/// The keys used by [`SignedCookieJar`] and [`PrivateCookieJar`].
+///
+/// When the app is created by [`create_app`](crate::app::create_app), the keys are built from
+/// [`CookieConfig`] and inserted into the request extensions.
+#[derive(Clone)]
+pub struct CookieKeys {
+    current: Key,
+    previous: Arc<[Key]>,
+}
+
+impl fmt::Debug for CookieKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieKeys").finish_non_exhaustive()
+    }
+}
+
+impl CookieKeys {
+    pub fn new(current: Key) -> Self {
+        Self {
+            current,
+            previous: Arc::new([]),
+        }
+    }
+
+    /// Keys that are only used to verify or decrypt cookies, so that the current key can be rotated.
+    pub fn previous<I: IntoIterator<Item = Key>>(mut self, keys: I) -> Self {
+        self.previous = keys.into_iter().collect();
+        self
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Key> {
+        std::iter::once(&self.current).chain(self.previous.iter())
+    }
+
+    fn from_request_head(head: &Head) -> Result<Self, MissingCookieKeysError> {
+        head.extensions
+            .get::<Self>()
+            .cloned()
+            .context(MissingCookieKeysSnafu)
+    }
+}
+
+impl From<&CookieConfig> for CookieKeys {
+    fn from(config: &CookieConfig) -> Self {
+        let current = match &config.key {
+            Some(key) => parse_key(key),
+            None => {
+                tracing::warn!(
+                    "`cookie.key` is not set, signed and private cookies will not survive a restart"
+                );
+                Key::generate()
+            }
+        };
+
+        Self::new(current).previous(config.previous_keys.iter().map(|key| parse_key(key)))
+    }
+}
+
+fn parse_key(key: &str) -> Key {
+    let key = key.as_bytes();
+
+    match key.len() {
+        64.. => Key::from(key),
+        32.. => Key::derive_from(key),
+        len => panic!("cookie key must be at least 32 bytes long, but got {len} bytes"),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    jar: cookie::CookieJar,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut jar = cookie::CookieJar::new();
+
+        headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| Cookie::parse_encoded(cookie.trim().to_owned()).ok())
+            .for_each(|cookie| jar.add_original(cookie));
+
+        Self { jar }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cookie<'static>> {
+        self.jar.get(name)
+    }
+
+    /// Adds a cookie, it will be sent back to the client when the jar is put into a [`SetCookie`](crate::response::SetCookie).
+    pub fn add<C: Into<Cookie<'static>>>(&mut self, cookie: C) {
+        self.jar.add(cookie)
+    }
+
+    /// Removes a cookie, a removal cookie will be sent back to the client if it was sent by the client.
+    pub fn remove<C: Into<Cookie<'static>>>(&mut self, cookie: C) {
+        self.jar.remove(cookie)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie<'static>> {
+        self.jar.iter()
+    }
+
+    /// Returns the cookies that were added or removed.
+    pub fn delta(&self) -> impl Iterator<Item = &Cookie<'static>> {
+        self.jar.delta()
+    }
+}
+
+impl FromRequestHead for CookieJar {
+    type Error = Infallible;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        Ok(Self::from_headers(&head.headers))
+    }
+}
+
+impl ApiRequestHead for CookieJar {
+    fn parameters(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Option<Vec<Parameter>> {
+        None
+    }
+}
+
+macro_rules! secure_jar {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $jar:ident, $jar_mut:ident, $check:ident
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            jar: cookie::CookieJar,
+            keys: CookieKeys,
+        }
+
+        impl $name {
+            pub fn new(keys: CookieKeys) -> Self {
+                Self {
+                    jar: Default::default(),
+                    keys,
+                }
+            }
+
+            pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+                let cookie = self.jar.get(name)?;
+
+                self.keys
+                    .iter()
+                    .find_map(|key| self.jar.$jar(key).$check(cookie.clone()))
+            }
+
+            pub fn add<C: Into<Cookie<'static>>>(&mut self, cookie: C) {
+                self.jar.$jar_mut(&self.keys.current).add(cookie)
+            }
+
+            pub fn remove<C: Into<Cookie<'static>>>(&mut self, cookie: C) {
+                self.jar.$jar_mut(&self.keys.current).remove(cookie)
+            }
+
+            /// Returns the cookies that passed the check, other cookies are ignored.
+            pub fn iter(&self) -> impl Iterator<Item = Cookie<'static>> + '_ {
+                self.jar.iter().filter_map(|cookie| self.get(cookie.name()))
+            }
+        }
+
+        impl FromRequestHead for $name {
+            type Error = MissingCookieKeysError;
+
+            async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+                let keys = CookieKeys::from_request_head(head)?;
+                let CookieJar { jar } = CookieJar::from_headers(&head.headers);
+
+                Ok(Self { jar, keys })
+            }
+        }
+
+        impl ApiRequestHead for $name {
+            fn parameters(
+                _: &mut BTreeMap<String, Schema>,
+                _: &mut Vec<String>,
+            ) -> Option<Vec<Parameter>> {
+                None
+            }
+        }
+
+        impl From<$name> for CookieJar {
+            fn from(jar: $name) -> Self {
+                CookieJar { jar: jar.jar }
+            }
+        }
+    };
+}
+
+secure_jar! {
+    /// A cookie jar whose cookies are signed, the client can read them but can not tamper with them.
+    SignedCookieJar, signed, signed_mut, verify
+}
+
+secure_jar! {
+    /// A cookie jar whose cookies are encrypted, the client can neither read nor tamper with them.
+    PrivateCookieJar, private, private_mut, decrypt
+}
+
+/// Deserializes cookies into `T`, which is documented as cookie parameters in OpenAPI.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CookieParams<T>(pub T);
+
+impl_deref!(CookieParams);
+
+impl<T> FromRequestHead for CookieParams<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = CookieParamsError;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        let jar = CookieJar::from_headers(&head.headers);
+
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+        jar.iter().for_each(|cookie| {
+            serializer.append_pair(cookie.name(), cookie.value());
+        });
+
+        let form = serializer.finish();
+        let params = crate::util::deserialize_form(form.as_bytes()).context(CookieParamsSnafu)?;
+
+        Ok(CookieParams(params))
+    }
+}
+
+impl<T: ToParameters> ApiRequestHead for CookieParams<T> {
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<Parameter>> {
+        Some(
+            <T as ToParameters>::parameters(schemas, schemas_in_progress)
+                .into_iter()
+                .map(|parameter_data| Parameter::Cookie {
+                    parameter_data,
+                    style: CookieStyle::Form,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, HeaderValue, header::COOKIE};
+
+    use super::{Cookie, CookieJar, CookieKeys, Key, PrivateCookieJar, SignedCookieJar};
+
+    fn headers(cookies: &[String]) -> HeaderMap {
+        cookies
+            .iter()
+            .map(|cookie| (COOKIE, HeaderValue::from_str(cookie).unwrap()))
+            .collect()
+    }
+
+    fn delta(jar: impl Into<CookieJar>) -> Vec<String> {
+        jar.into()
+            .delta()
+            .map(|cookie| cookie.stripped().encoded().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cookie_header() {
+        let headers = headers(&["a=1; b=hello%20world".into(), "c=3".into()]);
+        let jar = CookieJar::from_headers(&headers);
+
+        assert_eq!(jar.get("a").unwrap().value(), "1");
+        assert_eq!(jar.get("b").unwrap().value(), "hello world");
+        assert_eq!(jar.get("c").unwrap().value(), "3");
+        assert_eq!(jar.delta().count(), 0);
+    }
+
+    #[test]
+    fn test_secure_jars_with_rotated_key() {
+        let old = Key::generate();
+        let new = Key::generate();
+
+        let mut signed = SignedCookieJar::new(CookieKeys::new(old.clone()));
+        signed.add(Cookie::new("s", "signed"));
+
+        let mut private = PrivateCookieJar::new(CookieKeys::new(old.clone()));
+        private.add(Cookie::new("p", "private"));
+
+        let mut cookies = delta(signed);
+        cookies.extend(delta(private));
+
+        let jar = CookieJar::from_headers(&headers(&cookies)).jar;
+
+        let rotated = CookieKeys::new(new.clone()).previous([old]);
+
+        let signed = SignedCookieJar {
+            jar: jar.clone(),
+            keys: rotated.clone(),
+        };
+        assert_eq!(signed.get("s").unwrap().value(), "signed");
+        assert!(signed.get("p").is_none());
+
+        let private = PrivateCookieJar {
+            jar: jar.clone(),
+            keys: rotated,
+        };
+        assert_eq!(private.get("p").unwrap().value(), "private");
+        assert!(private.get("s").is_none());
+
+        let private = PrivateCookieJar {
+            jar,
+            keys: CookieKeys::new(new),
+        };
+        assert!(private.get("p").is_none());
+    }
+}
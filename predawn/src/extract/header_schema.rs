@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use http::{HeaderName, header};
+use predawn_core::openapi::{
+    Parameter, ParameterData, ParameterSchemaOrContent, ReferenceOr, Schema,
+};
+use predawn_schema::ToSchema;
+use serde_json::json;
+
+/// How a header parameter is documented.
+pub(crate) struct HeaderSchema {
+    pub(crate) schema: ReferenceOr<Schema>,
+    pub(crate) description: Option<String>,
+    pub(crate) example: Option<serde_json::Value>,
+}
+
+impl HeaderSchema {
+    /// The common request headers have a schema and an example, the others are documented as strings.
+    pub(crate) fn of(
+        name: &HeaderName,
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Self {
+        well_known(name, schemas, schemas_in_progress).unwrap_or_else(|| Self {
+            schema: <String as ToSchema>::schema_ref(schemas, schemas_in_progress),
+            description: None,
+            example: None,
+        })
+    }
+
+    pub(crate) fn into_parameter(self, name: &HeaderName) -> Parameter {
+        let Self {
+            schema,
+            description,
+            example,
+        } = self;
+
+        Parameter::Header {
+            parameter_data: ParameterData {
+                name: name.to_string(),
+                description,
+                required: true,
+                deprecated: Default::default(),
+                format: ParameterSchemaOrContent::Schema(schema),
+                example,
+                examples: Default::default(),
+                explode: Default::default(),
+                extensions: Default::default(),
+            },
+            style: Default::default(),
+        }
+    }
+}
+
+macro_rules! schema_type {
+    () => {
+        String
+    };
+    ($ty:ty) => {
+        $ty
+    };
+}
+
+macro_rules! well_known {
+    ($($name:ident $(: $schema:ty)? => $example:expr $(, $description:expr)?);+ $(;)?) => {
+        fn well_known(
+            name: &HeaderName,
+            schemas: &mut BTreeMap<String, Schema>,
+            schemas_in_progress: &mut Vec<String>,
+        ) -> Option<HeaderSchema> {
+            $(
+                if name == header::$name {
+                    return Some(HeaderSchema {
+                        schema: <schema_type!($($schema)?) as ToSchema>::schema_ref(
+                            schemas,
+                            schemas_in_progress,
+                        ),
+                        description: None$(.or(Some($description.to_string())))?,
+                        example: Some(json!($example)),
+                    });
+                }
+            )+
+
+            None
+        }
+    };
+}
+
+const HTTP_DATE: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+const CREDENTIALS: &str = "Credentials such as `Bearer <token>` or `Basic <credentials>`";
+
+well_known![
+    ACCESS_CONTROL_REQUEST_HEADERS => "content-type";
+    ACCESS_CONTROL_REQUEST_METHOD => "POST";
+    AUTHORIZATION => "<scheme> <credentials>", CREDENTIALS;
+    CACHE_CONTROL => "no-cache";
+    CONNECTION => "keep-alive";
+    CONTENT_DISPOSITION => "attachment; filename=\"file.txt\"";
+    CONTENT_ENCODING => "gzip";
+    CONTENT_LENGTH: u64 => 1024;
+    CONTENT_TYPE => "application/json";
+    COOKIE => "name=value; name2=value2";
+    DATE => HTTP_DATE;
+    EXPECT => "100-continue";
+    HOST => "example.com";
+    IF_MATCH => "\"bfc13a64729c4290ef5b2c2730249c88ca92d82d\"";
+    IF_MODIFIED_SINCE => HTTP_DATE;
+    IF_NONE_MATCH => "\"33a64df551425fcc55e4d42a148795d9f25f89d4\"";
+    IF_RANGE => HTTP_DATE;
+    IF_UNMODIFIED_SINCE => HTTP_DATE;
+    ORIGIN => "https://example.com";
+    PRAGMA => "no-cache";
+    PROXY_AUTHORIZATION => "<scheme> <credentials>", CREDENTIALS;
+    RANGE => "bytes=200-1000";
+    REFERER => "https://example.com/page";
+    SEC_WEBSOCKET_KEY => "dGhlIHNhbXBsZSBub25jZQ==";
+    SEC_WEBSOCKET_VERSION: u8 => 13;
+    TE => "trailers";
+    TRANSFER_ENCODING => "chunked";
+    UPGRADE => "websocket";
+    USER_AGENT => "Mozilla/5.0";
+];
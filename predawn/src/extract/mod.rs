@@ -1,7 +1,12 @@
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[cfg(feature = "cookie")]
+pub mod cookie;
+mod header_schema;
 pub mod multipart;
 mod path;
 mod query;
 mod typed_header;
 pub mod websocket;
 
+pub(crate) use self::header_schema::HeaderSchema;
 pub use self::{path::Path, query::Query, typed_header::TypedHeader};
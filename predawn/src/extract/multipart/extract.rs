@@ -1,16 +1,16 @@
-use http_body_util::BodyExt;
 use mime::{FORM_DATA, MULTIPART};
-use multer::Field;
+use multer::{Constraints, Field, SizeLimit};
 use predawn_core::{
-    body::RequestBody,
+    body::{RequestBody, decode_body},
     from_request::{FromRequest, OptionalFromRequest},
     media_type::{MediaType, RequestMediaType, has_media_type},
     request::Head,
 };
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 
 use crate::response_error::{
-    ByParseMultipartSnafu, InvalidMultipartContentTypeSnafu, MultipartError,
+    ByParseMultipartSnafu, DisallowedContentTypeSnafu, InvalidMultipartContentTypeSnafu,
+    MissingContentTypeSnafu, MultipartError,
 };
 
 #[doc(hidden)]
@@ -21,15 +21,7 @@ impl FromRequest for Multipart {
     type Error = MultipartError;
 
     async fn from_request(head: &mut Head, body: RequestBody) -> Result<Self, Self::Error> {
-        let content_type = head.content_type().unwrap_or_default();
-
-        if !<Multipart as RequestMediaType>::check_content_type(content_type) {
-            return InvalidMultipartContentTypeSnafu.fail();
-        }
-
-        let boundary = multer::parse_boundary(content_type).context(ByParseMultipartSnafu)?;
-        let multipart = multer::Multipart::new(body.into_data_stream(), boundary);
-        Ok(Multipart(multipart))
+        Self::with_size_limits(head, body, &[])
     }
 }
 
@@ -46,12 +38,44 @@ impl OptionalFromRequest for Multipart {
         }
 
         let boundary = multer::parse_boundary(content_type).context(ByParseMultipartSnafu)?;
-        let multipart = multer::Multipart::new(body.into_data_stream(), boundary);
+        let multipart = multer::Multipart::new(decode_body(head, body), boundary);
         Ok(Some(Multipart(multipart)))
     }
 }
 
 impl Multipart {
+    /// Creates a multipart stream that fails with `413 Payload Too Large` once one of the named
+    /// fields exceeds its limit.
+    pub fn with_size_limits(
+        head: &Head,
+        body: RequestBody,
+        size_limits: &[(&'static str, u64)],
+    ) -> Result<Self, MultipartError> {
+        let content_type = head.content_type().unwrap_or_default();
+
+        if !<Multipart as RequestMediaType>::check_content_type(content_type) {
+            return InvalidMultipartContentTypeSnafu.fail();
+        }
+
+        let boundary = multer::parse_boundary(content_type).context(ByParseMultipartSnafu)?;
+        let stream = decode_body(head, body);
+
+        let multipart = if size_limits.is_empty() {
+            multer::Multipart::new(stream, boundary)
+        } else {
+            let size_limit = size_limits
+                .iter()
+                .fold(SizeLimit::new(), |size_limit, (name, limit)| {
+                    size_limit.for_field(*name, *limit)
+                });
+
+            let constraints = Constraints::new().size_limit(size_limit);
+            multer::Multipart::with_constraints(stream, boundary, constraints)
+        };
+
+        Ok(Multipart(multipart))
+    }
+
     pub async fn next_field(&mut self) -> Result<Option<Field<'static>>, MultipartError> {
         self.0.next_field().await.context(ByParseMultipartSnafu)
     }
@@ -72,3 +96,64 @@ impl RequestMediaType for Multipart {
         )
     }
 }
+
+/// Checks the content type of `field` against `allowed`, which may contain wildcards like `image/*`.
+pub fn check_field_content_type(
+    field: &Field<'static>,
+    name: &'static str,
+    allowed: &[&str],
+) -> Result<(), MultipartError> {
+    let content_type = field
+        .content_type()
+        .context(MissingContentTypeSnafu { name })?;
+
+    if allowed
+        .iter()
+        .any(|pattern| content_type_matches(content_type, pattern))
+    {
+        return Ok(());
+    }
+
+    DisallowedContentTypeSnafu {
+        name,
+        content_type: content_type.essence_str(),
+    }
+    .fail()
+}
+
+fn content_type_matches(content_type: &mime::Mime, pattern: &str) -> bool {
+    let pattern = pattern.split(';').next().unwrap_or_default().trim();
+
+    let (ty, subtype) = pattern.split_once('/').unwrap_or((pattern, "*"));
+
+    let (actual_ty, actual_subtype) = content_type
+        .essence_str()
+        .split_once('/')
+        .unwrap_or_default();
+
+    let matches =
+        |pattern: &str, actual: &str| pattern == "*" || pattern.eq_ignore_ascii_case(actual);
+
+    matches(ty, actual_ty) && matches(subtype, actual_subtype)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_type_matches;
+
+    #[test]
+    fn test_content_type_matches() {
+        let png: mime::Mime = "image/png".parse().unwrap();
+        let svg: mime::Mime = "image/svg+xml; charset=utf-8".parse().unwrap();
+
+        assert!(content_type_matches(&png, "image/png"));
+        assert!(content_type_matches(&png, "IMAGE/PNG"));
+        assert!(content_type_matches(&png, "image/*"));
+        assert!(content_type_matches(&png, "*/*"));
+        assert!(content_type_matches(&svg, "image/svg+xml"));
+
+        assert!(!content_type_matches(&png, "image/jpeg"));
+        assert!(!content_type_matches(&png, "text/*"));
+        assert!(!content_type_matches(&svg, "image/svg"));
+    }
+}
@@ -1,6 +1,9 @@
 mod extract;
 mod json_field;
 mod parse_field;
+mod streaming_upload;
+#[cfg(feature = "fs")]
+mod temp_file_upload;
 mod upload;
 
 #[cfg_attr(docsrs, doc(cfg(feature = "macro")))]
@@ -8,5 +11,13 @@ mod upload;
 pub use predawn_macro::Multipart;
 
 #[doc(hidden)]
-pub use self::extract::Multipart;
-pub use self::{json_field::JsonField, parse_field::ParseField, upload::Upload};
+pub use self::extract::{Multipart, check_field_content_type};
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[cfg(feature = "fs")]
+pub use self::temp_file_upload::{TempFileUpload, UploadTempDir};
+pub use self::{
+    json_field::JsonField,
+    parse_field::{FieldContext, ParseField},
+    streaming_upload::StreamingUpload,
+    upload::Upload,
+};
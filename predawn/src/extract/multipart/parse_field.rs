@@ -8,6 +8,7 @@ use std::{
 use bytes::Bytes;
 use http::Uri;
 use multer::Field;
+use predawn_core::request::Head;
 use snafu::ResultExt;
 
 use crate::response_error::{
@@ -15,9 +16,31 @@ use crate::response_error::{
     MultipartError, ParseErrorAtNameSnafu,
 };
 
+/// What a field is parsed in, passed to [`ParseField::parse_field_in`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldContext<'a> {
+    head: Option<&'a Head>,
+}
+
+impl<'a> FieldContext<'a> {
+    pub fn new(head: &'a Head) -> Self {
+        Self { head: Some(head) }
+    }
+
+    /// Return the head of the request the form is read from, if any.
+    pub fn head(&self) -> Option<&'a Head> {
+        self.head
+    }
+}
+
 pub trait ParseField: Sized + Send {
     type Holder: Send;
 
+    /// Whether the parsed value keeps reading its field after `parse_field` returns.
+    ///
+    /// If so, the fields after it are not parsed, so it must be the last part of the form.
+    const STREAMING: bool = false;
+
     fn default_holder(name: &'static str) -> Self::Holder;
 
     fn parse_field(
@@ -26,12 +49,39 @@ pub trait ParseField: Sized + Send {
         name: &'static str,
     ) -> impl Future<Output = Result<Self::Holder, MultipartError>> + Send;
 
+    /// Like [`parse_field`](Self::parse_field), for types that depend on the request the form is
+    /// read from, such as `TempFileUpload` on its [`UploadTempDir`](super::UploadTempDir).
+    ///
+    /// The `Multipart` derive calls this one.
+    fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> impl Future<Output = Result<Self::Holder, MultipartError>> + Send {
+        let _ = cx;
+        Self::parse_field(holder, field, name)
+    }
+
     fn extract(holder: Self::Holder, name: &'static str) -> Result<Self, MultipartError>;
 }
 
+/// The `STREAMING` of a collection of `T`, which can not hold streaming items, as each of them
+/// would have to be read to its end before the next field.
+const fn not_streaming<T: ParseField>() -> bool {
+    assert!(
+        !T::STREAMING,
+        "a collection of streaming fields can not be parsed, only a single one"
+    );
+
+    false
+}
+
 impl<T: ParseField> ParseField for Option<T> {
     type Holder = T::Holder;
 
+    const STREAMING: bool = T::STREAMING;
+
     fn default_holder(name: &'static str) -> Self::Holder {
         T::default_holder(name)
     }
@@ -44,6 +94,15 @@ impl<T: ParseField> ParseField for Option<T> {
         T::parse_field(holder, field, name).await
     }
 
+    async fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        T::parse_field_in(holder, field, name, cx).await
+    }
+
     fn extract(holder: Self::Holder, name: &'static str) -> Result<Self, MultipartError> {
         match T::extract(holder, name) {
             Ok(o) => Ok(Some(o)),
@@ -56,6 +115,8 @@ impl<T: ParseField> ParseField for Option<T> {
 impl<T: ParseField> ParseField for Vec<T> {
     type Holder = Result<Self, MultipartError>;
 
+    const STREAMING: bool = not_streaming::<T>();
+
     fn default_holder(name: &'static str) -> Self::Holder {
         MissingFieldSnafu { name }.fail()
     }
@@ -65,7 +126,16 @@ impl<T: ParseField> ParseField for Vec<T> {
         field: Field<'static>,
         name: &'static str,
     ) -> Result<Self::Holder, MultipartError> {
-        let item_holder = T::parse_field(T::default_holder(name), field, name).await?;
+        Self::parse_field_in(holder, field, name, FieldContext::default()).await
+    }
+
+    async fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        let item_holder = T::parse_field_in(T::default_holder(name), field, name, cx).await?;
         let item = T::extract(item_holder, name)?;
 
         let mut holder = holder.unwrap_or_default();
@@ -81,6 +151,8 @@ impl<T: ParseField> ParseField for Vec<T> {
 impl<T: ParseField, const N: usize> ParseField for [T; N] {
     type Holder = Result<Vec<T>, MultipartError>;
 
+    const STREAMING: bool = not_streaming::<T>();
+
     fn default_holder(name: &'static str) -> Self::Holder {
         MissingFieldSnafu { name }.fail()
     }
@@ -90,7 +162,16 @@ impl<T: ParseField, const N: usize> ParseField for [T; N] {
         field: Field<'static>,
         name: &'static str,
     ) -> Result<Self::Holder, MultipartError> {
-        let item_holder = T::parse_field(T::default_holder(name), field, name).await?;
+        Self::parse_field_in(holder, field, name, FieldContext::default()).await
+    }
+
+    async fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        let item_holder = T::parse_field_in(T::default_holder(name), field, name, cx).await?;
         let item = T::extract(item_holder, name)?;
 
         let mut holder = holder.unwrap_or_else(|_| Vec::with_capacity(N));
@@ -117,6 +198,8 @@ where
 {
     type Holder = Result<Self, MultipartError>;
 
+    const STREAMING: bool = not_streaming::<T>();
+
     fn default_holder(name: &'static str) -> Self::Holder {
         MissingFieldSnafu { name }.fail()
     }
@@ -126,7 +209,16 @@ where
         field: Field<'static>,
         name: &'static str,
     ) -> Result<Self::Holder, MultipartError> {
-        let item_holder = T::parse_field(T::default_holder(name), field, name).await?;
+        Self::parse_field_in(holder, field, name, FieldContext::default()).await
+    }
+
+    async fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        let item_holder = T::parse_field_in(T::default_holder(name), field, name, cx).await?;
         let item = T::extract(item_holder, name)?;
 
         let mut holder = holder.unwrap_or_default();
@@ -142,6 +234,8 @@ where
 impl<T: ParseField + Ord> ParseField for BTreeSet<T> {
     type Holder = Result<Self, MultipartError>;
 
+    const STREAMING: bool = not_streaming::<T>();
+
     fn default_holder(name: &'static str) -> Self::Holder {
         MissingFieldSnafu { name }.fail()
     }
@@ -151,7 +245,16 @@ impl<T: ParseField + Ord> ParseField for BTreeSet<T> {
         field: Field<'static>,
         name: &'static str,
     ) -> Result<Self::Holder, MultipartError> {
-        let item_holder = T::parse_field(T::default_holder(name), field, name).await?;
+        Self::parse_field_in(holder, field, name, FieldContext::default()).await
+    }
+
+    async fn parse_field_in(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        let item_holder = T::parse_field_in(T::default_holder(name), field, name, cx).await?;
         let item = T::extract(item_holder, name)?;
 
         let mut holder = holder.unwrap_or_default();
@@ -0,0 +1,198 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use multer::Field;
+use predawn_core::openapi::Schema;
+use predawn_schema::ToSchema;
+use snafu::ResultExt;
+
+use super::{ParseField, upload::file_info};
+use crate::response_error::{
+    ByParseFieldSnafu, DuplicateFieldSnafu, MissingFieldSnafu, MultipartError,
+};
+
+/// A file whose content is read chunk by chunk, without buffering it in memory or on disk.
+///
+/// The content is read from the request body while it is consumed, so it must be the last part
+/// of the form, the fields after it are not parsed.
+#[derive(Debug)]
+pub struct StreamingUpload {
+    field_name: &'static str,
+    file_name: Box<str>,
+    content_type: Box<str>,
+    field: Field<'static>,
+}
+
+impl StreamingUpload {
+    /// Return the name of the parameter in the multipart form.
+    #[inline]
+    pub fn field_name(&self) -> &'static str {
+        self.field_name
+    }
+
+    /// Return the file name in the client's filesystem.
+    #[inline]
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Return the content type of the file.
+    #[inline]
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Return the next chunk of the file, or `None` at the end of the file.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        self.field.chunk().await.context(ByParseFieldSnafu {
+            name: self.field_name,
+        })
+    }
+}
+
+impl Stream for StreamingUpload {
+    type Item = Result<Bytes, MultipartError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let name = self.field_name;
+
+        Pin::new(&mut self.field)
+            .poll_next(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.context(ByParseFieldSnafu { name })))
+    }
+}
+
+impl ToSchema for StreamingUpload {
+    fn title() -> Cow<'static, str> {
+        "StreamingUpload".into()
+    }
+
+    fn schema(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Schema {
+        crate::util::binary_schema(Self::title())
+    }
+}
+
+impl ParseField for StreamingUpload {
+    type Holder = Result<Self, MultipartError>;
+
+    const STREAMING: bool = true;
+
+    fn default_holder(name: &'static str) -> Self::Holder {
+        MissingFieldSnafu { name }.fail()
+    }
+
+    async fn parse_field(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+    ) -> Result<Self::Holder, MultipartError> {
+        if holder.is_ok() {
+            return DuplicateFieldSnafu { name }.fail();
+        }
+
+        let (file_name, content_type) = file_info(&field, name)?;
+
+        Ok(Ok(StreamingUpload {
+            field_name: name,
+            file_name,
+            content_type,
+            field,
+        }))
+    }
+
+    fn extract(holder: Self::Holder, _: &'static str) -> Result<Self, MultipartError> {
+        holder
+    }
+}
+
+#[cfg(all(test, feature = "macro"))]
+mod tests {
+    use predawn_core::{error::Error, from_request::FromRequest, request::Request};
+
+    use super::StreamingUpload;
+    use crate::{
+        ToSchema,
+        extract::multipart::Multipart,
+        handler::handler_fn,
+        server::{Server, bind},
+    };
+
+    #[derive(ToSchema, Multipart)]
+    #[multipart(body_limit = 64 * 1024 * 1024)]
+    struct Form {
+        title: String,
+        file: StreamingUpload,
+        note: Option<String>,
+    }
+
+    fn part(name: &str, file_name: Option<&str>, content: &str) -> String {
+        let file_name = file_name
+            .map(|file_name| {
+                format!("; filename=\"{file_name}\"\r\ncontent-type: application/octet-stream")
+            })
+            .unwrap_or_default();
+
+        format!(
+            "--boundary\r\ncontent-disposition: form-data; name=\"{name}\"{file_name}\r\n\r\n{content}\r\n"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_streaming_upload() {
+        assert_eq!(<Form as FromRequest>::BODY_LIMIT, Some(64 * 1024 * 1024));
+
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handler = handler_fn(|req: Request| async move {
+            let (mut head, body) = req.split();
+
+            let Form {
+                title,
+                mut file,
+                note,
+            } = Form::from_request(&mut head, body).await?;
+
+            let mut size = 0;
+
+            while let Some(chunk) = file.chunk().await? {
+                assert!(chunk.iter().all(|byte| *byte == b'x'));
+                size += chunk.len();
+            }
+
+            Ok::<_, Error>(format!("{title} {} {size} {note:?}", file.file_name()))
+        });
+
+        tokio::spawn(Server::new(listener).run(handler));
+
+        let content = "x".repeat(256 * 1024);
+
+        // the fields after the streaming one are not parsed
+        let body = [
+            part("title", None, "video"),
+            part("file", Some("a.bin"), &content),
+            part("note", None, "ignored"),
+            "--boundary--\r\n".to_string(),
+        ]
+        .concat();
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/"))
+            .header("content-type", "multipart/form-data; boundary=boundary")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.text().await.unwrap(),
+            format!("video a.bin {} None", content.len())
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::Write as _,
+    hash::{BuildHasher, RandomState},
+    io,
+    path::{Path, PathBuf},
+};
+
+use multer::Field;
+use predawn_core::openapi::Schema;
+use predawn_schema::ToSchema;
+use sha2::{Digest, Sha256};
+use snafu::ResultExt;
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+use super::{FieldContext, ParseField, upload::file_info};
+use crate::response_error::{
+    ByParseFieldSnafu, DuplicateFieldSnafu, MissingFieldSnafu, MultipartError, SpoolFieldSnafu,
+};
+
+/// The directory [`TempFileUpload`]s are spooled to, read from the request extensions.
+///
+/// `create_app` inserts it according to `server.upload_temp_dir`, [`std::env::temp_dir`] is used without it.
+#[derive(Debug, Clone)]
+pub struct UploadTempDir(pub PathBuf);
+
+/// A file spooled to a temporary file while it is read, so that its size is not bounded by memory.
+///
+/// The temporary file is removed when this is dropped, unless it is [persisted](Self::persist).
+#[derive(Debug)]
+pub struct TempFileUpload {
+    field_name: &'static str,
+    file_name: Box<str>,
+    content_type: Box<str>,
+    path: TempPath,
+    size: u64,
+    sha256: [u8; 32],
+}
+
+impl TempFileUpload {
+    /// Return the name of the parameter in the multipart form.
+    #[inline]
+    pub fn field_name(&self) -> &'static str {
+        self.field_name
+    }
+
+    /// Return the file name in the client's filesystem.
+    #[inline]
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Return the content type of the file.
+    #[inline]
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Return the path of the temporary file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path.0
+    }
+
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Return the SHA-256 digest of the file.
+    #[inline]
+    pub fn sha256(&self) -> &[u8; 32] {
+        &self.sha256
+    }
+
+    /// Return the SHA-256 digest of the file as lowercase hex.
+    pub fn sha256_hex(&self) -> String {
+        self.sha256
+            .iter()
+            .fold(String::with_capacity(64), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            })
+    }
+
+    pub async fn open(&self) -> io::Result<File> {
+        File::open(self.path()).await
+    }
+
+    /// Moves the temporary file to `to`, so that it is kept after this is dropped.
+    pub async fn persist<P: AsRef<Path>>(mut self, to: P) -> io::Result<()> {
+        let to = to.as_ref();
+
+        // renaming fails across file systems, in which case the temporary file is copied
+        if fs::rename(self.path(), to).await.is_ok() {
+            self.path.0 = PathBuf::new();
+            return Ok(());
+        }
+
+        fs::copy(self.path(), to).await?;
+        Ok(())
+    }
+}
+
+/// Removes the file when dropped, also if spooling fails halfway.
+#[derive(Debug)]
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if self.0.as_os_str().is_empty() {
+            return;
+        }
+
+        let path = std::mem::take(&mut self.0);
+
+        // not to block the runtime when dropped in a handler
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || std::fs::remove_file(path));
+            }
+            Err(_) => {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+async fn create_temp_file(dir: &Path) -> io::Result<(File, TempPath)> {
+    let mut created_dir = false;
+
+    loop {
+        let state = RandomState::new();
+        let file_name = format!(
+            "predawn-upload-{:016x}{:016x}",
+            state.hash_one(0),
+            state.hash_one(1)
+        );
+        let path = dir.join(file_name);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => return Ok((file, TempPath(path))),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !created_dir => {
+                fs::create_dir_all(dir).await?;
+                created_dir = true;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl ToSchema for TempFileUpload {
+    fn title() -> Cow<'static, str> {
+        "TempFileUpload".into()
+    }
+
+    fn schema(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Schema {
+        crate::util::binary_schema(Self::title())
+    }
+}
+
+impl ParseField for TempFileUpload {
+    type Holder = Result<Self, MultipartError>;
+
+    fn default_holder(name: &'static str) -> Self::Holder {
+        MissingFieldSnafu { name }.fail()
+    }
+
+    async fn parse_field(
+        holder: Self::Holder,
+        field: Field<'static>,
+        name: &'static str,
+    ) -> Result<Self::Holder, MultipartError> {
+        Self::parse_field_in(holder, field, name, FieldContext::default()).await
+    }
+
+    async fn parse_field_in(
+        holder: Self::Holder,
+        mut field: Field<'static>,
+        name: &'static str,
+        cx: FieldContext<'_>,
+    ) -> Result<Self::Holder, MultipartError> {
+        if holder.is_ok() {
+            return DuplicateFieldSnafu { name }.fail();
+        }
+
+        let (file_name, content_type) = file_info(&field, name)?;
+
+        let temp_dir = cx
+            .head()
+            .and_then(|head| head.extensions.get::<UploadTempDir>());
+
+        let dir = match temp_dir {
+            Some(UploadTempDir(dir)) => Cow::Borrowed(dir.as_path()),
+            None => Cow::Owned(std::env::temp_dir()),
+        };
+
+        let (mut file, path) = create_temp_file(&dir)
+            .await
+            .context(SpoolFieldSnafu { name })?;
+
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+
+        while let Some(chunk) = field.chunk().await.context(ByParseFieldSnafu { name })? {
+            hasher.update(&chunk);
+            size += chunk.len() as u64;
+
+            file.write_all(&chunk)
+                .await
+                .context(SpoolFieldSnafu { name })?;
+        }
+
+        file.flush().await.context(SpoolFieldSnafu { name })?;
+
+        Ok(Ok(TempFileUpload {
+            field_name: name,
+            file_name,
+            content_type,
+            path,
+            size,
+            sha256: hasher.finalize().into(),
+        }))
+    }
+
+    fn extract(holder: Self::Holder, _: &'static str) -> Result<Self, MultipartError> {
+        holder
+    }
+}
+
+#[cfg(all(test, feature = "macro"))]
+mod tests {
+    use std::time::Duration;
+
+    use predawn_core::{error::Error, from_request::FromRequest, request::Request};
+
+    use super::{TempFileUpload, UploadTempDir};
+    use crate::{
+        ToSchema,
+        extract::multipart::Multipart,
+        handler::handler_fn,
+        server::{Server, bind},
+    };
+
+    #[derive(ToSchema, Multipart)]
+    struct Form {
+        file: TempFileUpload,
+    }
+
+    #[tokio::test]
+    async fn test_spool_to_upload_temp_dir() {
+        let dir = std::env::temp_dir().join(format!("predawn-uploads-{}", std::process::id()));
+
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let upload_dir = dir.clone();
+        let handler = handler_fn(move |req: Request| {
+            let dir = upload_dir.clone();
+
+            async move {
+                let (mut head, body) = req.split();
+                head.extensions.insert(UploadTempDir(dir.clone()));
+
+                let Form { file } = Form::from_request(&mut head, body).await?;
+                assert!(file.path().starts_with(&dir));
+
+                Ok::<_, Error>(tokio::fs::read_to_string(file.path()).await.unwrap())
+            }
+        });
+
+        tokio::spawn(Server::new(listener).run(handler));
+
+        let body = "--boundary\r\ncontent-disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\ncontent-type: text/plain\r\n\r\nhello\r\n--boundary--\r\n";
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/"))
+            .header("content-type", "multipart/form-data; boundary=boundary")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "hello");
+
+        // the temporary file is removed in the background once the upload is dropped
+        let mut removed = false;
+
+        for _ in 0..50 {
+            if std::fs::read_dir(&dir).unwrap().next().is_none() {
+                removed = true;
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert!(removed);
+
+        std::fs::remove_dir(dir).unwrap();
+    }
+}
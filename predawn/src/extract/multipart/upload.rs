@@ -76,16 +76,7 @@ impl ParseField for Upload {
             return DuplicateFieldSnafu { name }.fail();
         }
 
-        let file_name = field
-            .file_name()
-            .context(MissingFileNameSnafu { name })?
-            .into();
-
-        let content_type = field
-            .content_type()
-            .context(MissingContentTypeSnafu { name })?
-            .as_ref()
-            .into();
+        let (file_name, content_type) = file_info(&field, name)?;
 
         let bytes = <Bytes as ParseField>::parse_field(
             <Bytes as ParseField>::default_holder(name),
@@ -106,3 +97,22 @@ impl ParseField for Upload {
         holder
     }
 }
+
+/// Returns the file name and the content type of a file field.
+pub(super) fn file_info(
+    field: &Field<'static>,
+    name: &'static str,
+) -> Result<(Box<str>, Box<str>), MultipartError> {
+    let file_name = field
+        .file_name()
+        .context(MissingFileNameSnafu { name })?
+        .into();
+
+    let content_type = field
+        .content_type()
+        .context(MissingContentTypeSnafu { name })?
+        .as_ref()
+        .into();
+
+    Ok((file_name, content_type))
+}
@@ -10,8 +10,12 @@ use predawn_core::{
 };
 use snafu::IntoError;
 
+use super::HeaderSchema;
 use crate::response_error::{DecodeSnafu, MissingSnafu, TypedHeaderError};
 
+/// Extracts a [`Header`], documented as a header parameter.
+///
+/// The common headers are documented with a schema and an example, the others as strings.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TypedHeader<T>(pub T);
 
@@ -60,8 +64,76 @@ where
     }
 }
 
-impl<T> ApiRequestHead for TypedHeader<T> {
-    fn parameters(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Option<Vec<Parameter>> {
-        None
+impl<T: Header> ApiRequestHead for TypedHeader<T> {
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<Parameter>> {
+        let name = T::name();
+        let parameter = HeaderSchema::of(name, schemas, schemas_in_progress).into_parameter(name);
+
+        Some(vec![parameter])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use headers::{Authorization, Header, UserAgent, authorization::Bearer};
+    use http::{HeaderName, HeaderValue};
+    use predawn_core::{
+        api_request::ApiRequestHead,
+        openapi::{Parameter, ParameterData},
+    };
+
+    use super::TypedHeader;
+
+    static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+    struct XRequestId;
+
+    impl Header for XRequestId {
+        fn name() -> &'static HeaderName {
+            &X_REQUEST_ID
+        }
+
+        fn decode<'i, I>(_: &mut I) -> Result<Self, headers::Error>
+        where
+            I: Iterator<Item = &'i HeaderValue>,
+        {
+            Ok(Self)
+        }
+
+        fn encode<E: Extend<HeaderValue>>(&self, _: &mut E) {}
+    }
+
+    fn parameter_data<T: ApiRequestHead>() -> ParameterData {
+        let mut parameters = T::parameters(&mut Default::default(), &mut Default::default())
+            .expect("must have parameters");
+
+        assert_eq!(parameters.len(), 1);
+
+        match parameters.pop().unwrap() {
+            Parameter::Header { parameter_data, .. } => parameter_data,
+            _ => panic!("must be a header parameter"),
+        }
+    }
+
+    #[test]
+    fn test_header_parameter() {
+        let data = parameter_data::<TypedHeader<Authorization<Bearer>>>();
+        assert_eq!(data.name, "authorization");
+        assert!(data.required);
+        assert!(data.description.unwrap().contains("Bearer"));
+        assert!(data.example.is_some());
+
+        let data = parameter_data::<Option<TypedHeader<UserAgent>>>();
+        assert_eq!(data.name, "user-agent");
+        assert!(!data.required);
+
+        // headers without a known name are documented as strings
+        let data = parameter_data::<TypedHeader<XRequestId>>();
+        assert_eq!(data.name, "x-request-id");
+        assert_eq!(data.description, None);
+        assert_eq!(data.example, None);
     }
 }
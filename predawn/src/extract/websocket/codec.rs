@@ -0,0 +1,119 @@
+use predawn_core::error::BoxError;
+use serde::{Serialize, de::DeserializeOwned};
+use tokio_tungstenite::tungstenite::Message;
+
+/// The format of the messages of a [`TypedWebSocket`](super::TypedWebSocket).
+pub trait WebSocketCodec: Send + 'static {
+    /// The media type of the messages, documented in `x-websocket-messages`.
+    const CONTENT_TYPE: &'static str;
+
+    fn encode<T: Serialize>(value: &T) -> Result<Message, BoxError>;
+
+    /// Returns `None` if messages of this frame type are not supported.
+    fn decode<T: DeserializeOwned>(message: &Message) -> Option<Result<T, BoxError>>;
+}
+
+/// Sends JSON as text frames, and receives it from both text and binary frames.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl WebSocketCodec for JsonCodec {
+    const CONTENT_TYPE: &'static str = "application/json";
+
+    fn encode<T: Serialize>(value: &T) -> Result<Message, BoxError> {
+        let json = serde_json::to_string(value)?;
+        Ok(Message::text(json))
+    }
+
+    fn decode<T: DeserializeOwned>(message: &Message) -> Option<Result<T, BoxError>> {
+        let result = match message {
+            Message::Text(text) => serde_json::from_str(text.as_str()),
+            Message::Binary(bytes) => serde_json::from_slice(bytes),
+            _ => return None,
+        };
+
+        Some(result.map_err(Into::into))
+    }
+}
+
+/// Sends and receives [MessagePack](https://msgpack.org) as binary frames.
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackCodec;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[cfg(feature = "msgpack")]
+impl WebSocketCodec for MsgPackCodec {
+    const CONTENT_TYPE: &'static str = "application/msgpack";
+
+    fn encode<T: Serialize>(value: &T) -> Result<Message, BoxError> {
+        let bytes = rmp_serde::to_vec_named(value)?;
+        Ok(Message::binary(bytes))
+    }
+
+    fn decode<T: DeserializeOwned>(message: &Message) -> Option<Result<T, BoxError>> {
+        match message {
+            Message::Binary(bytes) => Some(rmp_serde::from_slice(bytes).map_err(Into::into)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::{JsonCodec, WebSocketCodec};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Person {
+        name: String,
+        age: u8,
+    }
+
+    #[test]
+    fn test_json_codec() {
+        let alice = Person {
+            name: "Alice".to_string(),
+            age: 18,
+        };
+
+        let message = JsonCodec::encode(&alice).unwrap();
+        assert_eq!(message, Message::text(r#"{"name":"Alice","age":18}"#));
+
+        let decoded: Person = JsonCodec::decode(&message).unwrap().unwrap();
+        assert_eq!(decoded, alice);
+
+        let binary = Message::binary(r#"{"name":"Alice","age":18}"#);
+        let decoded: Person = JsonCodec::decode(&binary).unwrap().unwrap();
+        assert_eq!(decoded, alice);
+
+        assert!(
+            JsonCodec::decode::<Person>(&Message::text("{}"))
+                .unwrap()
+                .is_err()
+        );
+        assert!(JsonCodec::decode::<Person>(&Message::Ping(Default::default())).is_none());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_codec() {
+        use super::MsgPackCodec;
+
+        let alice = Person {
+            name: "Alice".to_string(),
+            age: 18,
+        };
+
+        let message = MsgPackCodec::encode(&alice).unwrap();
+        assert!(message.is_binary());
+
+        let decoded: Person = MsgPackCodec::decode(&message).unwrap().unwrap();
+        assert_eq!(decoded, alice);
+
+        assert!(MsgPackCodec::decode::<Person>(&Message::text("{}")).is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use error2::{ErrorExt, Location, NextError};
+use predawn_core::error::BoxError;
+use snafu::Snafu;
+use tokio_tungstenite::tungstenite;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum TypedWebSocketError {
+    #[snafu(display("websocket error"))]
+    WebSocketError {
+        #[snafu(implicit)]
+        location: Location,
+        source: tungstenite::Error,
+    },
+    #[snafu(display("failed to encode message"))]
+    EncodeError {
+        #[snafu(implicit)]
+        location: Location,
+        source: BoxError,
+    },
+    #[snafu(display("failed to decode message"))]
+    DecodeError {
+        #[snafu(implicit)]
+        location: Location,
+        source: BoxError,
+    },
+    #[snafu(display("unsupported message frame type"))]
+    UnsupportedMessage {
+        #[snafu(implicit)]
+        location: Location,
+    },
+}
+
+impl ErrorExt for TypedWebSocketError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            TypedWebSocketError::WebSocketError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            TypedWebSocketError::EncodeError { location, source }
+            | TypedWebSocketError::DecodeError { location, source } => {
+                (*location, NextError::Std(source.as_ref()))
+            }
+            TypedWebSocketError::UnsupportedMessage { location } => (*location, NextError::None),
+        }
+    }
+}
@@ -1,14 +1,23 @@
+mod codec;
+mod error;
 mod request;
 mod response;
 mod socket;
+mod typed;
 
 pub use tokio_tungstenite::tungstenite::protocol::{
     Message,
     frame::{CloseFrame, Frame, Utf8Bytes, coding::CloseCode},
 };
 
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+#[cfg(feature = "msgpack")]
+pub use self::codec::MsgPackCodec;
 pub use self::{
+    codec::{JsonCodec, WebSocketCodec},
+    error::TypedWebSocketError,
     request::{DefaultOnFailedUpgrade, OnFailedUpgrade, WebSocketRequest},
     response::WebSocketResponse,
     socket::WebSocket,
+    typed::{TypedWebSocket, TypedWebSocketRequest},
 };
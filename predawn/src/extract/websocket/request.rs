@@ -240,7 +240,7 @@ impl FromRequestHead for WebSocketRequest {
 
             if protocol != WEBSOCKET {
                 return ProtocolPseudoHeaderNotEqualWebSocketSnafu {
-                    value: Box::from(protocol),
+                    value: Box::<str>::from(protocol),
                 }
                 .fail();
             }
@@ -0,0 +1,318 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+use http::HeaderValue;
+use indexmap::IndexMap;
+use predawn_core::{
+    api_request::ApiRequestHead,
+    from_request::FromRequestHead,
+    openapi::{self, Schema},
+    request::Head,
+};
+use predawn_schema::ToSchema;
+use serde::{Serialize, de::DeserializeOwned};
+use snafu::ResultExt;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+
+use super::{
+    CloseCode, CloseFrame, DefaultOnFailedUpgrade, JsonCodec, Message, OnFailedUpgrade,
+    TypedWebSocketError, Utf8Bytes, WebSocket, WebSocketCodec, WebSocketRequest, WebSocketResponse,
+    error::{DecodeSnafu, EncodeSnafu, UnsupportedMessageSnafu, WebSocketSnafu},
+};
+use crate::response_error::WebSocketError;
+
+type Marker<In, Out, C> = PhantomData<fn() -> (In, Out, C)>;
+
+/// A [`WebSocket`] that receives `In` and sends `Out` messages, encoded by the codec `C`.
+///
+/// A message that cannot be decoded closes the connection, with the code `1007` if its payload is
+/// invalid, or `1003` if its frame type is not supported by the codec.
+pub struct TypedWebSocket<In, Out, C = JsonCodec> {
+    socket: WebSocket,
+    _marker: Marker<In, Out, C>,
+}
+
+impl<In, Out, C> fmt::Debug for TypedWebSocket<In, Out, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedWebSocket")
+            .field("socket", &self.socket)
+            .finish()
+    }
+}
+
+impl<In, Out, C> TypedWebSocket<In, Out, C>
+where
+    In: DeserializeOwned,
+    Out: Serialize,
+    C: WebSocketCodec,
+{
+    pub fn new(socket: WebSocket) -> Self {
+        Self {
+            socket,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Receive another message, skipping ping and pong messages.
+    ///
+    /// Returns `None` if the stream has closed.
+    pub async fn recv(&mut self) -> Option<Result<In, TypedWebSocketError>> {
+        loop {
+            let message = match self.socket.recv().await? {
+                Ok(message) => message,
+                Err(e) => return Some(Err(e).context(WebSocketSnafu)),
+            };
+
+            match message {
+                Message::Text(_) | Message::Binary(_) => {}
+                Message::Close(_) => return None,
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+            }
+
+            let result = match C::decode(&message) {
+                Some(Ok(value)) => Ok(value),
+                Some(Err(e)) => {
+                    self.close_with(CloseCode::Invalid, "failed to decode message")
+                        .await;
+
+                    Err(e).context(DecodeSnafu)
+                }
+                None => {
+                    self.close_with(CloseCode::Unsupported, "unsupported message frame type")
+                        .await;
+
+                    UnsupportedMessageSnafu.fail()
+                }
+            };
+
+            return Some(result);
+        }
+    }
+
+    /// Send a message.
+    pub async fn send(&mut self, message: &Out) -> Result<(), TypedWebSocketError> {
+        let message = C::encode(message).context(EncodeSnafu)?;
+        self.socket.send(message).await.context(WebSocketSnafu)
+    }
+
+    /// Gracefully close this WebSocket.
+    pub async fn close(&mut self, frame: Option<CloseFrame>) -> Result<(), TypedWebSocketError> {
+        self.socket.close(frame).await.context(WebSocketSnafu)
+    }
+
+    /// Return the selected WebSocket subprotocol, if one has been chosen.
+    #[inline(always)]
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.socket.protocol()
+    }
+
+    pub fn into_inner(self) -> WebSocket {
+        self.socket
+    }
+
+    async fn close_with(&mut self, code: CloseCode, reason: &'static str) {
+        let frame = CloseFrame {
+            code,
+            reason: Utf8Bytes::from_static(reason),
+        };
+
+        // the error that caused the close is more useful to the caller than a failure to close
+        if let Err(e) = self.socket.close(Some(frame)).await {
+            tracing::debug!("failed to close WebSocket: {:?}", e);
+        }
+    }
+}
+
+/// A [`WebSocketRequest`] whose messages are documented in the `x-websocket-messages` extension
+/// of the operation, upgraded to a [`TypedWebSocket`].
+pub struct TypedWebSocketRequest<In, Out, C = JsonCodec, F = DefaultOnFailedUpgrade> {
+    request: WebSocketRequest<F>,
+    _marker: Marker<In, Out, C>,
+}
+
+impl<In, Out, C, F> fmt::Debug for TypedWebSocketRequest<In, Out, C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedWebSocketRequest")
+            .field("request", &self.request)
+            .finish()
+    }
+}
+
+impl<In, Out, C, F> TypedWebSocketRequest<In, Out, C, F> {
+    pub fn config_mut(&mut self) -> &mut WebSocketConfig {
+        &mut self.request.config
+    }
+
+    pub fn protocols<I>(self, protocols: I) -> (Self, Option<I::Item>)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let (request, invalid_header_value) = self.request.protocols(protocols);
+
+        let request = Self {
+            request,
+            _marker: PhantomData,
+        };
+
+        (request, invalid_header_value)
+    }
+
+    pub fn on_failed_upgrade<G>(self, callback: G) -> TypedWebSocketRequest<In, Out, C, G>
+    where
+        G: OnFailedUpgrade,
+    {
+        TypedWebSocketRequest {
+            request: self.request.on_failed_upgrade(callback),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn on_upgrade<Cb, Fut>(self, callback: Cb) -> WebSocketResponse
+    where
+        F: OnFailedUpgrade,
+        In: DeserializeOwned,
+        Out: Serialize,
+        C: WebSocketCodec,
+        Cb: FnOnce(TypedWebSocket<In, Out, C>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.request
+            .on_upgrade(move |socket| callback(TypedWebSocket::new(socket)))
+    }
+}
+
+impl<In, Out, C> ApiRequestHead for TypedWebSocketRequest<In, Out, C>
+where
+    In: ToSchema,
+    Out: ToSchema,
+    C: WebSocketCodec,
+{
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<openapi::Parameter>> {
+        <WebSocketRequest as ApiRequestHead>::parameters(schemas, schemas_in_progress)
+    }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        let messages = serde_json::json!({
+            "contentType": C::CONTENT_TYPE,
+            "inbound": In::schema_ref(schemas, schemas_in_progress),
+            "outbound": Out::schema_ref(schemas, schemas_in_progress),
+        });
+
+        IndexMap::from([("x-websocket-messages".to_string(), messages)])
+    }
+}
+
+impl<In, Out, C> FromRequestHead for TypedWebSocketRequest<In, Out, C> {
+    type Error = WebSocketError;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        let request = WebSocketRequest::from_request_head(head).await?;
+
+        Ok(Self {
+            request,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use futures_util::{SinkExt, StreamExt};
+    use predawn_core::{
+        error::{BoxError, Error},
+        from_request::FromRequestHead,
+        request::Request,
+    };
+    use serde::{Serialize, de::DeserializeOwned};
+    use tokio_tungstenite::{
+        connect_async,
+        tungstenite::{Message, protocol::frame::coding::CloseCode},
+    };
+
+    use super::{JsonCodec, TypedWebSocketRequest, WebSocketCodec};
+    use crate::{
+        handler::handler_fn,
+        server::{Server, bind},
+    };
+
+    /// Receives JSON from text frames only.
+    struct TextCodec;
+
+    impl WebSocketCodec for TextCodec {
+        const CONTENT_TYPE: &'static str = "application/json";
+
+        fn encode<T: Serialize>(value: &T) -> Result<Message, BoxError> {
+            JsonCodec::encode(value)
+        }
+
+        fn decode<T: DeserializeOwned>(message: &Message) -> Option<Result<T, BoxError>> {
+            match message {
+                Message::Text(text) => {
+                    Some(serde_json::from_str(text.as_str()).map_err(Into::into))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Serves a WebSocket that echoes the numbers it receives, until a message fails to decode.
+    async fn serve<C: WebSocketCodec>() -> SocketAddr {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handler = handler_fn(|req: Request| async move {
+            let (mut head, _) = req.split();
+            let request =
+                TypedWebSocketRequest::<u32, u32, C>::from_request_head(&mut head).await?;
+
+            let response = request.on_upgrade(|mut socket| async move {
+                while let Some(Ok(number)) = socket.recv().await {
+                    socket.send(&number).await.unwrap();
+                }
+            });
+
+            Ok::<_, Error>(response)
+        });
+
+        tokio::spawn(Server::new(listener).run(handler));
+
+        addr
+    }
+
+    async fn close_code(addr: SocketAddr, message: Message) -> CloseCode {
+        let (mut stream, _) = connect_async(format!("ws://{addr}/")).await.unwrap();
+
+        stream.send(Message::text("1")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("1"));
+
+        stream.send(message).await.unwrap();
+
+        match stream.next().await.unwrap().unwrap() {
+            Message::Close(Some(frame)) => frame.code,
+            message => panic!("expected a close frame, got {message:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recv() {
+        let addr = serve::<JsonCodec>().await;
+        assert_eq!(
+            close_code(addr, Message::text("\"one\"")).await,
+            CloseCode::Invalid
+        );
+
+        let addr = serve::<TextCodec>().await;
+        assert_eq!(
+            close_code(addr, Message::binary("2")).await,
+            CloseCode::Unsupported
+        );
+    }
+}
@@ -0,0 +1,190 @@
+mod serve_dir;
+mod serve_file;
+
+use std::{
+    fs::Metadata,
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use headers::ETag;
+use http::{
+    HeaderMap, HeaderValue,
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, VARY},
+};
+use predawn_core::{request::Head, response::Response};
+use snafu::IntoError;
+use tokio::fs::File;
+
+pub use self::{serve_dir::ServeDir, serve_file::ServeFile};
+use crate::{
+    response::conditional::{Content, respond},
+    response_error::{ServeFileError, serve_file_error},
+};
+
+/// Which precompressed siblings (`<file>.br`, `<file>.gz`) may be served instead of a file.
+#[derive(Debug, Clone, Copy, Default)]
+struct Precompressed {
+    br: bool,
+    gzip: bool,
+}
+
+impl Precompressed {
+    fn is_enabled(self) -> bool {
+        self.br || self.gzip
+    }
+
+    /// The enabled encodings accepted by the request, in order of preference.
+    fn candidates(self, headers: &HeaderMap) -> impl Iterator<Item = (&'static str, &'static str)> {
+        [("br", "br", self.br), ("gzip", "gz", self.gzip)]
+            .into_iter()
+            .filter(move |(encoding, _, enabled)| *enabled && accepts_encoding(headers, encoding))
+            .map(|(encoding, extension, _)| (encoding, extension))
+    }
+}
+
+fn accepts_encoding(headers: &HeaderMap, encoding: &str) -> bool {
+    let mut wildcard = false;
+
+    for item in headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+    {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+
+        let acceptable = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("q")
+                    .then(|| value.trim().parse::<f32>().ok())?
+            })
+            .next()
+            .is_none_or(|q| q > 0.0);
+
+        if name.eq_ignore_ascii_case(encoding) {
+            return acceptable;
+        }
+
+        if name == "*" {
+            wildcard = acceptable;
+        }
+    }
+
+    wildcard
+}
+
+fn io_error(path: &Path, e: io::Error) -> ServeFileError {
+    match e.kind() {
+        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory => {
+            serve_file_error::NotFoundSnafu.build()
+        }
+        _ => serve_file_error::IoSnafu { path }.into_error(e),
+    }
+}
+
+fn guess_content_type(path: &Path) -> HeaderValue {
+    let mime = mime_guess::from_path(path)
+        .first_raw()
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM.as_ref());
+
+    HeaderValue::from_static(mime)
+}
+
+/// Opens `path`, failing with not found if it is not a regular file.
+pub(crate) async fn open_file(path: &Path) -> Result<(File, Metadata), ServeFileError> {
+    let file = File::open(path).await.map_err(|e| io_error(path, e))?;
+    let meta = file.metadata().await.map_err(|e| io_error(path, e))?;
+
+    if !meta.is_file() {
+        return Err(serve_file_error::NotFoundSnafu.build());
+    }
+
+    Ok((file, meta))
+}
+
+/// Opens `path`, or one of its precompressed siblings, skipping anything that is not a regular file.
+async fn open(
+    path: &Path,
+    precompressed: Precompressed,
+    headers: &HeaderMap,
+) -> Result<(File, Metadata, Option<&'static str>), ServeFileError> {
+    for (encoding, extension) in precompressed.candidates(headers) {
+        let mut sibling = path.as_os_str().to_owned();
+        sibling.push(".");
+        sibling.push(extension);
+        let sibling = PathBuf::from(sibling);
+
+        let Ok(file) = File::open(&sibling).await else {
+            continue;
+        };
+
+        match file.metadata().await {
+            Ok(meta) if meta.is_file() => return Ok((file, meta, Some(encoding))),
+            _ => continue,
+        }
+    }
+
+    let (file, meta) = open_file(path).await?;
+
+    Ok((file, meta, None))
+}
+
+pub(crate) fn file_etag(meta: &Metadata, encoding: Option<&str>) -> Option<ETag> {
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let len = meta.len();
+
+    let tag = match encoding {
+        Some(encoding) => format!("\"{:x}-{len:x}-{encoding}\"", modified.as_nanos()),
+        None => format!("\"{:x}-{len:x}\"", modified.as_nanos()),
+    };
+
+    tag.parse().ok()
+}
+
+/// Responds with the file at `path`, honoring conditional, range and `Accept-Encoding` request headers.
+///
+/// `content_type` is guessed from the extension of `path` if not given.
+async fn serve_file(
+    head: &Head,
+    path: &Path,
+    content_type: Option<&HeaderValue>,
+    precompressed: Precompressed,
+) -> Result<Response, ServeFileError> {
+    let (file, meta, encoding) = open(path, precompressed, &head.headers).await?;
+
+    let content_type = content_type
+        .cloned()
+        .unwrap_or_else(|| guess_content_type(path));
+
+    let mut response = Response::default();
+    let headers = response.headers_mut();
+
+    headers.insert(CONTENT_TYPE, content_type);
+
+    if let Some(encoding) = encoding {
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
+    }
+
+    if precompressed.is_enabled() {
+        headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+    }
+
+    let etag = file_etag(&meta, encoding);
+
+    let content = Content::File(file.into_std().await);
+
+    Ok(respond(
+        &head.method,
+        &head.headers,
+        response,
+        content,
+        meta.len(),
+        etag,
+        meta.modified().ok(),
+    ))
+}
@@ -0,0 +1,255 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use http::{HeaderValue, Method, StatusCode, header::LOCATION};
+use indexmap::IndexMap;
+use predawn_core::{
+    error::Error,
+    request::{Head, Request},
+    response::Response,
+};
+use rudi::Context;
+
+use super::{Precompressed, io_error, serve_file};
+use crate::{
+    handler::{DynHandler, Handler},
+    normalized_path::NormalizedPath,
+    path_params::PathParams,
+    plugin::Plugin,
+    response_error::{ServeFileError, serve_file_error},
+};
+
+/// Serves the files under a directory.
+///
+/// The requested file is taken from the catch-all path parameter named `path`, so it should be
+/// mounted at a route like `/assets/{*path}`. As a [`Plugin`], it registers both the route set by
+/// [`ServeDir::route`] and the catch-all route under it.
+#[derive(Debug, Clone)]
+pub struct ServeDir {
+    root: PathBuf,
+    route: NormalizedPath,
+    index_files: Vec<String>,
+    fallback: Option<PathBuf>,
+    precompressed: Precompressed,
+}
+
+impl ServeDir {
+    pub const PATH_PARAM: &'static str = "path";
+
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            route: NormalizedPath::default(),
+            index_files: vec!["index.html".to_string()],
+            fallback: None,
+            precompressed: Precompressed::default(),
+        }
+    }
+
+    /// The path to register when used as a [`Plugin`], defaults to `/`.
+    pub fn route<P: Into<NormalizedPath>>(mut self, route: P) -> Self {
+        self.route = route.into();
+        self
+    }
+
+    /// The files served for a directory, tried in order, defaults to `index.html`.
+    pub fn index_files<I, S>(mut self, index_files: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.index_files = index_files.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The file served with `200 OK` when a `GET` or `HEAD` request matches nothing.
+    pub fn fallback<P: Into<PathBuf>>(mut self, fallback: P) -> Self {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Falls back to the `index.html` of the root directory, for single-page applications.
+    pub fn spa_fallback(self) -> Self {
+        let index = self.root.join("index.html");
+        self.fallback(index)
+    }
+
+    /// Serves `<file>.br` instead of `<file>` if it exists and the client accepts `br`.
+    pub fn precompressed_br(mut self) -> Self {
+        self.precompressed.br = true;
+        self
+    }
+
+    /// Serves `<file>.gz` instead of `<file>` if it exists and the client accepts `gzip`.
+    pub fn precompressed_gzip(mut self) -> Self {
+        self.precompressed.gzip = true;
+        self
+    }
+
+    pub async fn serve(&self, head: &Head) -> Result<Response, ServeFileError> {
+        let relative = match head.extensions.get::<PathParams>() {
+            Some(PathParams::Ok(params)) => params
+                .iter()
+                .find(|(key, _)| &**key == Self::PATH_PARAM)
+                .map(|(_, value)| value.clone()),
+            Some(PathParams::Err(_)) => return self.not_found(head).await,
+            None => None,
+        };
+
+        let relative = relative.as_deref().unwrap_or_default();
+
+        let Some(path) = self.resolve(relative) else {
+            return self.not_found(head).await;
+        };
+
+        let meta = match tokio::fs::metadata(&path).await {
+            Ok(meta) => meta,
+            Err(e) => {
+                return match io_error(&path, e) {
+                    ServeFileError::NotFoundError { .. } => self.not_found(head).await,
+                    e => Err(e),
+                };
+            }
+        };
+
+        if !meta.is_dir() {
+            return self.serve_file(head, &path).await;
+        }
+
+        if self.index_files.is_empty() {
+            return self.not_found(head).await;
+        }
+
+        // relative links in an index file only work if the directory ends with a slash
+        if !relative.is_empty() && !head.uri.path().ends_with('/') {
+            return Ok(redirect_with_slash(head));
+        }
+
+        for index_file in &self.index_files {
+            let index = path.join(index_file);
+
+            if tokio::fs::metadata(&index)
+                .await
+                .is_ok_and(|meta| meta.is_file())
+            {
+                return self.serve_file(head, &index).await;
+            }
+        }
+
+        self.not_found(head).await
+    }
+
+    async fn serve_file(&self, head: &Head, path: &Path) -> Result<Response, ServeFileError> {
+        serve_file(head, path, None, self.precompressed).await
+    }
+
+    async fn not_found(&self, head: &Head) -> Result<Response, ServeFileError> {
+        match &self.fallback {
+            Some(fallback) if head.method == Method::GET || head.method == Method::HEAD => {
+                self.serve_file(head, fallback).await
+            }
+            _ => Err(serve_file_error::NotFoundSnafu.build()),
+        }
+    }
+
+    /// Joins the percent-decoded request path onto the root, rejecting anything but plain file names.
+    fn resolve(&self, relative: &str) -> Option<PathBuf> {
+        let relative = NormalizedPath::new(relative);
+
+        let mut path = self.root.clone();
+
+        for segment in relative.split('/').filter(|segment| !segment.is_empty()) {
+            if segment.contains('\\') {
+                return None;
+            }
+
+            let mut components = Path::new(segment).components();
+
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(name)), None) => path.push(name),
+                (Some(Component::CurDir), None) => {}
+                _ => return None,
+            }
+        }
+
+        Some(path)
+    }
+}
+
+fn redirect_with_slash(head: &Head) -> Response {
+    let location = match head.uri.query() {
+        Some(query) => format!("{}/?{query}", head.uri.path()),
+        None => format!("{}/", head.uri.path()),
+    };
+
+    let mut response = Response::default();
+    *response.status_mut() = StatusCode::TEMPORARY_REDIRECT;
+
+    if let Ok(location) = HeaderValue::try_from(location) {
+        response.headers_mut().insert(LOCATION, location);
+    }
+
+    response
+}
+
+impl Handler for ServeDir {
+    async fn call(&self, req: Request) -> Result<Response, Error> {
+        Ok(self.serve(&req.head).await?)
+    }
+}
+
+impl Plugin for ServeDir {
+    fn create_route(
+        self: Arc<Self>,
+        _: &mut Context,
+    ) -> (NormalizedPath, IndexMap<Method, DynHandler>) {
+        let path = self
+            .route
+            .clone()
+            .join(NormalizedPath::new(&format!("{{*{}}}", Self::PATH_PARAM)));
+
+        (path, methods(DynHandler::new(self)))
+    }
+
+    fn create_routes(
+        self: Arc<Self>,
+        cx: &mut Context,
+    ) -> Vec<(NormalizedPath, IndexMap<Method, DynHandler>)> {
+        let root = (self.route.clone(), methods(DynHandler::new(self.clone())));
+
+        vec![root, self.create_route(cx)]
+    }
+}
+
+pub(super) fn methods(handler: DynHandler) -> IndexMap<Method, DynHandler> {
+    let mut map = IndexMap::with_capacity(2);
+    map.insert(Method::GET, handler.clone());
+    map.insert(Method::HEAD, handler);
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::ServeDir;
+
+    #[test]
+    fn test_resolve() {
+        let dir = ServeDir::new("dist");
+
+        assert_eq!(dir.resolve(""), Some(PathBuf::from("dist")));
+        assert_eq!(dir.resolve("/"), Some(PathBuf::from("dist")));
+        assert_eq!(
+            dir.resolve("js//app.js"),
+            Some(PathBuf::from("dist/js/app.js"))
+        );
+        assert_eq!(dir.resolve("./app.js"), Some(PathBuf::from("dist/app.js")));
+
+        assert_eq!(dir.resolve(".."), None);
+        assert_eq!(dir.resolve("js/../../secret"), None);
+        assert_eq!(dir.resolve("..\\secret"), None);
+    }
+}
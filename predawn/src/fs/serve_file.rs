@@ -0,0 +1,87 @@
+use std::{path::PathBuf, sync::Arc};
+
+use http::{HeaderValue, Method};
+use indexmap::IndexMap;
+use predawn_core::{
+    error::Error,
+    request::{Head, Request},
+    response::Response,
+};
+use rudi::Context;
+
+use super::{Precompressed, serve_dir::methods, serve_file};
+use crate::{
+    handler::{DynHandler, Handler},
+    normalized_path::NormalizedPath,
+    plugin::Plugin,
+    response_error::ServeFileError,
+};
+
+/// Serves a single file.
+#[derive(Debug, Clone)]
+pub struct ServeFile {
+    path: PathBuf,
+    route: NormalizedPath,
+    content_type: Option<HeaderValue>,
+    precompressed: Precompressed,
+}
+
+impl ServeFile {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            route: NormalizedPath::default(),
+            content_type: None,
+            precompressed: Precompressed::default(),
+        }
+    }
+
+    /// The path to register when used as a [`Plugin`], defaults to `/`.
+    pub fn route<P: Into<NormalizedPath>>(mut self, route: P) -> Self {
+        self.route = route.into();
+        self
+    }
+
+    /// Overrides the content type guessed from the file extension.
+    pub fn content_type(mut self, content_type: HeaderValue) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Serves `<file>.br` instead of `<file>` if it exists and the client accepts `br`.
+    pub fn precompressed_br(mut self) -> Self {
+        self.precompressed.br = true;
+        self
+    }
+
+    /// Serves `<file>.gz` instead of `<file>` if it exists and the client accepts `gzip`.
+    pub fn precompressed_gzip(mut self) -> Self {
+        self.precompressed.gzip = true;
+        self
+    }
+
+    pub async fn serve(&self, head: &Head) -> Result<Response, ServeFileError> {
+        serve_file(
+            head,
+            &self.path,
+            self.content_type.as_ref(),
+            self.precompressed,
+        )
+        .await
+    }
+}
+
+impl Handler for ServeFile {
+    async fn call(&self, req: Request) -> Result<Response, Error> {
+        Ok(self.serve(&req.head).await?)
+    }
+}
+
+impl Plugin for ServeFile {
+    fn create_route(
+        self: Arc<Self>,
+        _: &mut Context,
+    ) -> (NormalizedPath, IndexMap<Method, DynHandler>) {
+        (self.route.clone(), methods(DynHandler::new(self)))
+    }
+}
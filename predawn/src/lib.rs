@@ -4,11 +4,15 @@ extern crate self as predawn;
 
 pub mod any_map;
 pub mod app;
+pub mod client;
 pub mod config;
 #[doc(hidden)]
 pub mod controller;
 pub mod environment;
 pub mod extract;
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[cfg(feature = "fs")]
+pub mod fs;
 pub mod handler;
 mod macros;
 pub mod media_type;
@@ -18,26 +22,31 @@ pub mod openapi;
 mod path_params;
 pub mod payload;
 pub mod plugin;
+pub mod pubsub;
 pub mod response;
 pub mod response_error;
 pub mod route;
 pub mod server;
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+#[cfg(feature = "session")]
+pub mod session;
 pub mod test_client;
 mod traits;
 pub(crate) mod util;
+pub mod validate;
 pub use error2;
 pub use http;
 pub use predawn_core::{
     api_request, api_response, body, either, error, from_request, into_response,
     media_type::{MultiRequestMediaType, MultiResponseMediaType},
-    request,
+    problem, request,
     response::{MultiResponse, SingleResponse},
 };
 #[cfg_attr(docsrs, doc(cfg(feature = "macro")))]
 #[cfg(feature = "macro")]
 pub use predawn_macro::{
-    MultiRequestMediaType, MultiResponse, MultiResponseMediaType, SecurityScheme, SingleResponse,
-    Tag, ToParameters, controller,
+    Example, MultiRequestMediaType, MultiResponse, MultiResponseMediaType, SecurityScheme,
+    SingleResponse, Tag, ToParameters, controller,
 };
 #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
 #[cfg(feature = "schemars")]
@@ -47,12 +56,13 @@ pub use predawn_schema::to_schema::ToSchema;
 #[cfg(feature = "macro")]
 pub use predawn_schema_macro::ToSchema;
 
-pub use self::traits::{SecurityScheme, Tag, ToParameters};
+pub use self::traits::{Example, SecurityScheme, Tag, ToParameters};
 
 #[doc(hidden)]
 pub mod __internal {
     pub use indexmap;
     pub use paste;
+    pub use regex;
     pub use rudi;
     pub use serde_json;
 }
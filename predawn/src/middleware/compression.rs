@@ -0,0 +1,564 @@
+use std::{collections::BTreeMap, io};
+
+use async_compression::{
+    Level,
+    tokio::bufread::{BrotliEncoder, GzipEncoder, ZlibEncoder, ZstdEncoder},
+};
+use futures_util::TryStreamExt;
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{
+        ACCEPT_ENCODING, ACCEPT_RANGES, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH,
+        CONTENT_RANGE, CONTENT_TYPE, ETAG, VARY,
+    },
+};
+use http_body::Body;
+use http_body_util::BodyExt;
+use predawn_core::{
+    body::ResponseBody,
+    error::Error,
+    openapi::{Header, ParameterSchemaOrContent, ReferenceOr, Responses, Schema},
+    request::Request,
+    response::Response,
+};
+use predawn_schema::ToSchema;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use super::Middleware;
+use crate::{
+    config::server::{CompressionConfig, CompressionLevel},
+    handler::Handler,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Gzip,
+    Deflate,
+    Br,
+    Zstd,
+}
+
+impl Encoding {
+    /// Preferred order when the client assigns the same quality to several encodings.
+    const PREFERENCE: [Encoding; 4] = [
+        Encoding::Br,
+        Encoding::Zstd,
+        Encoding::Gzip,
+        Encoding::Deflate,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Br => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            s if s.eq_ignore_ascii_case("gzip") || s.eq_ignore_ascii_case("x-gzip") => {
+                Some(Encoding::Gzip)
+            }
+            s if s.eq_ignore_ascii_case("deflate") => Some(Encoding::Deflate),
+            s if s.eq_ignore_ascii_case("br") => Some(Encoding::Br),
+            s if s.eq_ignore_ascii_case("zstd") => Some(Encoding::Zstd),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Encoding::Gzip => 0,
+            Encoding::Deflate => 1,
+            Encoding::Br => 2,
+            Encoding::Zstd => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Compression {
+    enabled: [bool; 4],
+    min_size: u64,
+    level: CompressionLevel,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::from(&CompressionConfig::default())
+    }
+}
+
+impl From<&CompressionConfig> for Compression {
+    fn from(cfg: &CompressionConfig) -> Self {
+        let mut enabled = [false; 4];
+        enabled[Encoding::Gzip.index()] = cfg.gzip;
+        enabled[Encoding::Deflate.index()] = cfg.deflate;
+        enabled[Encoding::Br.index()] = cfg.br;
+        enabled[Encoding::Zstd.index()] = cfg.zstd;
+
+        Self {
+            enabled,
+            min_size: cfg.min_size,
+            level: cfg.level,
+        }
+    }
+}
+
+impl Compression {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn encoding(mut self, encoding: Encoding, enable: bool) -> Self {
+        self.enabled[encoding.index()] = enable;
+        self
+    }
+
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn level(mut self, level: CompressionLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The `Content-Encoding` header of the responses this middleware may compress, `None` if
+    /// every encoding is disabled.
+    pub(crate) fn content_encoding_header(
+        &self,
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<ReferenceOr<Header>> {
+        let encodings = Encoding::PREFERENCE
+            .into_iter()
+            .filter(|encoding| self.enabled[encoding.index()])
+            .map(|encoding| format!("`{}`", encoding.as_str()))
+            .collect::<Vec<_>>();
+
+        if encodings.is_empty() {
+            return None;
+        }
+
+        Some(ReferenceOr::Item(Header {
+            description: Some(format!(
+                "one of {}, when the request accepts it and the body is large enough",
+                encodings.join(", ")
+            )),
+            style: Default::default(),
+            required: false,
+            deprecated: Default::default(),
+            format: ParameterSchemaOrContent::Schema(<String as ToSchema>::schema_ref(
+                schemas,
+                schemas_in_progress,
+            )),
+            example: Default::default(),
+            examples: Default::default(),
+            extensions: Default::default(),
+        }))
+    }
+}
+
+/// Documents `header` on the responses with a media type that may be compressed.
+pub(crate) fn document_content_encoding(responses: &mut Responses, header: &ReferenceOr<Header>) {
+    responses.responses.values_mut().for_each(|response| {
+        let ReferenceOr::Item(response) = response else {
+            return;
+        };
+
+        if response
+            .content
+            .keys()
+            .any(|media_type| is_compressible(media_type))
+        {
+            response
+                .headers
+                .insert(CONTENT_ENCODING.as_str().to_string(), header.clone());
+        }
+    });
+}
+
+impl<H: Handler> Middleware<H> for Compression {
+    type Output = CompressionHandler<H>;
+
+    fn transform(self, input: H) -> Self::Output {
+        CompressionHandler {
+            compression: self,
+            inner: input,
+        }
+    }
+}
+
+pub struct CompressionHandler<H> {
+    compression: Compression,
+    inner: H,
+}
+
+impl<H: Handler> Handler for CompressionHandler<H> {
+    async fn call(&self, req: Request) -> Result<Response, Error> {
+        let encoding = negotiate(&req.head.headers, &self.compression.enabled);
+
+        let mut response = self.inner.call(req).await?;
+
+        if !should_compress(&response, self.compression.min_size) {
+            return Ok(response);
+        }
+
+        add_vary(response.headers_mut());
+
+        let Some(encoding) = encoding else {
+            return Ok(response);
+        };
+
+        let headers = response.headers_mut();
+        headers.remove(CONTENT_LENGTH);
+        headers.remove(ACCEPT_RANGES);
+        weaken_etag(headers);
+        headers.insert(
+            CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+
+        let body = response.body_mut();
+        *body = compress(std::mem::take(body), encoding, self.compression.level);
+
+        Ok(response)
+    }
+}
+
+fn negotiate(headers: &HeaderMap, enabled: &[bool; 4]) -> Option<Encoding> {
+    // qualities are stored in thousandths
+    let mut explicit: [Option<u16>; 4] = [None; 4];
+    let mut wildcard = None;
+
+    for value in headers.get_all(ACCEPT_ENCODING) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+
+        for item in value.split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or_default().trim();
+
+            let quality = parts
+                .filter_map(|param| {
+                    let (key, value) = param.split_once('=')?;
+                    key.trim()
+                        .eq_ignore_ascii_case("q")
+                        .then(|| value.trim().parse::<f32>().ok())?
+                })
+                .next()
+                .map(|q| (q.clamp(0.0, 1.0) * 1000.0) as u16)
+                .unwrap_or(1000);
+
+            if name == "*" {
+                wildcard = Some(quality);
+            } else if let Some(encoding) = Encoding::from_name(name) {
+                explicit[encoding.index()] = Some(quality);
+            }
+        }
+    }
+
+    let mut best: Option<(Encoding, u16)> = None;
+
+    for encoding in Encoding::PREFERENCE {
+        if !enabled[encoding.index()] {
+            continue;
+        }
+
+        let quality = explicit[encoding.index()].or(wildcard).unwrap_or(0);
+
+        if quality == 0 {
+            continue;
+        }
+
+        match best {
+            Some((_, best_quality)) if best_quality >= quality => {}
+            _ => best = Some((encoding, quality)),
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+fn should_compress(response: &Response, min_size: u64) -> bool {
+    let status = response.status();
+
+    if status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return false;
+    }
+
+    let headers = response.headers();
+
+    if headers.contains_key(CONTENT_ENCODING) || headers.contains_key(CONTENT_RANGE) {
+        return false;
+    }
+
+    let no_transform = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+
+    if no_transform {
+        return false;
+    }
+
+    let Some(content_type) = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    if !is_compressible(content_type) {
+        return false;
+    }
+
+    match response.body().size_hint().exact() {
+        Some(size) => size >= min_size,
+        None => true,
+    }
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if essence == mime::TEXT_EVENT_STREAM.essence_str() {
+        return false;
+    }
+
+    if essence == mime::IMAGE_SVG.essence_str() {
+        return true;
+    }
+
+    !(essence.starts_with("image/")
+        || essence.starts_with("audio/")
+        || essence.starts_with("video/")
+        || essence.starts_with("application/grpc")
+        || matches!(
+            essence.as_str(),
+            "application/gzip" | "application/zip" | "application/zstd" | "application/x-brotli"
+        ))
+}
+
+fn add_vary(headers: &mut HeaderMap) {
+    let exists = headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| {
+            let value = value.trim();
+            value == "*" || value.eq_ignore_ascii_case("accept-encoding")
+        });
+
+    if !exists {
+        headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+/// The encoded body is no longer byte-for-byte the content a strong `ETag` was computed from.
+fn weaken_etag(headers: &mut HeaderMap) {
+    if let Some(etag) = headers.get(ETAG)
+        && etag.as_bytes().starts_with(b"\"")
+    {
+        let weak = [b"W/", etag.as_bytes()].concat();
+        headers.insert(ETAG, HeaderValue::from_bytes(&weak).unwrap());
+    }
+}
+
+fn compress(body: ResponseBody, encoding: Encoding, level: CompressionLevel) -> ResponseBody {
+    let reader = StreamReader::new(body.into_data_stream().map_err(io::Error::other));
+
+    let level = match (encoding, level) {
+        // the default quality of brotli is too slow for dynamic responses
+        (Encoding::Br, CompressionLevel::Default) => Level::Precise(4),
+        (_, CompressionLevel::Fastest) => Level::Fastest,
+        (_, CompressionLevel::Default) => Level::Default,
+        (_, CompressionLevel::Best) => Level::Best,
+        (_, CompressionLevel::Precise(quality)) => Level::Precise(quality),
+    };
+
+    match encoding {
+        Encoding::Gzip => {
+            ResponseBody::from_stream(ReaderStream::new(GzipEncoder::with_quality(reader, level)))
+        }
+        Encoding::Deflate => {
+            ResponseBody::from_stream(ReaderStream::new(ZlibEncoder::with_quality(reader, level)))
+        }
+        Encoding::Br => ResponseBody::from_stream(ReaderStream::new(BrotliEncoder::with_quality(
+            reader, level,
+        ))),
+        Encoding::Zstd => {
+            ResponseBody::from_stream(ReaderStream::new(ZstdEncoder::with_quality(reader, level)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use http::{
+        HeaderMap, HeaderValue,
+        header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, ETAG},
+    };
+    use predawn_core::{
+        body::ResponseBody,
+        error::Error,
+        openapi::{self, ReferenceOr, Responses, StatusCode},
+        response::Response,
+    };
+
+    use super::{Compression, Encoding, document_content_encoding, negotiate};
+    use crate::{
+        handler::{HandlerExt, handler_fn},
+        server::{Server, bind},
+    };
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let all = [true; 4];
+
+        assert_eq!(negotiate(&HeaderMap::new(), &all), None);
+        assert_eq!(negotiate(&accept("identity"), &all), None);
+        assert_eq!(negotiate(&accept("gzip, br"), &all), Some(Encoding::Br));
+        assert_eq!(
+            negotiate(&accept("gzip;q=1.0, br;q=0.5"), &all),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(negotiate(&accept("*"), &all), Some(Encoding::Br));
+        assert_eq!(
+            negotiate(&accept("br;q=0, zstd;q=0, *;q=0.1"), &all),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(negotiate(&accept("gzip;q=0"), &all), None);
+
+        let mut only_gzip = [false; 4];
+        only_gzip[Encoding::Gzip.index()] = true;
+
+        assert_eq!(
+            negotiate(&accept("br, zstd, x-gzip;q=0.2"), &only_gzip),
+            Some(Encoding::Gzip)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weaken_etag() {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handler = handler_fn(|_| async {
+            let mut response = Response::new(ResponseBody::from("hello ".repeat(100)));
+
+            let headers = response.headers_mut();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+            headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+
+            Ok::<_, Error>(response)
+        });
+
+        let server = Server::new(listener);
+        tokio::spawn(server.run(handler.with(Compression::new().min_size(0))));
+
+        let request = |encoding| {
+            reqwest::Client::new()
+                .get(format!("http://{addr}/"))
+                .header(ACCEPT_ENCODING, encoding)
+                .send()
+        };
+
+        let response = request("gzip").await.unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[ETAG], "W/\"abc\"");
+
+        let response = request("identity").await.unwrap();
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(response.headers()[ETAG], "\"abc\"");
+    }
+
+    #[test]
+    fn test_document_content_encoding() {
+        let header = Compression::new()
+            .encoding(Encoding::Br, false)
+            .encoding(Encoding::Zstd, false)
+            .content_encoding_header(&mut BTreeMap::new(), &mut Vec::new())
+            .unwrap();
+
+        let ReferenceOr::Item(item) = &header else {
+            panic!("expected an inline header");
+        };
+        assert!(!item.required);
+        assert_eq!(
+            item.description.as_deref(),
+            Some(
+                "one of `gzip`, `deflate`, when the request accepts it and the body is large enough"
+            )
+        );
+
+        let response = |media_type: Option<&str>| {
+            let mut response = openapi::Response::default();
+
+            if let Some(media_type) = media_type {
+                response
+                    .content
+                    .insert(media_type.to_string(), Default::default());
+            }
+
+            ReferenceOr::Item(response)
+        };
+
+        let mut responses = Responses::default();
+        responses.responses.extend([
+            (StatusCode::Code(200), response(Some("application/json"))),
+            (StatusCode::Code(201), response(Some("text/event-stream"))),
+            (StatusCode::Code(204), response(None)),
+        ]);
+
+        document_content_encoding(&mut responses, &header);
+
+        let documented = responses
+            .responses
+            .iter()
+            .filter_map(|(status, response)| match response {
+                ReferenceOr::Item(response)
+                    if response.headers.contains_key("content-encoding") =>
+                {
+                    Some(status.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(documented, [StatusCode::Code(200)]);
+
+        let none = Compression::new()
+            .encoding(Encoding::Gzip, false)
+            .encoding(Encoding::Deflate, false)
+            .encoding(Encoding::Br, false)
+            .encoding(Encoding::Zstd, false)
+            .content_encoding_header(&mut BTreeMap::new(), &mut Vec::new());
+
+        assert!(none.is_none());
+    }
+}
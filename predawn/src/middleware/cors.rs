@@ -0,0 +1,525 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        ORIGIN, VARY,
+    },
+};
+use predawn_core::{
+    error::Error,
+    request::{Head, Request},
+    response::Response,
+};
+use regex::Regex;
+
+use super::Middleware;
+use crate::{config::server::CorsConfig, handler::Handler, route::Router};
+
+type Predicate = Arc<dyn Fn(&HeaderValue, &Head) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+enum OriginMatcher {
+    Any,
+    Exact(HeaderValue),
+    Wildcard { prefix: String, suffix: String },
+    Regex(Regex),
+    Predicate(Predicate),
+}
+
+impl fmt::Debug for OriginMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OriginMatcher::Any => f.write_str("Any"),
+            OriginMatcher::Exact(origin) => f.debug_tuple("Exact").field(origin).finish(),
+            OriginMatcher::Wildcard { prefix, suffix } => f
+                .debug_struct("Wildcard")
+                .field("prefix", prefix)
+                .field("suffix", suffix)
+                .finish(),
+            OriginMatcher::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            OriginMatcher::Predicate(_) => f.write_str("Predicate"),
+        }
+    }
+}
+
+impl OriginMatcher {
+    fn matches(&self, origin: &HeaderValue, head: &Head) -> bool {
+        match self {
+            OriginMatcher::Any => true,
+            OriginMatcher::Exact(exact) => exact == origin,
+            OriginMatcher::Wildcard { prefix, suffix } => origin.to_str().is_ok_and(|origin| {
+                origin.len() > prefix.len() + suffix.len()
+                    && origin.starts_with(prefix.as_str())
+                    && origin.ends_with(suffix.as_str())
+            }),
+            OriginMatcher::Regex(regex) => {
+                origin.to_str().is_ok_and(|origin| regex.is_match(origin))
+            }
+            OriginMatcher::Predicate(predicate) => predicate(origin, head),
+        }
+    }
+}
+
+/// Handles [CORS](https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS) requests.
+///
+/// Preflight requests are answered directly. If no methods are configured, the methods
+/// registered for the requested path are allowed, see [`Cors::routes`].
+#[derive(Debug, Clone, Default)]
+pub struct Cors {
+    allow_origins: Vec<OriginMatcher>,
+    allow_methods: Option<Vec<Method>>,
+    allow_headers: Option<Vec<HeaderName>>,
+    allow_any_header: bool,
+    expose_headers: Vec<HeaderName>,
+    allow_credentials: bool,
+    max_age: Option<Duration>,
+    routes: Option<Arc<matchit::Router<Box<[Method]>>>>,
+}
+
+impl Cors {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn allow_any_origin(mut self) -> Self {
+        self.allow_origins.push(OriginMatcher::Any);
+        self
+    }
+
+    /// Allows an exact origin, or a wildcard pattern such as `https://*.example.com`.
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        let matcher = match origin.split_once('*') {
+            _ if origin == "*" => OriginMatcher::Any,
+            Some((prefix, suffix)) => OriginMatcher::Wildcard {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            },
+            None => OriginMatcher::Exact(
+                HeaderValue::from_str(origin)
+                    .unwrap_or_else(|e| panic!("invalid origin `{origin}`: {e}")),
+            ),
+        };
+
+        self.allow_origins.push(matcher);
+        self
+    }
+
+    /// Allows the origins fully matched by `regex`.
+    pub fn allow_origin_regex(mut self, regex: &str) -> Self {
+        let regex = Regex::new(&format!("^(?:{regex})$"))
+            .unwrap_or_else(|e| panic!("invalid origin regex `{regex}`: {e}"));
+
+        self.allow_origins.push(OriginMatcher::Regex(regex));
+        self
+    }
+
+    pub fn allow_origin_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&HeaderValue, &Head) -> bool + Send + Sync + 'static,
+    {
+        self.allow_origins
+            .push(OriginMatcher::Predicate(Arc::new(predicate)));
+        self
+    }
+
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.allow_methods
+            .get_or_insert_with(Vec::new)
+            .extend(methods);
+        self
+    }
+
+    /// Allows any request header by mirroring `Access-Control-Request-Headers`.
+    pub fn allow_any_header(mut self) -> Self {
+        self.allow_any_header = true;
+        self
+    }
+
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.allow_headers
+            .get_or_insert_with(Vec::new)
+            .extend(headers);
+        self
+    }
+
+    pub fn expose_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.expose_headers.extend(headers);
+        self
+    }
+
+    /// Can not be combined with [`Cors::allow_any_origin`], the handler panics when created.
+    pub fn allow_credentials(mut self, allow_credentials: bool) -> Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Uses the methods registered in `router` to answer preflight requests.
+    pub fn routes(mut self, router: &Router) -> Self {
+        let mut routes = matchit::Router::new();
+
+        for (path, methods) in router.routes() {
+            let mut methods = methods.to_vec();
+
+            if methods.contains(&Method::GET) && !methods.contains(&Method::HEAD) {
+                methods.push(Method::HEAD);
+            }
+
+            // already inserted into `router`, so it can not fail
+            let _ = routes.insert(path.to_string(), methods.into_boxed_slice());
+        }
+
+        self.routes = Some(Arc::new(routes));
+        self
+    }
+
+    fn is_any_origin(&self) -> bool {
+        self.allow_origins
+            .iter()
+            .any(|matcher| matches!(matcher, OriginMatcher::Any))
+    }
+
+    fn ensure_usable(&self) {
+        // mirroring every origin with credentials would let any site make credentialed requests
+        assert!(
+            !(self.allow_credentials && self.is_any_origin()),
+            "invalid CORS configuration: cannot combine `Access-Control-Allow-Credentials: true` \
+             with `Access-Control-Allow-Origin: *`"
+        );
+    }
+
+    fn is_allowed(&self, origin: &HeaderValue, head: &Head) -> bool {
+        self.allow_origins
+            .iter()
+            .any(|matcher| matcher.matches(origin, head))
+    }
+
+    fn append_common_headers(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        if self.is_any_origin() {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        }
+
+        if self.allow_credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+
+    fn preflight(&self, head: &Head, origin: &HeaderValue) -> Option<Response> {
+        let methods = match (&self.allow_methods, &self.routes) {
+            (Some(methods), _) => methods.clone(),
+            (None, Some(routes)) => routes.at(head.uri.path()).ok()?.value.to_vec(),
+            (None, None) => head
+                .headers
+                .get(ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|method| Method::from_bytes(method.as_bytes()).ok())
+                .into_iter()
+                .collect(),
+        };
+
+        let mut response = Response::default();
+        *response.status_mut() = StatusCode::NO_CONTENT;
+
+        let headers = response.headers_mut();
+
+        append_vary(
+            headers,
+            &[
+                ORIGIN,
+                ACCESS_CONTROL_REQUEST_METHOD,
+                ACCESS_CONTROL_REQUEST_HEADERS,
+            ],
+        );
+
+        if !self.is_allowed(origin, head) {
+            return Some(response);
+        }
+
+        self.append_common_headers(headers, origin);
+
+        if let Some(methods) = join(methods.iter().map(Method::as_str)) {
+            headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+
+        let allow_headers = if self.allow_any_header {
+            head.headers.get(ACCESS_CONTROL_REQUEST_HEADERS).cloned()
+        } else {
+            self.allow_headers
+                .as_ref()
+                .and_then(|h| join(h.iter().map(HeaderName::as_str)))
+        };
+
+        if let Some(allow_headers) = allow_headers {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+
+        if let Some(max_age) = self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.as_secs().into());
+        }
+
+        Some(response)
+    }
+}
+
+impl From<&CorsConfig> for Cors {
+    fn from(cfg: &CorsConfig) -> Self {
+        let mut cors = Cors::new().allow_credentials(cfg.allow_credentials);
+
+        for origin in &cfg.allow_origins {
+            cors = cors.allow_origin(origin);
+        }
+
+        for regex in &cfg.allow_origin_regexes {
+            cors = cors.allow_origin_regex(regex);
+        }
+
+        if !cfg.allow_methods.is_empty() {
+            cors = cors.allow_methods(cfg.allow_methods.iter().map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                    .unwrap_or_else(|e| panic!("invalid method `{method}`: {e}"))
+            }));
+        }
+
+        if cfg.allow_headers.iter().any(|header| header == "*") {
+            cors = cors.allow_any_header();
+        } else if !cfg.allow_headers.is_empty() {
+            cors = cors.allow_headers(cfg.allow_headers.iter().map(|h| parse_header_name(h)));
+        }
+
+        cors = cors.expose_headers(cfg.expose_headers.iter().map(|h| parse_header_name(h)));
+
+        if let Some(max_age) = cfg.max_age {
+            cors = cors.max_age(max_age);
+        }
+
+        cors
+    }
+}
+
+fn parse_header_name(name: &str) -> HeaderName {
+    HeaderName::from_bytes(name.as_bytes())
+        .unwrap_or_else(|e| panic!("invalid header name `{name}`: {e}"))
+}
+
+fn join<'a, I>(values: I) -> Option<HeaderValue>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let joined = values.collect::<Vec<_>>().join(", ");
+
+    if joined.is_empty() {
+        return None;
+    }
+
+    HeaderValue::from_str(&joined).ok()
+}
+
+fn append_vary(headers: &mut HeaderMap, names: &[HeaderName]) {
+    if let Some(value) = join(names.iter().map(HeaderName::as_str)) {
+        headers.append(VARY, value);
+    }
+}
+
+impl<H: Handler> Middleware<H> for Cors {
+    type Output = CorsHandler<H>;
+
+    fn transform(self, input: H) -> Self::Output {
+        self.ensure_usable();
+
+        CorsHandler {
+            cors: self,
+            inner: input,
+        }
+    }
+}
+
+pub struct CorsHandler<H> {
+    cors: Cors,
+    inner: H,
+}
+
+impl<H: Handler> Handler for CorsHandler<H> {
+    async fn call(&self, req: Request) -> Result<Response, Error> {
+        let cors = &self.cors;
+        let head = &req.head;
+
+        let Some(origin) = head.headers.get(ORIGIN).cloned() else {
+            return self.inner.call(req).await;
+        };
+
+        if head.method == Method::OPTIONS
+            && head.headers.contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            if let Some(response) = cors.preflight(head, &origin) {
+                return Ok(response);
+            }
+
+            return self.inner.call(req).await;
+        }
+
+        let allowed = cors.is_allowed(&origin, head);
+
+        let mut result = self.inner.call(req).await;
+
+        let headers = match &mut result {
+            Ok(response) => response.headers_mut(),
+            Err(e) => e.response_mut().headers_mut(),
+        };
+
+        if !cors.is_any_origin() {
+            append_vary(headers, &[ORIGIN]);
+        }
+
+        if allowed {
+            cors.append_common_headers(headers, &origin);
+
+            if let Some(expose_headers) = join(cors.expose_headers.iter().map(HeaderName::as_str)) {
+                headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose_headers);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use http::{
+        HeaderValue, Method, StatusCode,
+        header::{
+            ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE,
+            ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+        },
+    };
+    use predawn_core::error::Error;
+
+    use super::Cors;
+    use crate::{
+        handler::{HandlerExt, handler_fn},
+        server::{Server, bind},
+    };
+
+    async fn serve(cors: Cors) -> SocketAddr {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handler = handler_fn(|_| async { Ok::<_, Error>("hello") }).with(cors);
+        tokio::spawn(Server::new(listener).run(handler));
+
+        addr
+    }
+
+    async fn allow_origin(addr: SocketAddr, origin: &'static str) -> Option<HeaderValue> {
+        let response = reqwest::Client::new()
+            .get(format!("http://{addr}/"))
+            .header(ORIGIN, origin)
+            .send()
+            .await
+            .unwrap();
+
+        response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).cloned()
+    }
+
+    #[tokio::test]
+    async fn test_preflight() {
+        let cors = Cors::new()
+            .allow_origin("https://example.com")
+            .allow_methods([Method::GET, Method::POST])
+            .allow_any_header()
+            .allow_credentials(true)
+            .max_age(std::time::Duration::from_secs(600));
+
+        let addr = serve(cors).await;
+
+        let preflight = |origin| {
+            reqwest::Client::new()
+                .request(Method::OPTIONS, format!("http://{addr}/"))
+                .header(ORIGIN, origin)
+                .header(ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(ACCESS_CONTROL_REQUEST_HEADERS, "x-custom")
+                .send()
+        };
+
+        let response = preflight("https://example.com").await.unwrap();
+        let headers = response.headers();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://example.com");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], "GET, POST");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_HEADERS], "x-custom");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[ACCESS_CONTROL_MAX_AGE], "600");
+
+        let response = preflight("https://evil.io").await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid CORS configuration")]
+    fn test_credentials_with_any_origin() {
+        let handler = handler_fn(|_| async { Ok::<_, Error>("hello") });
+
+        let _ = handler.with(
+            Cors::new()
+                .allow_origin("https://example.com")
+                .allow_origin("*")
+                .allow_credentials(true),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_origin_regex() {
+        let addr = serve(Cors::new().allow_origin_regex(r"https://(\w+\.)?example\.com")).await;
+
+        assert_eq!(
+            allow_origin(addr, "https://example.com").await.unwrap(),
+            "https://example.com"
+        );
+        assert_eq!(
+            allow_origin(addr, "https://api.example.com").await.unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(
+            allow_origin(addr, "https://example.com.evil.io").await,
+            None
+        );
+        assert_eq!(
+            allow_origin(addr, "http://evil.io/https://example.com").await,
+            None
+        );
+
+        let addr = serve(Cors::new().allow_origin("https://*.example.com")).await;
+
+        assert_eq!(
+            allow_origin(addr, "https://api.example.com").await.unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(allow_origin(addr, "https://example.com").await, None);
+    }
+}
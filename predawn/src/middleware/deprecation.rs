@@ -0,0 +1,87 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use predawn_core::{error::Error, request::Request, response::Response};
+
+use super::Middleware;
+use crate::handler::Handler;
+
+pub(crate) const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+pub(crate) const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// Adds the [`Deprecation`](https://www.rfc-editor.org/rfc/rfc9745) and
+/// [`Sunset`](https://www.rfc-editor.org/rfc/rfc8594) headers to every response, including error responses.
+///
+/// Endpoints marked with `#[endpoint(deprecated = "<HTTP-date>")]` are wrapped in it automatically,
+/// and the date is sent as `@<seconds since the epoch>`. A bare `#[endpoint(deprecated)]` only marks
+/// the operation as deprecated in OpenAPI, without sending or documenting any header.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    deprecation: HeaderValue,
+    sunset: Option<HeaderValue>,
+}
+
+impl Deprecation {
+    /// Sends the date of the deprecation as a structured field date, like `Deprecation: @1688169599`.
+    pub fn new(date: SystemTime) -> Self {
+        let secs = date
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self {
+            deprecation: HeaderValue::from_str(&format!("@{secs}"))
+                .expect("unreachable: a number is a valid header value"),
+            sunset: None,
+        }
+    }
+
+    /// Sends the date after which the endpoint is expected to stop responding, as an HTTP-date.
+    pub fn sunset(mut self, date: SystemTime) -> Self {
+        self.sunset = Some(
+            HeaderValue::from_str(&httpdate::fmt_http_date(date))
+                .expect("unreachable: an HTTP-date is a valid header value"),
+        );
+        self
+    }
+
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(DEPRECATION, self.deprecation.clone());
+
+        if let Some(sunset) = &self.sunset {
+            headers.insert(SUNSET, sunset.clone());
+        }
+    }
+}
+
+impl<H: Handler> Middleware<H> for Deprecation {
+    type Output = DeprecationHandler<H>;
+
+    fn transform(self, input: H) -> Self::Output {
+        DeprecationHandler {
+            deprecation: self,
+            inner: input,
+        }
+    }
+}
+
+pub struct DeprecationHandler<H> {
+    deprecation: Deprecation,
+    inner: H,
+}
+
+impl<H: Handler> Handler for DeprecationHandler<H> {
+    async fn call(&self, req: Request) -> Result<Response, Error> {
+        match self.inner.call(req).await {
+            Ok(mut response) => {
+                self.deprecation.insert_headers(response.headers_mut());
+                Ok(response)
+            }
+            Err(mut e) => {
+                self.deprecation
+                    .insert_headers(e.response_mut().headers_mut());
+                Err(e)
+            }
+        }
+    }
+}
@@ -1,14 +1,29 @@
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[cfg(feature = "compression")]
+mod compression;
+mod cors;
+mod deprecation;
 mod limit;
+mod timeout;
 #[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
 #[cfg(feature = "tower-compat")]
 mod tower_compat;
 mod tracing;
 
+#[cfg(feature = "compression")]
+pub(crate) use self::compression::document_content_encoding;
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[cfg(feature = "compression")]
+pub use self::compression::{Compression, CompressionHandler, Encoding};
+pub(crate) use self::deprecation::{DEPRECATION, SUNSET};
 #[cfg_attr(docsrs, doc(cfg(feature = "tower-compat")))]
 #[cfg(feature = "tower-compat")]
 pub use self::tower_compat::TowerLayerCompatExt;
 pub use self::{
+    cors::{Cors, CorsHandler},
+    deprecation::{Deprecation, DeprecationHandler},
     limit::{RequestBodyLimit, RequestBodyLimitHandler},
+    timeout::{Timeout, TimeoutHandler},
     tracing::{Tracing, TracingHandler},
 };
 use crate::handler::Handler;
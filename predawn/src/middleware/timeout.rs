@@ -0,0 +1,204 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use predawn_core::{error::Error, request::Request, response::Response};
+use tokio::time::Instant;
+
+use super::Middleware;
+use crate::{handler::Handler, response_error::TimeoutSnafu};
+
+/// Answers requests not handled within `timeout` with `504 Gateway Timeout`, dropping the handler.
+///
+/// A `Timeout` inside another one, such as one added to an endpoint by
+/// `#[endpoint(timeout = "30s")]` inside the one configured by `server.request_timeout`,
+/// overrides the outer timeout, so it can be longer.
+///
+/// `#[endpoint(timeout = ..)]` also documents the `504` response of the endpoint, which a
+/// `Timeout` added by `#[endpoint(middleware = ..)]` does not.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    timeout: Duration,
+}
+
+impl Timeout {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<H: Handler> Middleware<H> for Timeout {
+    type Output = TimeoutHandler<H>;
+
+    fn transform(self, input: H) -> Self::Output {
+        TimeoutHandler {
+            timeout: self.timeout,
+            inner: input,
+        }
+    }
+}
+
+pub struct TimeoutHandler<H> {
+    timeout: Duration,
+    inner: H,
+}
+
+/// The deadline of the outermost timeout and the timeout it was set from, moved by the inner ones.
+#[derive(Clone)]
+struct Deadline(Arc<Mutex<(Instant, Duration)>>);
+
+impl<H: Handler> Handler for TimeoutHandler<H> {
+    async fn call(&self, mut req: Request) -> Result<Response, Error> {
+        let timeout = self.timeout;
+        let deadline = Instant::now() + timeout;
+
+        let shared = match req.head.extensions.get::<Deadline>() {
+            Some(outer) => {
+                *outer.0.lock().unwrap() = (deadline, timeout);
+                outer.clone()
+            }
+            None => {
+                let shared = Deadline(Arc::new(Mutex::new((deadline, timeout))));
+                req.head.extensions.insert(shared.clone());
+                shared
+            }
+        };
+
+        let call = self.inner.call(req);
+        let mut call = std::pin::pin!(call);
+
+        loop {
+            let (deadline, _) = *shared.0.lock().unwrap();
+
+            tokio::select! {
+                result = call.as_mut() => return result,
+                _ = tokio::time::sleep_until(deadline) => {
+                    // an inner timeout may have moved the deadline meanwhile
+                    let (deadline, timeout) = *shared.0.lock().unwrap();
+
+                    if Instant::now() >= deadline {
+                        return Err(TimeoutSnafu { timeout }.build().into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, time::Duration};
+
+    use bytes::Bytes;
+    use http::StatusCode;
+    use predawn_core::{
+        error::Error,
+        from_request::FromRequest,
+        request::{BodyTimeout, MinThroughput, Request},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use super::Timeout;
+    use crate::{
+        handler::{Handler, HandlerExt, handler_fn},
+        server::{Server, bind},
+    };
+
+    async fn serve<H: Handler>(handler: H) -> SocketAddr {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(Server::new(listener).run(handler));
+
+        addr
+    }
+
+    async fn status(addr: SocketAddr) -> StatusCode {
+        reqwest::get(format!("http://{addr}/"))
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let slow = || {
+            handler_fn(|_| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok::<_, Error>("done")
+            })
+        };
+
+        let addr = serve(slow().with(Timeout::new(Duration::from_millis(50)))).await;
+        assert_eq!(status(addr).await, StatusCode::GATEWAY_TIMEOUT);
+
+        // the inner timeout overrides the outer one
+        let handler = slow()
+            .with(Timeout::new(Duration::from_secs(5)))
+            .with(Timeout::new(Duration::from_millis(50)));
+
+        let addr = serve(handler).await;
+        assert_eq!(status(addr).await, StatusCode::OK);
+
+        // the outer timeout reports the timeout that moved its deadline
+        let handler = handler_fn(|_| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, Error>("done")
+        })
+        .with(Timeout::new(Duration::from_millis(150)))
+        .with(Timeout::new(Duration::from_millis(50)));
+
+        let addr = serve(handler).await;
+        let response = reqwest::get(format!("http://{addr}/")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(response.text().await.unwrap().contains("150ms"));
+    }
+
+    async fn slow_upload(timeout: BodyTimeout) -> Vec<u8> {
+        let handler = handler_fn(|req: Request| async move {
+            let (mut head, body) = req.split();
+            let bytes = Bytes::from_request(&mut head, body).await?;
+            Ok::<_, Error>(bytes)
+        })
+        .before(move |mut req| async move {
+            *req.body_timeout() = timeout;
+            Ok(req)
+        });
+
+        let addr = serve(handler).await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"POST / HTTP/1.1\r\nhost: localhost\r\ncontent-length: 10\r\n\r\nhello")
+            .await
+            .unwrap();
+
+        let mut buf = [0; 1024];
+        let n = stream.read(&mut buf).await.unwrap();
+        buf[..n].to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_body_timeout() {
+        let response = slow_upload(BodyTimeout {
+            read_timeout: Some(Duration::from_millis(100)),
+            min_throughput: None,
+        })
+        .await;
+        assert!(response.starts_with(b"HTTP/1.1 408"));
+
+        let response = slow_upload(BodyTimeout {
+            read_timeout: None,
+            min_throughput: Some(MinThroughput {
+                bytes_per_second: 1024,
+                grace_period: Duration::from_millis(100),
+            }),
+        })
+        .await;
+        assert!(response.starts_with(b"HTTP/1.1 408"));
+    }
+}
@@ -0,0 +1,949 @@
+use std::{collections::BTreeSet, fmt, sync::LazyLock};
+
+use serde_json::{Map, Value};
+
+use super::OpenAPI;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// A difference between two OpenAPI documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Where the change is, e.g. `GET /users/{id} 200 application/json name`.
+    pub location: String,
+    pub kind: ChangeKind,
+    /// Whether clients written against the old document may fail against the new one.
+    pub breaking: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    PathAdded,
+    PathRemoved,
+    OperationAdded,
+    OperationRemoved,
+    ParameterAdded { required: bool },
+    ParameterRemoved,
+    ParameterBecameRequired,
+    RequestBodyAdded { required: bool },
+    RequestBodyRemoved,
+    RequestBodyBecameRequired,
+    ResponseAdded,
+    ResponseRemoved,
+    MediaTypeAdded,
+    MediaTypeRemoved,
+    TypeChanged { old: String, new: String },
+    NullableChanged { nullable: bool },
+    EnumValuesAdded { values: Vec<Value> },
+    EnumValuesRemoved { values: Vec<Value> },
+    VariantsAdded { count: usize },
+    VariantsRemoved { count: usize },
+    AllOfSchemasAdded { count: usize },
+    AllOfSchemasRemoved { count: usize },
+    PropertyAdded { required: bool },
+    PropertyRemoved,
+    PropertyBecameRequired,
+    PropertyBecameOptional,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let required = |required: &bool| if *required { "required" } else { "optional" };
+        let values = |values: &[Value]| {
+            values
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            ChangeKind::PathAdded => f.write_str("path added"),
+            ChangeKind::PathRemoved => f.write_str("path removed"),
+            ChangeKind::OperationAdded => f.write_str("operation added"),
+            ChangeKind::OperationRemoved => f.write_str("operation removed"),
+            ChangeKind::ParameterAdded { required: r } => {
+                write!(f, "{} parameter added", required(r))
+            }
+            ChangeKind::ParameterRemoved => f.write_str("parameter removed"),
+            ChangeKind::ParameterBecameRequired => f.write_str("parameter became required"),
+            ChangeKind::RequestBodyAdded { required: r } => {
+                write!(f, "{} request body added", required(r))
+            }
+            ChangeKind::RequestBodyRemoved => f.write_str("request body removed"),
+            ChangeKind::RequestBodyBecameRequired => f.write_str("request body became required"),
+            ChangeKind::ResponseAdded => f.write_str("response added"),
+            ChangeKind::ResponseRemoved => f.write_str("response removed"),
+            ChangeKind::MediaTypeAdded => f.write_str("media type added"),
+            ChangeKind::MediaTypeRemoved => f.write_str("media type removed"),
+            ChangeKind::TypeChanged { old, new } => {
+                write!(f, "type changed from `{old}` to `{new}`")
+            }
+            ChangeKind::NullableChanged { nullable: true } => f.write_str("became nullable"),
+            ChangeKind::NullableChanged { nullable: false } => f.write_str("became non-nullable"),
+            ChangeKind::EnumValuesAdded { values: v } => {
+                write!(f, "enum values added: {}", values(v))
+            }
+            ChangeKind::EnumValuesRemoved { values: v } => {
+                write!(f, "enum values removed: {}", values(v))
+            }
+            ChangeKind::VariantsAdded { count } => write!(f, "{count} variants added"),
+            ChangeKind::VariantsRemoved { count } => write!(f, "{count} variants removed"),
+            ChangeKind::AllOfSchemasAdded { count } => {
+                write!(f, "{count} `allOf` schemas added")
+            }
+            ChangeKind::AllOfSchemasRemoved { count } => {
+                write!(f, "{count} `allOf` schemas removed")
+            }
+            ChangeKind::PropertyAdded { required: r } => {
+                write!(f, "{} property added", required(r))
+            }
+            ChangeKind::PropertyRemoved => f.write_str("property removed"),
+            ChangeKind::PropertyBecameRequired => f.write_str("property became required"),
+            ChangeKind::PropertyBecameOptional => f.write_str("property became optional"),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compatibility = if self.breaking {
+            "breaking"
+        } else {
+            "non-breaking"
+        };
+
+        write!(f, "[{compatibility}] {}: {}", self.location, self.kind)
+    }
+}
+
+/// Compares two OpenAPI documents, classifying every change as breaking or not.
+pub fn diff(old: &OpenAPI, new: &OpenAPI) -> Vec<Change> {
+    let old = serde_json::to_value(old).expect("failed to serialize `OpenAPI` to JSON");
+    let new = serde_json::to_value(new).expect("failed to serialize `OpenAPI` to JSON");
+
+    diff_values(&old, &new)
+}
+
+/// Like [`diff`], but for documents in their JSON form, either OpenAPI 3.0 or 3.1.
+pub fn diff_values(old: &Value, new: &Value) -> Vec<Change> {
+    let mut differ = Differ {
+        old_root: old,
+        new_root: new,
+        changes: Vec::new(),
+        in_progress: Vec::new(),
+    };
+
+    differ.paths();
+    differ.changes
+}
+
+/// Whether a schema is sent by clients or received by them, which decides if narrowing or widening
+/// it is breaking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Request,
+    Response,
+}
+
+struct Differ<'a> {
+    old_root: &'a Value,
+    new_root: &'a Value,
+    changes: Vec<Change>,
+    /// The references being compared, so that recursive schemas terminate.
+    in_progress: Vec<(&'a str, &'a str)>,
+}
+
+fn object(value: Option<&Value>) -> Option<&Map<String, Value>> {
+    value.and_then(Value::as_object)
+}
+
+/// The members of an object, none if it is absent.
+fn members(value: Option<&Value>) -> &Map<String, Value> {
+    static EMPTY: LazyLock<Map<String, Value>> = LazyLock::new(Map::new);
+
+    object(value).unwrap_or(&EMPTY)
+}
+
+fn is_required(value: &Value) -> bool {
+    value.get("required").and_then(Value::as_bool) == Some(true)
+}
+
+fn join(location: &str, segment: impl fmt::Display) -> String {
+    format!("{location} {segment}")
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, location: impl Into<String>, kind: ChangeKind, breaking: bool) {
+        self.changes.push(Change {
+            location: location.into(),
+            kind,
+            breaking,
+        });
+    }
+
+    /// Follows local references like `#/components/schemas/Pet`.
+    fn resolve(root: &'a Value, mut value: &'a Value) -> (&'a Value, Option<&'a str>) {
+        let mut reference = None;
+
+        // bounded, in case of a reference cycle
+        for _ in 0..32 {
+            let Some(pointer) = value.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+
+            let Some(target) = pointer.strip_prefix('#').and_then(|p| root.pointer(p)) else {
+                break;
+            };
+
+            reference = Some(pointer);
+            value = target;
+        }
+
+        (value, reference)
+    }
+
+    fn paths(&mut self) {
+        let old_paths = members(self.old_root.get("paths"));
+        let new_paths = members(self.new_root.get("paths"));
+
+        for (path, old_item) in old_paths {
+            match new_paths.get(path) {
+                Some(new_item) => self.path_item(path, old_item, new_item),
+                None => self.push(path, ChangeKind::PathRemoved, true),
+            }
+        }
+
+        for path in new_paths
+            .keys()
+            .filter(|path| !old_paths.contains_key(*path))
+        {
+            self.push(path, ChangeKind::PathAdded, false);
+        }
+    }
+
+    fn path_item(&mut self, path: &str, old_item: &'a Value, new_item: &'a Value) {
+        for method in METHODS {
+            let location = format!("{} {path}", method.to_ascii_uppercase());
+
+            match (old_item.get(method), new_item.get(method)) {
+                (Some(old), Some(new)) => self.operation(&location, old_item, old, new_item, new),
+                (Some(_), None) => self.push(location, ChangeKind::OperationRemoved, true),
+                (None, Some(_)) => self.push(location, ChangeKind::OperationAdded, false),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn parameters(
+        root: &'a Value,
+        item: &'a Value,
+        operation: &'a Value,
+    ) -> Vec<(String, &'a Value)> {
+        let mut parameters = Vec::<(String, &Value)>::new();
+
+        // operation parameters override the path item ones
+        for value in [item, operation]
+            .into_iter()
+            .filter_map(|value| value.get("parameters").and_then(Value::as_array))
+            .flatten()
+        {
+            let (parameter, _) = Self::resolve(root, value);
+
+            let key = format!(
+                "{}.{}",
+                parameter
+                    .get("in")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+                parameter
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+            );
+
+            match parameters.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, exist)) => *exist = parameter,
+                None => parameters.push((key, parameter)),
+            }
+        }
+
+        parameters
+    }
+
+    fn operation(
+        &mut self,
+        location: &str,
+        old_item: &'a Value,
+        old: &'a Value,
+        new_item: &'a Value,
+        new: &'a Value,
+    ) {
+        let old_parameters = Self::parameters(self.old_root, old_item, old);
+        let new_parameters = Self::parameters(self.new_root, new_item, new);
+
+        for (key, old_parameter) in &old_parameters {
+            let location = join(location, format_args!("parameter {key}"));
+
+            match new_parameters.iter().find(|(k, _)| k == key) {
+                Some((_, new_parameter)) => {
+                    if !is_required(old_parameter) && is_required(new_parameter) {
+                        self.push(location.clone(), ChangeKind::ParameterBecameRequired, true);
+                    }
+
+                    if let (Some(old_schema), Some(new_schema)) =
+                        (old_parameter.get("schema"), new_parameter.get("schema"))
+                    {
+                        self.schema(&location, old_schema, new_schema, Direction::Request);
+                    }
+                }
+                None => self.push(location, ChangeKind::ParameterRemoved, false),
+            }
+        }
+
+        for (key, new_parameter) in &new_parameters {
+            if !old_parameters.iter().any(|(k, _)| k == key) {
+                let required = is_required(new_parameter);
+
+                self.push(
+                    join(location, format_args!("parameter {key}")),
+                    ChangeKind::ParameterAdded { required },
+                    required,
+                );
+            }
+        }
+
+        self.request_body(location, old.get("requestBody"), new.get("requestBody"));
+        self.responses(location, old.get("responses"), new.get("responses"));
+    }
+
+    fn request_body(&mut self, location: &str, old: Option<&'a Value>, new: Option<&'a Value>) {
+        let location = join(location, "request body");
+
+        let old = old.map(|old| Self::resolve(self.old_root, old).0);
+        let new = new.map(|new| Self::resolve(self.new_root, new).0);
+
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                if !is_required(old) && is_required(new) {
+                    self.push(
+                        location.clone(),
+                        ChangeKind::RequestBodyBecameRequired,
+                        true,
+                    );
+                }
+
+                self.content(&location, old, new, Direction::Request);
+            }
+            (Some(_), None) => self.push(location, ChangeKind::RequestBodyRemoved, false),
+            (None, Some(new)) => {
+                let required = is_required(new);
+                self.push(
+                    location,
+                    ChangeKind::RequestBodyAdded { required },
+                    required,
+                );
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn responses(&mut self, location: &str, old: Option<&'a Value>, new: Option<&'a Value>) {
+        let (Some(old), Some(new)) = (object(old), object(new)) else {
+            return;
+        };
+
+        for (status, old_response) in old {
+            let location = join(location, status);
+
+            match new.get(status) {
+                Some(new_response) => {
+                    let (old_response, _) = Self::resolve(self.old_root, old_response);
+                    let (new_response, _) = Self::resolve(self.new_root, new_response);
+
+                    self.content(&location, old_response, new_response, Direction::Response);
+                }
+                None => self.push(location, ChangeKind::ResponseRemoved, true),
+            }
+        }
+
+        for status in new.keys().filter(|status| !old.contains_key(*status)) {
+            self.push(join(location, status), ChangeKind::ResponseAdded, false);
+        }
+    }
+
+    fn content(&mut self, location: &str, old: &'a Value, new: &'a Value, direction: Direction) {
+        let old_content = members(old.get("content"));
+        let new_content = members(new.get("content"));
+
+        for (media_type, old_media_type) in old_content {
+            let location = join(location, media_type);
+
+            match new_content.get(media_type) {
+                Some(new_media_type) => {
+                    if let (Some(old_schema), Some(new_schema)) =
+                        (old_media_type.get("schema"), new_media_type.get("schema"))
+                    {
+                        self.schema(&location, old_schema, new_schema, direction);
+                    }
+                }
+                None => self.push(location, ChangeKind::MediaTypeRemoved, true),
+            }
+        }
+
+        for media_type in new_content
+            .keys()
+            .filter(|media_type| !old_content.contains_key(*media_type))
+        {
+            self.push(
+                join(location, media_type),
+                ChangeKind::MediaTypeAdded,
+                false,
+            );
+        }
+    }
+
+    fn schema(&mut self, location: &str, old: &'a Value, new: &'a Value, direction: Direction) {
+        let (old, old_ref) = Self::resolve(self.old_root, old);
+        let (new, new_ref) = Self::resolve(self.new_root, new);
+
+        let Some((old_ref, new_ref)) = old_ref.zip(new_ref) else {
+            return self.resolved_schema(location, old, new, direction);
+        };
+
+        if self.in_progress.contains(&(old_ref, new_ref)) {
+            return;
+        }
+
+        self.in_progress.push((old_ref, new_ref));
+        self.resolved_schema(location, old, new, direction);
+        self.in_progress.pop();
+    }
+
+    fn resolved_schema(
+        &mut self,
+        location: &str,
+        old: &'a Value,
+        new: &'a Value,
+        direction: Direction,
+    ) {
+        // narrowing what is sent or widening what is received is breaking
+        let narrowing_breaks = direction == Direction::Request;
+
+        let (old_type, old_nullable) = schema_type(old);
+        let (new_type, new_nullable) = schema_type(new);
+
+        if let (Some(old_type), Some(new_type)) = (&old_type, &new_type)
+            && old_type != new_type
+            && !(old_type == "integer" && new_type == "number" && narrowing_breaks)
+        {
+            self.push(
+                location,
+                ChangeKind::TypeChanged {
+                    old: old_type.clone(),
+                    new: new_type.clone(),
+                },
+                true,
+            );
+            return;
+        }
+
+        if old_nullable != new_nullable {
+            self.push(
+                location,
+                ChangeKind::NullableChanged {
+                    nullable: new_nullable,
+                },
+                new_nullable != narrowing_breaks,
+            );
+        }
+
+        self.enumeration(location, old, new, narrowing_breaks);
+        self.variants(location, old, new, direction);
+        self.properties(location, old, new, direction);
+
+        if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+            self.schema(&format!("{location}[]"), old_items, new_items, direction);
+        }
+
+        if let (Some(old_values @ Value::Object(_)), Some(new_values @ Value::Object(_))) = (
+            old.get("additionalProperties"),
+            new.get("additionalProperties"),
+        ) {
+            self.schema(
+                &format!("{location}{{}}"),
+                old_values,
+                new_values,
+                direction,
+            );
+        }
+    }
+
+    fn enumeration(&mut self, location: &str, old: &Value, new: &Value, narrowing_breaks: bool) {
+        let (Some(old_values), Some(new_values)) = (enum_values(old), enum_values(new)) else {
+            return;
+        };
+
+        let removed = old_values
+            .iter()
+            .filter(|value| !new_values.contains(value))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let added = new_values
+            .iter()
+            .filter(|value| !old_values.contains(value))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !removed.is_empty() {
+            self.push(
+                location,
+                ChangeKind::EnumValuesRemoved { values: removed },
+                narrowing_breaks,
+            );
+        }
+
+        if !added.is_empty() {
+            self.push(
+                location,
+                ChangeKind::EnumValuesAdded { values: added },
+                !narrowing_breaks,
+            );
+        }
+    }
+
+    fn variants(&mut self, location: &str, old: &'a Value, new: &'a Value, direction: Direction) {
+        let narrowing_breaks = direction == Direction::Request;
+
+        for key in ["oneOf", "anyOf", "allOf"] {
+            let (Some(old_variants), Some(new_variants)) = (
+                old.get(key).and_then(Value::as_array),
+                new.get(key).and_then(Value::as_array),
+            ) else {
+                continue;
+            };
+
+            // a `{ "type": "null" }` variant is how 3.1 spells `nullable`
+            let old_variants = old_variants.iter().filter(|v| !is_null_schema(v));
+            let new_variants = new_variants.iter().filter(|v| !is_null_schema(v));
+
+            let mut old_variants = old_variants.collect::<Vec<_>>();
+            let mut new_variants = new_variants.collect::<Vec<_>>();
+
+            // variants are matched by their `$ref` or their constant value, the rest by position
+            old_variants.retain(|old_variant| {
+                match new_variants
+                    .iter()
+                    .position(|new_variant| same_variant(old_variant, new_variant))
+                {
+                    Some(index) => {
+                        let new_variant = new_variants.remove(index);
+                        self.schema(location, old_variant, new_variant, direction);
+                        false
+                    }
+                    None => true,
+                }
+            });
+
+            let common = old_variants.len().min(new_variants.len());
+
+            for (old_variant, new_variant) in old_variants.iter().zip(&new_variants) {
+                self.schema(location, old_variant, new_variant, direction);
+            }
+
+            let removed = old_variants.len() - common;
+            let added = new_variants.len() - common;
+
+            // a value must match every `allOf` schema, so adding one narrows the schema,
+            // whereas adding a `oneOf` or `anyOf` variant widens it
+            let (removed, added, added_narrows) = if key == "allOf" {
+                (
+                    ChangeKind::AllOfSchemasRemoved { count: removed },
+                    ChangeKind::AllOfSchemasAdded { count: added },
+                    true,
+                )
+            } else {
+                (
+                    ChangeKind::VariantsRemoved { count: removed },
+                    ChangeKind::VariantsAdded { count: added },
+                    false,
+                )
+            };
+
+            if old_variants.len() > common {
+                self.push(location, removed, narrowing_breaks != added_narrows);
+            }
+
+            if new_variants.len() > common {
+                self.push(location, added, narrowing_breaks == added_narrows);
+            }
+        }
+    }
+
+    fn properties(&mut self, location: &str, old: &'a Value, new: &'a Value, direction: Direction) {
+        let (Some(old_properties), Some(new_properties)) =
+            (object(old.get("properties")), object(new.get("properties")))
+        else {
+            return;
+        };
+
+        let old_required = required_properties(old);
+        let new_required = required_properties(new);
+
+        let path = |name: &str| {
+            if location.ends_with(']') || location.ends_with('}') {
+                format!("{location}.{name}")
+            } else {
+                join(location, name)
+            }
+        };
+
+        for (name, old_property) in old_properties {
+            let location = path(name);
+
+            let Some(new_property) = new_properties.get(name) else {
+                self.push(location, ChangeKind::PropertyRemoved, true);
+                continue;
+            };
+
+            match (
+                old_required.contains(name.as_str()),
+                new_required.contains(name.as_str()),
+            ) {
+                (false, true) => self.push(
+                    location.clone(),
+                    ChangeKind::PropertyBecameRequired,
+                    direction == Direction::Request,
+                ),
+                (true, false) => self.push(
+                    location.clone(),
+                    ChangeKind::PropertyBecameOptional,
+                    direction == Direction::Response,
+                ),
+                _ => {}
+            }
+
+            self.schema(&location, old_property, new_property, direction);
+        }
+
+        for name in new_properties
+            .keys()
+            .filter(|name| !old_properties.contains_key(*name))
+        {
+            let required = new_required.contains(name.as_str());
+
+            self.push(
+                path(name),
+                ChangeKind::PropertyAdded { required },
+                required && direction == Direction::Request,
+            );
+        }
+    }
+}
+
+/// The type of a schema and whether it is nullable, in either the 3.0 or the 3.1 form.
+fn schema_type(schema: &Value) -> (Option<String>, bool) {
+    let nullable = schema.get("nullable").and_then(Value::as_bool) == Some(true);
+
+    match schema.get("type") {
+        Some(Value::String(ty)) => (Some(ty.clone()), nullable),
+        Some(Value::Array(types)) => {
+            let nullable = nullable || types.iter().any(|ty| ty == "null");
+            let mut types = types
+                .iter()
+                .filter_map(Value::as_str)
+                .filter(|ty| *ty != "null");
+
+            match (types.next(), types.next()) {
+                (Some(ty), None) => (Some(ty.to_string()), nullable),
+                _ => (None, nullable),
+            }
+        }
+        _ => {
+            let nullable = nullable
+                || ["oneOf", "anyOf"].iter().any(|key| {
+                    schema
+                        .get(*key)
+                        .and_then(Value::as_array)
+                        .is_some_and(|variants| variants.iter().any(is_null_schema))
+                });
+
+            (None, nullable)
+        }
+    }
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn enum_values(schema: &Value) -> Option<Vec<Value>> {
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return Some(values.iter().filter(|v| !v.is_null()).cloned().collect());
+    }
+
+    schema.get("const").map(|value| vec![value.clone()])
+}
+
+fn same_variant(old: &Value, new: &Value) -> bool {
+    if let (Some(old), Some(new)) = (old.get("$ref"), new.get("$ref")) {
+        return old == new;
+    }
+
+    matches!((enum_values(old), enum_values(new)), (Some(old), Some(new)) if old == new)
+}
+
+fn required_properties(schema: &Value) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{ChangeKind, diff_values};
+
+    #[test]
+    fn test_diff_values() {
+        let old = json!({
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "parameters": [
+                            { "in": "query", "name": "limit", "schema": { "type": "integer" } }
+                        ],
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "array",
+                                            "items": { "$ref": "#/components/schemas/Pet" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "post": {
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" }
+                                }
+                            }
+                        },
+                        "responses": { "201": {} }
+                    },
+                    "delete": { "responses": { "204": {} } }
+                },
+                "/owners": { "get": { "responses": { "200": {} } } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "kind": { "type": "string", "enum": ["cat", "dog"] },
+                            "age": { "type": "integer" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let new = json!({
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "parameters": [
+                            {
+                                "in": "query",
+                                "name": "limit",
+                                "required": true,
+                                "schema": { "type": "integer" }
+                            }
+                        ],
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "array",
+                                            "items": { "$ref": "#/components/schemas/Pet" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "post": {
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" }
+                                }
+                            }
+                        },
+                        "responses": { "201": {} }
+                    }
+                },
+                "/stores": { "get": { "responses": { "200": {} } } }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "kind": { "type": "string", "enum": ["cat"] },
+                            "color": { "type": "string" }
+                        }
+                    }
+                }
+            }
+        });
+
+        let changes = diff_values(&old, &new)
+            .into_iter()
+            .map(|change| (change.location, change.kind, change.breaking))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            [
+                ("/owners".to_string(), ChangeKind::PathRemoved, true),
+                (
+                    "GET /pets parameter query.limit".to_string(),
+                    ChangeKind::ParameterBecameRequired,
+                    true
+                ),
+                (
+                    "GET /pets 200 application/json[].age".to_string(),
+                    ChangeKind::PropertyRemoved,
+                    true
+                ),
+                (
+                    "GET /pets 200 application/json[].kind".to_string(),
+                    ChangeKind::EnumValuesRemoved {
+                        values: vec![json!("dog")]
+                    },
+                    false
+                ),
+                (
+                    "GET /pets 200 application/json[].color".to_string(),
+                    ChangeKind::PropertyAdded { required: false },
+                    false
+                ),
+                (
+                    "POST /pets request body application/json age".to_string(),
+                    ChangeKind::PropertyRemoved,
+                    true
+                ),
+                (
+                    "POST /pets request body application/json kind".to_string(),
+                    ChangeKind::EnumValuesRemoved {
+                        values: vec![json!("dog")]
+                    },
+                    true
+                ),
+                (
+                    "POST /pets request body application/json color".to_string(),
+                    ChangeKind::PropertyAdded { required: false },
+                    false
+                ),
+                (
+                    "DELETE /pets".to_string(),
+                    ChangeKind::OperationRemoved,
+                    true
+                ),
+                ("/stores".to_string(), ChangeKind::PathAdded, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_all_of() {
+        let document = |all_of: Value, name: Value| {
+            json!({
+                "paths": {
+                    "/pets": {
+                        "post": {
+                            "requestBody": {
+                                "content": { "application/json": { "schema": { "allOf": all_of } } }
+                            },
+                            "responses": {
+                                "200": {
+                                    "content": { "application/json": { "schema": { "allOf": all_of } } }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Named": { "type": "object", "properties": { "name": name } },
+                        "Aged": { "type": "object", "properties": { "age": { "type": "integer" } } }
+                    }
+                }
+            })
+        };
+
+        let named = json!({ "$ref": "#/components/schemas/Named" });
+        let aged = json!({ "$ref": "#/components/schemas/Aged" });
+
+        let old = document(json!([named]), json!({ "type": "string" }));
+        let new = document(json!([aged, named]), json!({ "type": "integer" }));
+
+        let changes = diff_values(&old, &new)
+            .into_iter()
+            .map(|change| (change.location, change.kind, change.breaking))
+            .collect::<Vec<_>>();
+
+        let type_changed = ChangeKind::TypeChanged {
+            old: "string".to_string(),
+            new: "integer".to_string(),
+        };
+
+        assert_eq!(
+            changes,
+            [
+                (
+                    "POST /pets request body application/json name".to_string(),
+                    type_changed.clone(),
+                    true
+                ),
+                (
+                    "POST /pets request body application/json".to_string(),
+                    ChangeKind::AllOfSchemasAdded { count: 1 },
+                    true
+                ),
+                (
+                    "POST /pets 200 application/json name".to_string(),
+                    type_changed,
+                    true
+                ),
+                (
+                    "POST /pets 200 application/json".to_string(),
+                    ChangeKind::AllOfSchemasAdded { count: 1 },
+                    false
+                ),
+            ]
+        );
+    }
+}
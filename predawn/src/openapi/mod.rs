@@ -0,0 +1,164 @@
+mod diff;
+mod v3_1;
+mod yaml;
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use indexmap::IndexMap;
+pub use predawn_core::openapi::*;
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    diff::{Change, ChangeKind, diff, diff_values},
+    v3_1::convert_to_v3_1,
+    yaml::to_yaml,
+};
+use crate::{
+    ToSchema,
+    app::{Hooks, create_app},
+    config::openapi::OpenAPIConfig,
+    environment::Environment,
+    middleware::{DEPRECATION, SUNSET},
+};
+
+/// The OpenAPI version of the served document.
+///
+/// The document is always assembled as OpenAPI 3.0, and converted when it is rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpenAPIVersion {
+    #[default]
+    #[serde(rename = "3.0")]
+    V3_0,
+    #[serde(rename = "3.1")]
+    V3_1,
+}
+
+impl OpenAPIVersion {
+    pub fn render(self, api: &OpenAPI) -> serde_json::Value {
+        match self {
+            OpenAPIVersion::V3_0 => {
+                serde_json::to_value(api).expect("failed to serialize `OpenAPI` to JSON")
+            }
+            OpenAPIVersion::V3_1 => convert_to_v3_1(api),
+        }
+    }
+}
+
+/// Writes the OpenAPI document of the app to `path` without starting the server, so that it can be
+/// committed and diffed in code review.
+///
+/// The document is written as YAML if `path` ends with `.yaml` or `.yml`, and as pretty-printed
+/// JSON otherwise, in the version set by `openapi.version`.
+pub async fn export<H: Hooks>(env: Environment, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+
+    let (mut cx, _) = create_app::<H>(env).await;
+
+    let version = cx.resolve::<OpenAPIConfig>().version;
+    let api = version.render(&cx.resolve::<OpenAPI>());
+
+    let is_yaml = path
+        .extension()
+        .is_some_and(|extension| extension == "yaml" || extension == "yml");
+
+    let document = if is_yaml {
+        to_yaml(&api)
+    } else {
+        let mut json = serde_json::to_string_pretty(&api)?;
+        json.push('\n');
+        json
+    };
+
+    tokio::fs::write(path, document).await
+}
+
+#[doc(hidden)]
+pub fn transform_parameters(parameters: Vec<Parameter>) -> Vec<ReferenceOr<Parameter>> {
+    parameters.into_iter().map(ReferenceOr::Item).collect()
+}
+
+#[doc(hidden)]
+pub fn transform_request_body(
+    request_body: Option<RequestBody>,
+) -> Option<ReferenceOr<RequestBody>> {
+    request_body.map(ReferenceOr::Item)
+}
+
+#[doc(hidden)]
+pub fn transform_responses(
+    responses: BTreeMap<http::StatusCode, Response>,
+) -> IndexMap<StatusCode, ReferenceOr<Response>> {
+    responses
+        .into_iter()
+        .map(|(status, response)| {
+            (
+                StatusCode::Code(status.as_u16()),
+                ReferenceOr::Item(response),
+            )
+        })
+        .collect()
+}
+
+/// Adds the example to every media type of `content`.
+#[doc(hidden)]
+pub fn insert_example(
+    content: &mut IndexMap<String, MediaType>,
+    name: &str,
+    example: ReferenceOr<Example>,
+) {
+    content.values_mut().for_each(|media_type| {
+        media_type
+            .examples
+            .insert(name.to_string(), example.clone());
+    });
+}
+
+/// Marks the operation as deprecated and documents the `Deprecation` and `Sunset` headers on
+/// every response of it.
+#[doc(hidden)]
+pub fn deprecate_operation(
+    operation: &mut Operation,
+    sunset: bool,
+    schemas: &mut BTreeMap<String, Schema>,
+    schemas_in_progress: &mut Vec<String>,
+) {
+    operation.deprecated = true;
+
+    let mut header = |description: &str| Header {
+        description: Some(description.to_string()),
+        style: Default::default(),
+        required: true,
+        deprecated: Default::default(),
+        format: ParameterSchemaOrContent::Schema(<String as ToSchema>::schema_ref(
+            schemas,
+            schemas_in_progress,
+        )),
+        example: Default::default(),
+        examples: Default::default(),
+        extensions: Default::default(),
+    };
+
+    let mut headers = vec![(
+        DEPRECATION.as_str().to_string(),
+        ReferenceOr::Item(header("the date of the deprecation, like `@1688169599`")),
+    )];
+
+    if sunset {
+        headers.push((
+            SUNSET.as_str().to_string(),
+            ReferenceOr::Item(header(
+                "the HTTP-date after which the endpoint is expected to stop responding",
+            )),
+        ));
+    }
+
+    operation
+        .responses
+        .responses
+        .values_mut()
+        .for_each(|response| {
+            if let ReferenceOr::Item(response) = response {
+                response.headers.extend(headers.clone());
+            }
+        });
+}
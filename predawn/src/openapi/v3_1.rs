@@ -0,0 +1,280 @@
+use serde_json::{Map, Value, json};
+
+use super::OpenAPI;
+
+/// Keywords kept next to the `anyOf` a nullable schema without a `type` is wrapped in.
+const ANNOTATIONS: [&str; 7] = [
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+/// Converts an OpenAPI 3.0 document to OpenAPI 3.1, whose schemas are JSON Schema 2020-12.
+///
+/// Only the schemas an OpenAPI 3.0 document can hold are converted: those in `components`,
+/// paths, parameters, headers and media types. The output has no `webhooks`, and references stay
+/// `#/components/schemas/...` rather than moving to `$defs`. Schemas inside `x-` extensions and
+/// `example`/`examples` values are left as they are.
+pub fn convert_to_v3_1(api: &OpenAPI) -> Value {
+    let mut value = serde_json::to_value(api).expect("failed to serialize `OpenAPI` to JSON");
+
+    if let Value::Object(document) = &mut value {
+        document.insert("openapi".to_string(), Value::from("3.1.0"));
+        convert_document(document, None);
+    }
+
+    value
+}
+
+/// `parent` is the key `object` is the value of, a `default` key in `responses` is a response.
+fn convert_document(object: &mut Map<String, Value>, parent: Option<&str>) {
+    for (key, value) in object.iter_mut() {
+        match key.as_str() {
+            "schema" => convert_schema(value),
+            "schemas" => {
+                if let Value::Object(schemas) = value {
+                    schemas.values_mut().for_each(convert_schema);
+                }
+            }
+            // arbitrary values, which may contain keys like `schema`
+            "example" | "examples" => {}
+            "default" if parent != Some("responses") => {}
+            key if key.starts_with("x-") => {}
+            key => match value {
+                Value::Object(object) => convert_document(object, Some(key)),
+                Value::Array(array) => array.iter_mut().for_each(|value| {
+                    if let Value::Object(object) = value {
+                        convert_document(object, Some(key));
+                    }
+                }),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn convert_schema(schema: &mut Value) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+
+    if object.contains_key("$ref") {
+        return;
+    }
+
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        properties.values_mut().for_each(convert_schema);
+    }
+
+    for key in ["additionalProperties", "items", "not"] {
+        if let Some(child) = object.get_mut(key) {
+            convert_schema(child);
+        }
+    }
+
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(Value::Array(children)) = object.get_mut(key) {
+            children.iter_mut().for_each(convert_schema);
+        }
+    }
+
+    if object.get("type").and_then(Value::as_str) == Some("string") {
+        match object.get("format").and_then(Value::as_str) {
+            Some("binary") => {
+                object.remove("format");
+                object.insert(
+                    "contentMediaType".to_string(),
+                    Value::from("application/octet-stream"),
+                );
+            }
+            Some("byte") => {
+                object.remove("format");
+                object.insert("contentEncoding".to_string(), Value::from("base64"));
+            }
+            _ => {}
+        }
+    }
+
+    for (exclusive, bound) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(&Value::Bool(is_exclusive)) = object.get(exclusive) {
+            if is_exclusive && let Some(bound) = object.remove(bound) {
+                object.insert(exclusive.to_string(), bound);
+            } else {
+                object.remove(exclusive);
+            }
+        }
+    }
+
+    if let Some(example) = object.remove("example") {
+        object.insert("examples".to_string(), Value::Array(vec![example]));
+    }
+
+    if object.remove("nullable") == Some(Value::Bool(true)) {
+        convert_nullable(object);
+    }
+
+    if let Some(Value::Array(values)) = object.get("enum")
+        && let [value] = values.as_slice()
+    {
+        let value = value.clone();
+        object.remove("enum");
+        object.insert("const".to_string(), value);
+    }
+}
+
+fn convert_nullable(object: &mut Map<String, Value>) {
+    if let Some(Value::String(ty)) = object.get("type") {
+        let ty = json!([ty, "null"]);
+        object.insert("type".to_string(), ty);
+
+        if let Some(Value::Array(values)) = object.get_mut("enum")
+            && !values.contains(&Value::Null)
+        {
+            values.push(Value::Null);
+        }
+
+        return;
+    }
+
+    for key in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(children)) = object.get_mut(key) {
+            children.push(json!({ "type": "null" }));
+            return;
+        }
+    }
+
+    let (annotations, inner): (Map<_, _>, Map<_, _>) = std::mem::take(object)
+        .into_iter()
+        .partition(|(key, _)| ANNOTATIONS.contains(&key.as_str()));
+
+    *object = annotations;
+
+    // an empty schema already accepts `null`
+    if !inner.is_empty() {
+        object.insert(
+            "anyOf".to_string(),
+            json!([Value::Object(inner), { "type": "null" }]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{convert_document, convert_schema};
+
+    fn convert(mut schema: Value) -> Value {
+        convert_schema(&mut schema);
+        schema
+    }
+
+    #[test]
+    fn test_convert_schema() {
+        assert_eq!(
+            convert(json!({ "type": "integer", "nullable": true, "title": "Option<u8>" })),
+            json!({ "type": ["integer", "null"], "title": "Option<u8>" })
+        );
+
+        assert_eq!(
+            convert(json!({ "type": "string", "enum": ["A", "B"], "nullable": true })),
+            json!({ "type": ["string", "null"], "enum": ["A", "B", null] })
+        );
+
+        assert_eq!(
+            convert(json!({ "oneOf": [{ "type": "string", "enum": ["A"] }], "nullable": true })),
+            json!({ "oneOf": [{ "type": "string", "const": "A" }, { "type": "null" }] })
+        );
+
+        assert_eq!(
+            convert(json!({
+                "allOf": [{ "$ref": "#/components/schemas/Person" }],
+                "nullable": true,
+                "description": "A person"
+            })),
+            json!({
+                "anyOf": [
+                    { "allOf": [{ "$ref": "#/components/schemas/Person" }] },
+                    { "type": "null" }
+                ],
+                "description": "A person"
+            })
+        );
+
+        assert_eq!(
+            convert(json!({
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "format": "binary" },
+                    "data": { "type": "string", "format": "byte" }
+                }
+            })),
+            json!({
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string", "contentMediaType": "application/octet-stream" },
+                    "data": { "type": "string", "contentEncoding": "base64" }
+                }
+            })
+        );
+
+        assert_eq!(
+            convert(json!({
+                "type": "number",
+                "minimum": 0,
+                "exclusiveMinimum": true,
+                "maximum": 10,
+                "exclusiveMaximum": false,
+                "example": 5
+            })),
+            json!({ "type": "number", "exclusiveMinimum": 0, "maximum": 10, "examples": [5] })
+        );
+    }
+
+    #[test]
+    fn test_convert_document() {
+        let nullable = json!({ "type": "integer", "nullable": true });
+        let converted = json!({ "type": ["integer", "null"] });
+
+        let content = |schema: &Value| json!({ "application/json": { "schema": schema } });
+
+        let mut document = json!({
+            "paths": {
+                "/": {
+                    "get": {
+                        "responses": {
+                            "200": { "description": "", "content": content(&nullable) },
+                            "default": { "description": "", "content": content(&nullable) }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "examples": {
+                    "raw": { "value": { "schema": { "nullable": true } } }
+                }
+            }
+        });
+
+        let Value::Object(object) = &mut document else {
+            unreachable!()
+        };
+        convert_document(object, None);
+
+        let responses = &document["paths"]["/"]["get"]["responses"];
+        assert_eq!(responses["200"]["content"], content(&converted));
+        assert_eq!(responses["default"]["content"], content(&converted));
+
+        assert_eq!(
+            document["components"]["examples"]["raw"]["value"],
+            json!({ "schema": { "nullable": true } })
+        );
+    }
+}
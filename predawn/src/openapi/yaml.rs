@@ -0,0 +1,195 @@
+use serde_json::Value;
+
+/// Renders `value` as a block style YAML document.
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+
+    match value {
+        Value::Object(object) if !object.is_empty() => write_block(&mut out, value, 0),
+        Value::Array(array) if !array.is_empty() => write_block(&mut out, value, 0),
+        _ => {
+            write_scalar(&mut out, value);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => !object.is_empty(),
+        Value::Array(array) => !array.is_empty(),
+        _ => false,
+    }
+}
+
+/// Writes a non-empty mapping or sequence, each line indented by `indent` spaces.
+fn write_block(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                push_indent(out, indent);
+                write_str(out, key);
+                out.push(':');
+                write_child(out, value, indent + 2);
+            }
+        }
+        Value::Array(array) => {
+            for item in array {
+                push_indent(out, indent);
+                out.push('-');
+
+                if is_block(item) {
+                    // the first line of the item goes right after the dash
+                    let mut item_out = String::new();
+                    write_block(&mut item_out, item, indent + 2);
+                    out.push(' ');
+                    out.push_str(&item_out[indent + 2..]);
+                } else {
+                    out.push(' ');
+                    write_scalar(out, item);
+                    out.push('\n');
+                }
+            }
+        }
+        _ => unreachable!("only mappings and sequences are written as blocks"),
+    }
+}
+
+fn write_child(out: &mut String, value: &Value, indent: usize) {
+    if is_block(value) {
+        out.push('\n');
+        write_block(out, value, indent);
+    } else {
+        out.push(' ');
+        write_scalar(out, value);
+        out.push('\n');
+    }
+}
+
+fn write_scalar(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_str(out, s),
+        Value::Array(_) => out.push_str("[]"),
+        Value::Object(_) => out.push_str("{}"),
+    }
+}
+
+fn write_str(out: &mut String, s: &str) {
+    if needs_quotes(s) {
+        // a JSON string is a valid YAML double quoted scalar
+        out.push_str(&Value::from(s).to_string());
+    } else {
+        out.push_str(s);
+    }
+}
+
+fn needs_quotes(s: &str) -> bool {
+    const RESERVED: [&str; 12] = [
+        "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n", ".inf", ".nan",
+    ];
+
+    let Some(first) = s.chars().next() else {
+        return true;
+    };
+
+    RESERVED
+        .iter()
+        .any(|reserved| s.eq_ignore_ascii_case(reserved))
+        || s.trim() != s
+        || first.is_ascii_digit()
+        || matches!(first, '-' | '+' | '.' | '?' | '<' | '=')
+        || s.chars().any(|c| {
+            c.is_control()
+                || matches!(
+                    c,
+                    ':' | '#'
+                        | '{'
+                        | '}'
+                        | '['
+                        | ']'
+                        | ','
+                        | '&'
+                        | '*'
+                        | '!'
+                        | '|'
+                        | '>'
+                        | '\''
+                        | '"'
+                        | '%'
+                        | '@'
+                        | '`'
+                        | '\\'
+                )
+        })
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.extend(std::iter::repeat_n(' ', indent));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::to_yaml;
+
+    #[test]
+    fn test_to_yaml() {
+        let value = json!({
+            "openapi": "3.1.0",
+            "info": { "title": "Pets", "version": "1.0" },
+            "paths": {
+                "/pets/{id}": {
+                    "get": {
+                        "tags": ["pets", "read"],
+                        "parameters": [{ "name": "id", "required": true }],
+                        "responses": {
+                            "200": { "$ref": "#/components/responses/Pet" }
+                        }
+                    }
+                }
+            },
+            "servers": [],
+            "security": [{}],
+            "nested": [[1, 2], "true", "", " padded", "a: b", "multi\nline", null, 1.5]
+        });
+
+        let expected = r##"info:
+  title: Pets
+  version: "1.0"
+nested:
+  - - 1
+    - 2
+  - "true"
+  - ""
+  - " padded"
+  - "a: b"
+  - "multi\nline"
+  - null
+  - 1.5
+openapi: "3.1.0"
+paths:
+  "/pets/{id}":
+    get:
+      parameters:
+        - name: id
+          required: true
+      responses:
+        "200":
+          $ref: "#/components/responses/Pet"
+      tags:
+        - pets
+        - read
+security:
+  - {}
+servers: []
+"##;
+
+        assert_eq!(to_yaml(&value), expected);
+    }
+}
@@ -1,4 +1,5 @@
 mod openapi_json;
+mod openapi_yaml;
 pub mod ui;
 
 use std::sync::Arc;
@@ -14,4 +15,12 @@ pub trait Plugin {
         self: Arc<Self>,
         cx: &mut Context,
     ) -> (NormalizedPath, IndexMap<Method, DynHandler>);
+
+    /// Overridden by plugins that register more than one path.
+    fn create_routes(
+        self: Arc<Self>,
+        cx: &mut Context,
+    ) -> Vec<(NormalizedPath, IndexMap<Method, DynHandler>)> {
+        vec![self.create_route(cx)]
+    }
 }
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
-use http::Method;
+use bytes::Bytes;
+use http::{HeaderValue, Method, header::CONTENT_TYPE};
 use indexmap::IndexMap;
-use predawn_core::openapi::OpenAPI;
+use predawn_core::{openapi::OpenAPI, request::Request, response::Response};
 use rudi::{Context, Singleton};
 
 use super::Plugin;
@@ -10,7 +11,7 @@ use crate::{
     config::openapi::OpenAPIConfig,
     handler::{DynHandler, handler_fn},
     normalized_path::NormalizedPath,
-    payload::Json,
+    response::conditional::{Content, bytes_etag, respond},
 };
 
 #[derive(Clone, Copy)]
@@ -21,23 +22,66 @@ impl Plugin for OpenAPIJson {
         self: Arc<Self>,
         cx: &mut Context,
     ) -> (NormalizedPath, IndexMap<Method, DynHandler>) {
-        let json_path = cx.resolve::<OpenAPIConfig>().json_path;
+        let OpenAPIConfig {
+            version,
+            pretty,
+            json_path,
+            ..
+        } = cx.resolve::<OpenAPIConfig>();
 
-        let api = cx.resolve::<OpenAPI>();
+        let api = version.render(&cx.resolve::<OpenAPI>());
 
-        let handler = handler_fn(move |_| {
-            let api = api.clone();
-            async move { Ok(Json(api)) }
-        });
-        let handler = DynHandler::new(handler);
+        let json = if pretty {
+            serde_json::to_vec_pretty(&api)
+        } else {
+            serde_json::to_vec(&api)
+        }
+        .expect("failed to serialize `OpenAPI` to JSON");
 
-        let mut map = IndexMap::with_capacity(1);
-        map.insert(Method::GET, handler);
-
-        (json_path, map)
+        (
+            json_path,
+            document_methods(json.into(), mime::APPLICATION_JSON.as_ref()),
+        )
     }
 }
 
+/// Serves a rendered document, answering conditional requests with its `ETag`.
+pub(super) fn document_methods(
+    document: Bytes,
+    content_type: &'static str,
+) -> IndexMap<Method, DynHandler> {
+    let etag = bytes_etag(&document);
+
+    let handler = handler_fn(move |req: Request| {
+        let document = document.clone();
+        let etag = etag.clone();
+
+        async move {
+            let mut response = Response::default();
+
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+            let len = document.len() as u64;
+
+            Ok(respond(
+                &req.head.method,
+                &req.head.headers,
+                response,
+                Content::Bytes(document),
+                len,
+                etag,
+                None,
+            ))
+        }
+    });
+
+    let mut map = IndexMap::with_capacity(1);
+    map.insert(Method::GET, DynHandler::new(handler));
+    map
+}
+
 #[Singleton]
 fn OpenAPIJsonRegister() -> OpenAPIJson {
     OpenAPIJson
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use http::Method;
+use indexmap::IndexMap;
+use predawn_core::openapi::OpenAPI;
+use rudi::{Context, Singleton};
+
+use super::{Plugin, openapi_json::document_methods};
+use crate::{
+    config::openapi::OpenAPIConfig, handler::DynHandler, normalized_path::NormalizedPath,
+    openapi::to_yaml,
+};
+
+#[derive(Clone, Copy)]
+pub struct OpenAPIYaml;
+
+impl Plugin for OpenAPIYaml {
+    fn create_route(
+        self: Arc<Self>,
+        cx: &mut Context,
+    ) -> (NormalizedPath, IndexMap<Method, DynHandler>) {
+        let OpenAPIConfig {
+            version, yaml_path, ..
+        } = cx.resolve::<OpenAPIConfig>();
+
+        let api = version.render(&cx.resolve::<OpenAPI>());
+        let yaml = to_yaml(&api);
+
+        (yaml_path, document_methods(yaml.into(), "application/yaml"))
+    }
+}
+
+#[Singleton]
+fn OpenAPIYamlRegister() -> OpenAPIYaml {
+    OpenAPIYaml
+}
+
+#[Singleton(name = std::any::type_name::<OpenAPIYaml>())]
+fn OpenAPIYamlToPlugin(yaml: OpenAPIYaml) -> Arc<dyn Plugin> {
+    Arc::new(yaml)
+}
@@ -0,0 +1,469 @@
+mod subscription;
+
+use std::{
+    any::{Any, type_name},
+    collections::{BTreeSet, HashMap},
+    fmt,
+    marker::PhantomData,
+    ptr,
+    sync::{Arc, Mutex, Weak},
+};
+
+use rudi::Singleton;
+
+use self::subscription::{Pushed, Queue};
+pub use self::subscription::{RecvError, Subscription};
+use crate::config::pubsub::{LagPolicy, PubSubConfig};
+
+/// Fans out messages published to named topics to every subscriber of them, such as the
+/// WebSockets or event streams of a chat room.
+///
+/// Each subscriber has its own buffer of `capacity` messages, a subscriber that does not keep up
+/// is handled according to the [`LagPolicy`].
+#[derive(Clone)]
+pub struct Hub {
+    inner: Arc<HubInner>,
+}
+
+struct HubInner {
+    capacity: usize,
+    lag_policy: LagPolicy,
+    state: Mutex<HubState>,
+}
+
+#[derive(Default)]
+struct HubState {
+    topics: HashMap<Box<str>, Arc<dyn AnyTopic>>,
+    next_subscriber_id: u64,
+    drained: bool,
+}
+
+impl fmt::Debug for Hub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hub")
+            .field("capacity", &self.inner.capacity)
+            .field("lag_policy", &self.inner.lag_policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[Singleton]
+impl Hub {
+    #[di]
+    pub fn new(#[di(ref)] config: &PubSubConfig) -> Self {
+        Self::with_config(config.capacity, config.lag_policy)
+    }
+}
+
+impl Hub {
+    pub fn with_config(capacity: usize, lag_policy: LagPolicy) -> Self {
+        Self {
+            inner: Arc::new(HubInner {
+                capacity: capacity.max(1),
+                lag_policy,
+                state: Default::default(),
+            }),
+        }
+    }
+
+    /// Returns the topic named `name`, it is created by the first subscription and removed once
+    /// the last one is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the topic has subscribers of another message type, either now or when it is used.
+    pub fn topic<T>(&self, name: &str) -> Topic<T>
+    where
+        T: Clone + Send + 'static,
+    {
+        let topic = self.inner.state.lock().unwrap().topics.get(name).cloned();
+
+        if let Some(topic) = topic {
+            downcast::<T>(topic, name);
+        }
+
+        Topic {
+            hub: self.clone(),
+            name: name.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Closes every topic, subscriptions end after their buffered messages are received.
+    pub fn drain(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+
+        if state.drained {
+            return;
+        }
+
+        state.drained = true;
+        state.topics.drain().for_each(|(_, topic)| topic.close());
+    }
+
+    /// Drains the hub once `signal` completes, to be passed to
+    /// [`Server::run_with_graceful_shutdown`](crate::server::Server::run_with_graceful_shutdown),
+    /// so that long-lived event streams and WebSockets do not hold up the shutdown.
+    pub fn drain_on<S>(&self, signal: S) -> impl Future<Output = ()> + Send + 'static
+    where
+        S: Future<Output = ()> + Send + 'static,
+    {
+        let hub = self.clone();
+
+        async move {
+            signal.await;
+            hub.drain();
+        }
+    }
+}
+
+impl HubInner {
+    /// Removes the topic if it is still registered and has no subscribers left.
+    fn remove_if_unused<T>(&self, topic: &TopicInner<T>) {
+        let mut state = self.state.lock().unwrap();
+
+        let registered = state
+            .topics
+            .get(&topic.name)
+            .is_some_and(|registered| ptr::addr_eq(Arc::as_ptr(registered), ptr::from_ref(topic)));
+
+        if registered && topic.subscribers.lock().unwrap().is_empty() {
+            state.topics.remove(&topic.name);
+        }
+    }
+}
+
+fn downcast<T>(topic: Arc<dyn AnyTopic>, name: &str) -> Arc<TopicInner<T>>
+where
+    T: Send + 'static,
+{
+    topic
+        .into_any()
+        .downcast::<TopicInner<T>>()
+        .unwrap_or_else(|_| type_mismatch::<T>(name))
+}
+
+fn type_mismatch<T>(name: &str) -> ! {
+    panic!(
+        "the topic `{name}` was created with another message type than `{}`",
+        type_name::<T>()
+    )
+}
+
+trait AnyTopic: Send + Sync {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    fn close(&self);
+}
+
+/// A named channel of a [`Hub`], whose messages are received by all of its subscribers.
+pub struct Topic<T> {
+    hub: Hub,
+    name: Box<str>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+            name: self.name.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Topic<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Topic")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) struct TopicInner<T> {
+    name: Box<str>,
+    hub: Weak<HubInner>,
+    subscribers: Mutex<HashMap<u64, Subscriber<T>>>,
+}
+
+struct Subscriber<T> {
+    queue: Arc<Queue<T>>,
+    member: Option<Box<str>>,
+}
+
+impl<T: Send + 'static> AnyTopic for TopicInner<T> {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn close(&self) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .drain()
+            .for_each(|(_, subscriber)| subscriber.queue.close());
+    }
+}
+
+impl<T> TopicInner<T> {
+    pub(crate) fn unsubscribe(&self, id: u64) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        if subscribers.remove(&id).is_some() && subscribers.is_empty() {
+            drop(subscribers);
+            self.remove_if_unused();
+        }
+    }
+
+    fn remove_if_unused(&self) {
+        if let Some(hub) = self.hub.upgrade() {
+            hub.remove_if_unused(self);
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Topic<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sends the message to every subscriber, returns how many of them buffered it.
+    pub fn publish(&self, message: T) -> usize {
+        let Some(topic) = self.registered() else {
+            return 0;
+        };
+
+        let HubInner {
+            capacity,
+            lag_policy,
+            ..
+        } = &*self.hub.inner;
+
+        let mut delivered = 0;
+        let mut subscribers = topic.subscribers.lock().unwrap();
+
+        subscribers.retain(|_, subscriber| {
+            match subscriber
+                .queue
+                .push(message.clone(), *capacity, *lag_policy)
+            {
+                Pushed::Buffered => {
+                    delivered += 1;
+                    true
+                }
+                Pushed::Dropped => true,
+                Pushed::Disconnected => false,
+            }
+        });
+
+        if subscribers.is_empty() {
+            drop(subscribers);
+            topic.remove_if_unused();
+        }
+
+        delivered
+    }
+
+    pub fn subscribe(&self) -> Subscription<T> {
+        self.insert(None)
+    }
+
+    /// Subscribes on behalf of `member`, who is listed in the [`presence`](Self::presence) of the
+    /// topic until the subscription is dropped.
+    pub fn join<M: Into<Box<str>>>(&self, member: M) -> Subscription<T> {
+        self.insert(Some(member.into()))
+    }
+
+    /// The members who joined the topic, a member who joined more than once is listed once.
+    pub fn presence(&self) -> Vec<String> {
+        let Some(topic) = self.registered() else {
+            return Vec::new();
+        };
+
+        topic
+            .subscribers
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|subscriber| subscriber.member.as_deref())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.registered()
+            .map(|topic| topic.subscribers.lock().unwrap().len())
+            .unwrap_or_default()
+    }
+
+    fn registered(&self) -> Option<Arc<TopicInner<T>>> {
+        let state = self.hub.inner.state.lock().unwrap();
+        let topic = state.topics.get(&self.name)?.clone();
+        drop(state);
+
+        Some(downcast(topic, &self.name))
+    }
+
+    fn insert(&self, member: Option<Box<str>>) -> Subscription<T> {
+        let mut state = self.hub.inner.state.lock().unwrap();
+
+        if state.drained {
+            return Subscription {
+                id: 0,
+                queue: Arc::new(Queue::new(true)),
+                topic: Weak::new(),
+            };
+        }
+
+        let topic = state
+            .topics
+            .entry(self.name.clone())
+            .or_insert_with(|| {
+                Arc::new(TopicInner::<T> {
+                    name: self.name.clone(),
+                    hub: Arc::downgrade(&self.hub.inner),
+                    subscribers: Default::default(),
+                })
+            })
+            .clone();
+
+        let Ok(topic) = topic.into_any().downcast::<TopicInner<T>>() else {
+            // not to poison the lock
+            drop(state);
+            type_mismatch::<T>(&self.name);
+        };
+
+        let id = state.next_subscriber_id;
+        state.next_subscriber_id += 1;
+
+        let queue = Arc::new(Queue::new(false));
+
+        topic.subscribers.lock().unwrap().insert(
+            id,
+            Subscriber {
+                queue: queue.clone(),
+                member,
+            },
+        );
+
+        Subscription {
+            id,
+            queue,
+            topic: Arc::downgrade(&topic),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hub, RecvError};
+    use crate::config::pubsub::LagPolicy;
+
+    #[tokio::test]
+    async fn test_publish_and_subscribe() {
+        let hub = Hub::with_config(2, LagPolicy::DropOldest);
+        let topic = hub.topic::<u32>("numbers");
+
+        let mut alice = topic.join("alice");
+        let mut bob = hub.topic::<u32>("numbers").join("bob");
+        let _alice_again = topic.join("alice");
+
+        assert_eq!(topic.subscriber_count(), 3);
+        assert_eq!(topic.presence(), ["alice", "bob"]);
+
+        assert_eq!(topic.publish(1), 3);
+        assert_eq!(alice.recv().await, Ok(1));
+
+        topic.publish(2);
+        topic.publish(3);
+
+        assert_eq!(alice.recv().await, Ok(2));
+        assert_eq!(bob.recv().await, Err(RecvError::Lagged { dropped: 1 }));
+        assert_eq!(bob.recv().await, Ok(2));
+
+        drop(bob);
+        assert_eq!(topic.presence(), ["alice"]);
+
+        hub.drain();
+
+        assert_eq!(alice.recv().await, Ok(3));
+        assert_eq!(alice.recv().await, Err(RecvError::Closed));
+        assert_eq!(topic.subscriber_count(), 0);
+
+        let mut late = topic.subscribe();
+        assert_eq!(late.recv().await, Err(RecvError::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_lag_policy() {
+        let hub = Hub::with_config(1, LagPolicy::DropNewest);
+        let topic = hub.topic::<u32>("numbers");
+
+        let mut subscription = topic.subscribe();
+        assert_eq!(topic.publish(1), 1);
+        assert_eq!(topic.publish(2), 0);
+        assert_eq!(
+            subscription.recv().await,
+            Err(RecvError::Lagged { dropped: 1 })
+        );
+        assert_eq!(subscription.recv().await, Ok(1));
+
+        let hub = Hub::with_config(1, LagPolicy::Disconnect);
+        let topic = hub.topic::<u32>("numbers");
+
+        let mut subscription = topic.subscribe();
+        topic.publish(1);
+        topic.publish(2);
+        assert_eq!(topic.subscriber_count(), 0);
+        assert_eq!(
+            subscription.recv().await,
+            Err(RecvError::Lagged { dropped: 1 })
+        );
+        assert_eq!(subscription.recv().await, Ok(1));
+        assert_eq!(subscription.recv().await, Err(RecvError::Closed));
+    }
+
+    fn topic_count(hub: &Hub) -> usize {
+        hub.inner.state.lock().unwrap().topics.len()
+    }
+
+    #[tokio::test]
+    async fn test_remove_unused_topics() {
+        let hub = Hub::with_config(1, LagPolicy::Disconnect);
+        let topic = hub.topic::<u32>("numbers");
+
+        let first = topic.subscribe();
+        let second = topic.subscribe();
+        assert_eq!(topic_count(&hub), 1);
+
+        drop(first);
+        assert_eq!(topic_count(&hub), 1);
+
+        drop(second);
+        assert_eq!(topic_count(&hub), 0);
+        assert_eq!(topic.publish(1), 0);
+
+        // another message type can be used once the topic is removed
+        let mut subscription = hub.topic::<String>("numbers").subscribe();
+        assert_eq!(topic_count(&hub), 1);
+
+        assert_eq!(hub.topic::<String>("numbers").publish("1".into()), 1);
+        assert_eq!(hub.topic::<String>("numbers").publish("2".into()), 0);
+        assert_eq!(topic_count(&hub), 0);
+        assert_eq!(
+            subscription.recv().await,
+            Err(RecvError::Lagged { dropped: 1 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "another message type")]
+    fn test_topic_type_mismatch() {
+        let hub = Hub::with_config(1, LagPolicy::DropOldest);
+        let _numbers = hub.topic::<u32>("numbers").subscribe();
+        hub.topic::<String>("numbers");
+    }
+}
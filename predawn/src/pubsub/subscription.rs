@@ -0,0 +1,215 @@
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    fmt,
+    sync::{Arc, Mutex, Weak},
+};
+
+use futures_core::Stream;
+use serde::{Serialize, de::DeserializeOwned};
+use snafu::Snafu;
+use tokio::sync::Notify;
+
+use super::TopicInner;
+use crate::{
+    config::pubsub::LagPolicy,
+    extract::websocket::{
+        CloseCode, CloseFrame, TypedWebSocket, TypedWebSocketError, Utf8Bytes, WebSocketCodec,
+    },
+    response::sse::EventStream,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Snafu)]
+pub enum RecvError {
+    #[snafu(display("the subscriber lagged behind, {dropped} message(s) were dropped"))]
+    Lagged { dropped: u64 },
+    #[snafu(display("the subscription is closed"))]
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pushed {
+    Buffered,
+    Dropped,
+    Disconnected,
+}
+
+pub(crate) struct Queue<T> {
+    state: Mutex<QueueState<T>>,
+    notify: Notify,
+}
+
+struct QueueState<T> {
+    messages: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+impl<T> Queue<T> {
+    pub(crate) fn new(closed: bool) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                messages: VecDeque::new(),
+                dropped: 0,
+                closed,
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub(crate) fn push(&self, message: T, capacity: usize, lag_policy: LagPolicy) -> Pushed {
+        let mut state = self.state.lock().unwrap();
+
+        if state.closed {
+            return Pushed::Disconnected;
+        }
+
+        let pushed = if state.messages.len() < capacity {
+            state.messages.push_back(message);
+            Pushed::Buffered
+        } else {
+            state.dropped += 1;
+
+            match lag_policy {
+                LagPolicy::DropOldest => {
+                    state.messages.pop_front();
+                    state.messages.push_back(message);
+                    Pushed::Buffered
+                }
+                LagPolicy::DropNewest => Pushed::Dropped,
+                LagPolicy::Disconnect => {
+                    state.closed = true;
+                    Pushed::Disconnected
+                }
+            }
+        };
+
+        drop(state);
+        self.notify.notify_one();
+
+        pushed
+    }
+
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.notify.notify_one();
+    }
+}
+
+/// The messages published to a [`Topic`](super::Topic) since subscribing, buffered until they are received.
+///
+/// Dropping it unsubscribes from the topic.
+pub struct Subscription<T> {
+    pub(crate) id: u64,
+    pub(crate) queue: Arc<Queue<T>>,
+    pub(crate) topic: Weak<TopicInner<T>>,
+}
+
+impl<T> fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Receive the next message.
+    ///
+    /// Returns [`RecvError::Lagged`] once if messages were dropped since the last call, and
+    /// [`RecvError::Closed`] after all buffered messages are received from a closed subscription.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        loop {
+            {
+                let mut state = self.queue.state.lock().unwrap();
+
+                if state.dropped > 0 {
+                    let dropped = std::mem::take(&mut state.dropped);
+                    return LaggedSnafu { dropped }.fail();
+                }
+
+                if let Some(message) = state.messages.pop_front() {
+                    return Ok(message);
+                }
+
+                if state.closed {
+                    return ClosedSnafu.fail();
+                }
+            }
+
+            self.queue.notify.notified().await;
+        }
+    }
+
+    /// Turns into a stream of the messages, skipping over lags, which ends when the subscription is closed.
+    pub fn into_stream(self) -> impl Stream<Item = T> + Send + 'static
+    where
+        T: Send + 'static,
+    {
+        futures_util::stream::unfold(self, |mut subscription| async move {
+            loop {
+                match subscription.recv().await {
+                    Ok(message) => return Some((message, subscription)),
+                    Err(RecvError::Lagged { .. }) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Sends every message as an event, until the subscription is closed.
+    pub fn into_event_stream(self) -> EventStream<T>
+    where
+        T: Serialize + Send + 'static,
+    {
+        EventStream::new(futures_util::StreamExt::map(
+            self.into_stream(),
+            Ok::<_, Infallible>,
+        ))
+    }
+
+    /// Sends every message to the WebSocket, until either the subscription or the WebSocket is closed.
+    ///
+    /// Messages received from the WebSocket meanwhile are discarded. When the subscription is
+    /// closed, such as when the [`Hub`](super::Hub) is drained, the WebSocket is closed with
+    /// the code `1001`.
+    pub async fn pump<In, C>(
+        mut self,
+        socket: &mut TypedWebSocket<In, T, C>,
+    ) -> Result<(), TypedWebSocketError>
+    where
+        In: DeserializeOwned,
+        T: Serialize,
+        C: WebSocketCodec,
+    {
+        loop {
+            tokio::select! {
+                message = self.recv() => match message {
+                    Ok(message) => socket.send(&message).await?,
+                    Err(RecvError::Lagged { .. }) => continue,
+                    Err(RecvError::Closed) => {
+                        let frame = CloseFrame {
+                            code: CloseCode::Away,
+                            reason: Utf8Bytes::from_static("going away"),
+                        };
+
+                        return socket.close(Some(frame)).await;
+                    }
+                },
+                inbound = socket.recv() => match inbound {
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        if let Some(topic) = self.topic.upgrade() {
+            topic.unsubscribe(self.id);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::{
+    fmt::Write as _,
+    hash::{BuildHasher, RandomState},
+    io,
+    ops::Bound,
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use futures_util::{
+    StreamExt,
+    stream::{self, BoxStream},
+};
+use headers::{
+    AcceptRanges, ContentLength, ContentRange, ETag, HeaderMapExt, IfMatch, IfModifiedSince,
+    IfNoneMatch, IfRange, IfUnmodifiedSince, LastModified, Range,
+};
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header::CONTENT_TYPE};
+use predawn_core::{body::ResponseBody, response::Response};
+use sha2::{Digest, Sha256};
+
+/// More ranges than this are answered with the full representation.
+const MAX_RANGES: usize = 32;
+
+/// What to send for a representation, according to the conditional and range headers of a request.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Full,
+    NotModified,
+    PreconditionFailed,
+    /// Inclusive byte ranges, sorted and without overlaps.
+    Partial(Vec<(u64, u64)>),
+    RangeNotSatisfiable,
+}
+
+fn evaluate(
+    method: &Method,
+    headers: &HeaderMap,
+    len: u64,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+) -> Outcome {
+    // RFC 9110 section 13.2.2
+    if let Some(if_match) = headers.typed_get::<IfMatch>() {
+        if !etag.is_some_and(|etag| if_match.precondition_passes(etag)) {
+            return Outcome::PreconditionFailed;
+        }
+    } else if let Some(since) = headers.typed_get::<IfUnmodifiedSince>()
+        && let Some(last_modified) = last_modified
+        && !since.precondition_passes(last_modified)
+    {
+        return Outcome::PreconditionFailed;
+    }
+
+    let get_or_head = method == Method::GET || method == Method::HEAD;
+
+    if let Some(if_none_match) = headers.typed_get::<IfNoneMatch>() {
+        if etag.is_some_and(|etag| !if_none_match.precondition_passes(etag)) {
+            return if get_or_head {
+                Outcome::NotModified
+            } else {
+                Outcome::PreconditionFailed
+            };
+        }
+    } else if get_or_head
+        && let Some(since) = headers.typed_get::<IfModifiedSince>()
+        && let Some(last_modified) = last_modified
+        && !since.is_modified(last_modified)
+    {
+        return Outcome::NotModified;
+    }
+
+    if method != Method::GET {
+        return Outcome::Full;
+    }
+
+    let Some(range) = headers.typed_get::<Range>() else {
+        return Outcome::Full;
+    };
+
+    if let Some(if_range) = headers.typed_get::<IfRange>() {
+        let last_modified = last_modified.map(LastModified::from);
+
+        if if_range.is_modified(etag, last_modified.as_ref()) {
+            return Outcome::Full;
+        }
+    }
+
+    let mut ranges = range
+        .satisfiable_ranges(len)
+        .filter_map(|(start, end)| {
+            let start = match start {
+                Bound::Included(start) => start,
+                Bound::Excluded(start) => start.checked_add(1)?,
+                Bound::Unbounded => 0,
+            };
+
+            let end = match end {
+                Bound::Included(end) => end.min(len.checked_sub(1)?),
+                Bound::Excluded(end) => end.checked_sub(1)?.min(len.checked_sub(1)?),
+                Bound::Unbounded => len.checked_sub(1)?,
+            };
+
+            (start <= end).then_some((start, end))
+        })
+        .collect::<Vec<_>>();
+
+    // RFC 9110 section 14.2, overlapping and adjacent ranges are coalesced,
+    // so that no byte is sent twice
+    ranges.sort_unstable();
+    ranges.dedup_by(|(start, end), (_, prev_end)| {
+        if *start <= prev_end.saturating_add(1) {
+            *prev_end = (*prev_end).max(*end);
+            true
+        } else {
+            false
+        }
+    });
+
+    match ranges.len() {
+        0 => Outcome::RangeNotSatisfiable,
+        n if n > MAX_RANGES => Outcome::Full,
+        _ => Outcome::Partial(ranges),
+    }
+}
+
+/// The representation sent by [`respond`].
+pub(crate) enum Content {
+    Bytes(Bytes),
+    #[cfg(feature = "fs")]
+    File(std::fs::File),
+}
+
+impl Content {
+    fn part(&self, start: u64, end: u64) -> BoxStream<'static, io::Result<Bytes>> {
+        match self {
+            Content::Bytes(bytes) => {
+                let part = bytes.slice(start as usize..=end as usize);
+                stream::once(async move { Ok(part) }).boxed()
+            }
+            #[cfg(feature = "fs")]
+            Content::File(file) => {
+                use futures_util::TryStreamExt;
+                use tokio::io::{AsyncReadExt, AsyncSeekExt};
+                use tokio_util::io::ReaderStream;
+
+                // the clones share the position of `file`, which is fine as the parts are read
+                // one after another and each one seeks to its start first
+                let file = file.try_clone();
+
+                let read = async move {
+                    let mut file = tokio::fs::File::from_std(file?);
+                    file.seek(io::SeekFrom::Start(start)).await?;
+                    io::Result::Ok(ReaderStream::new(file.take(end - start + 1)))
+                };
+
+                stream::once(read).try_flatten().boxed()
+            }
+        }
+    }
+
+    fn into_body(self) -> ResponseBody {
+        match self {
+            Content::Bytes(bytes) => bytes.into(),
+            #[cfg(feature = "fs")]
+            Content::File(file) => ResponseBody::from_stream(tokio_util::io::ReaderStream::new(
+                tokio::fs::File::from_std(file),
+            )),
+        }
+    }
+}
+
+/// A strong `ETag` derived from the SHA-256 digest of the content, so that it is the same across
+/// processes and releases.
+pub(crate) fn bytes_etag(bytes: &Bytes) -> Option<ETag> {
+    let digest = Sha256::digest(bytes);
+
+    let mut tag = String::with_capacity(34);
+    tag.push('"');
+    digest[..16].iter().for_each(|byte| {
+        let _ = write!(tag, "{byte:02x}");
+    });
+    tag.push('"');
+
+    tag.parse().ok()
+}
+
+/// Sets the status, validators, `Content-Length` and body of `response` according to the conditional
+/// and range headers of the request.
+///
+/// The `Content-Type` of `response` is used for the parts of a `multipart/byteranges` body.
+pub(crate) fn respond(
+    method: &Method,
+    request_headers: &HeaderMap,
+    mut response: Response,
+    content: Content,
+    len: u64,
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+) -> Response {
+    let headers = response.headers_mut();
+
+    headers.typed_insert(AcceptRanges::bytes());
+
+    if let Some(etag) = &etag {
+        headers.typed_insert(etag.clone());
+    }
+
+    if let Some(last_modified) = last_modified {
+        headers.typed_insert(LastModified::from(last_modified));
+    }
+
+    let (status, body_len, body) = match evaluate(
+        method,
+        request_headers,
+        len,
+        etag.as_ref(),
+        last_modified,
+    ) {
+        Outcome::NotModified => {
+            headers.remove(CONTENT_TYPE);
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            *response.body_mut() = ResponseBody::empty();
+            return response;
+        }
+        Outcome::PreconditionFailed => {
+            headers.remove(CONTENT_TYPE);
+            *response.status_mut() = StatusCode::PRECONDITION_FAILED;
+            *response.body_mut() = ResponseBody::empty();
+            return response;
+        }
+        Outcome::RangeNotSatisfiable => {
+            headers.remove(CONTENT_TYPE);
+            headers.typed_insert(ContentRange::unsatisfied_bytes(len));
+            *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            *response.body_mut() = ResponseBody::empty();
+            return response;
+        }
+        Outcome::Full => (StatusCode::OK, len, content.into_body()),
+        Outcome::Partial(ranges) if ranges.len() == 1 => {
+            let (start, end) = ranges[0];
+
+            headers.typed_insert(ContentRange::bytes(start..=end, len).unwrap());
+
+            (
+                StatusCode::PARTIAL_CONTENT,
+                end - start + 1,
+                ResponseBody::from_stream(content.part(start, end)),
+            )
+        }
+        Outcome::Partial(ranges) => {
+            let state = RandomState::new();
+            let boundary = format!("{:016x}{:016x}", state.hash_one(0), state.hash_one(1));
+
+            let content_type = headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or(mime::APPLICATION_OCTET_STREAM.as_ref())
+                .to_string();
+
+            let mut body_len = 0;
+            let mut parts = Vec::with_capacity(ranges.len() * 2 + 1);
+
+            for (idx, (start, end)) in ranges.into_iter().enumerate() {
+                let part_head = format!(
+                    "{}--{boundary}\r\nContent-Type: {content_type}\r\nContent-Range: bytes {start}-{end}/{len}\r\n\r\n",
+                    if idx == 0 { "" } else { "\r\n" }
+                );
+
+                body_len += part_head.len() as u64 + end - start + 1;
+
+                parts.push(stream::once(async move { Ok(Bytes::from(part_head)) }).boxed());
+                parts.push(content.part(start, end));
+            }
+
+            let tail = format!("\r\n--{boundary}--\r\n");
+            body_len += tail.len() as u64;
+            parts.push(stream::once(async move { Ok(Bytes::from(tail)) }).boxed());
+
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::try_from(format!("multipart/byteranges; boundary={boundary}"))
+                    .unwrap(),
+            );
+
+            (
+                StatusCode::PARTIAL_CONTENT,
+                body_len,
+                ResponseBody::from_stream(stream::iter(parts).flatten()),
+            )
+        }
+    };
+
+    headers.typed_insert(ContentLength(body_len));
+    *response.status_mut() = status;
+    *response.body_mut() = if method == Method::HEAD {
+        ResponseBody::empty()
+    } else {
+        body
+    };
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use headers::{ETag, HeaderMapExt, IfModifiedSince, IfNoneMatch, IfRange, Range};
+    use http::{HeaderMap, Method};
+
+    use super::{Outcome, evaluate};
+
+    #[test]
+    fn test_evaluate() {
+        let etag = "\"abc\"".parse::<ETag>().unwrap();
+        let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        let eval = |headers: &HeaderMap| {
+            evaluate(&Method::GET, headers, 100, Some(&etag), Some(last_modified))
+        };
+
+        assert_eq!(eval(&HeaderMap::new()), Outcome::Full);
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(IfNoneMatch::from(etag.clone()));
+        assert_eq!(eval(&headers), Outcome::NotModified);
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(IfModifiedSince::from(last_modified));
+        assert_eq!(eval(&headers), Outcome::NotModified);
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Range::bytes(10..20).unwrap());
+        assert_eq!(eval(&headers), Outcome::Partial(vec![(10, 19)]));
+
+        headers.insert("range", "bytes=90-,0-0,-10".parse().unwrap());
+        assert_eq!(eval(&headers), Outcome::Partial(vec![(0, 0), (90, 99)]));
+
+        headers.insert("range", "bytes=0-9,5-19,20-29,50-59".parse().unwrap());
+        assert_eq!(eval(&headers), Outcome::Partial(vec![(0, 29), (50, 59)]));
+
+        headers.insert("range", "bytes=0-9,0-9".parse().unwrap());
+        assert_eq!(eval(&headers), Outcome::Partial(vec![(0, 9)]));
+
+        headers.insert("range", "bytes=100-".parse().unwrap());
+        assert_eq!(eval(&headers), Outcome::RangeNotSatisfiable);
+
+        headers.typed_insert(Range::bytes(10..20).unwrap());
+        headers.typed_insert(IfRange::etag("\"other\"".parse().unwrap()));
+        assert_eq!(eval(&headers), Outcome::Full);
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_file_ranges() {
+        use http_body_util::BodyExt;
+        use predawn_core::response::Response;
+
+        use super::{Content, respond};
+
+        let path = std::env::temp_dir().join(format!("predawn-ranges-{}", std::process::id()));
+        std::fs::write(&path, "0123456789").unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=7-8,1-2".parse().unwrap());
+
+        let content = Content::File(std::fs::File::open(&path).unwrap());
+        let response = respond(
+            &Method::GET,
+            &headers,
+            Response::default(),
+            content,
+            10,
+            None,
+            None,
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        let parts = body
+            .split("\r\n\r\n")
+            .skip(1)
+            .map(|part| &part[..2])
+            .collect::<Vec<_>>();
+
+        assert_eq!(parts, ["12", "78"]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
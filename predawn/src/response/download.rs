@@ -1,19 +1,49 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
 
-use bytes::{BufMut, BytesMut};
 use http::{
-    HeaderValue, StatusCode,
-    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    header::{
+        ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_MATCH,
+        IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, RANGE,
+    },
 };
+use http_body::Body;
+use http_body_util::BodyExt;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
 use predawn_core::{
     api_response::ApiResponse,
+    body::ResponseBody,
+    error::Error,
     into_response::IntoResponse,
     media_type::{MediaType, MultiResponseMediaType, ResponseMediaType, SingleMediaType},
-    openapi::{self, Schema},
+    openapi::{self, Header, ParameterSchemaOrContent, ReferenceOr, Schema},
+    request::Request,
     response::{MultiResponse, Response, SingleResponse},
 };
 use predawn_schema::ToSchema;
 
+use super::conditional::{Content, bytes_etag, respond};
+use crate::handler::Handler;
+
+/// `attr-char` of RFC 8187, everything else is percent-encoded in `filename*`.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
 #[derive(Debug)]
 enum DownloadType {
     Inline,
@@ -29,6 +59,73 @@ impl DownloadType {
     }
 }
 
+/// Marks a response whose range and conditional requests are handled by [`resolve`].
+#[derive(Debug, Clone, Copy)]
+struct Ranged;
+
+/// The body of a [`Download`] answering a `HEAD` request, kept for [`resolve`] to derive the
+/// validators from, so that a conditional `HEAD` is answered like the `GET` it stands for.
+#[derive(Clone)]
+struct HeadBody(Arc<Mutex<Option<ResponseBody>>>);
+
+/// Empties the body of the `GET` response used to answer a `HEAD` request.
+pub(crate) fn clear_head_body(response: &mut Response) {
+    let body = std::mem::take(response.body_mut());
+
+    if response.extensions().get::<Ranged>().is_some() {
+        response
+            .extensions_mut()
+            .insert(HeadBody(Arc::new(Mutex::new(Some(body)))));
+    }
+}
+
+/// A file on disk that is opened and streamed when the response is sent.
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone)]
+pub struct DiskFile(std::path::PathBuf);
+
+#[cfg(feature = "fs")]
+impl DiskFile {
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        Self(path.into())
+    }
+}
+
+#[cfg(feature = "fs")]
+impl IntoResponse for DiskFile {
+    type Error = std::convert::Infallible;
+
+    fn into_response(self) -> Result<Response, Self::Error> {
+        use futures_util::TryStreamExt;
+        use predawn_core::body::ResponseBody;
+
+        let path = self.0.clone();
+
+        let body = futures_util::stream::once(tokio::fs::File::open(path))
+            .map_ok(tokio_util::io::ReaderStream::new)
+            .try_flatten();
+
+        let mut response = Response::new(ResponseBody::from_stream(body));
+        response.extensions_mut().insert(self);
+
+        Ok(response)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl MediaType for DiskFile {
+    const MEDIA_TYPE: &'static str = <Vec<u8> as MediaType>::MEDIA_TYPE;
+}
+
+#[cfg(feature = "fs")]
+impl ResponseMediaType for DiskFile {}
+
+/// Sends `data` with a `Content-Disposition` header.
+///
+/// `Range`, `If-Range`, `If-None-Match` and the other conditional request headers are honored,
+/// the `ETag` is derived from the content, or from the modification time and size of a [`DiskFile`].
+/// A body of unknown length, such as a stream, is sent as it is, without `ETag` and `Accept-Ranges`.
 #[derive(Debug)]
 pub struct Download<T> {
     data: T,
@@ -41,41 +138,71 @@ impl<T> Download<T> {
     where
         N: TryInto<HeaderValue>,
     {
-        fn inner<T>(data: T, file_name: HeaderValue) -> Download<T> {
-            Download {
-                data,
-                ty: DownloadType::Inline,
-                file_name,
-            }
-        }
-
-        Ok(inner(data, file_name.try_into()?))
+        Ok(Self::new(data, DownloadType::Inline, file_name.try_into()?))
     }
 
     pub fn attachment<N>(data: T, file_name: N) -> Result<Self, N::Error>
     where
         N: TryInto<HeaderValue>,
     {
-        fn inner<T>(data: T, file_name: HeaderValue) -> Download<T> {
-            Download {
-                data,
-                ty: DownloadType::Attachment,
-                file_name,
-            }
+        Ok(Self::new(
+            data,
+            DownloadType::Attachment,
+            file_name.try_into()?,
+        ))
+    }
+
+    fn new(data: T, ty: DownloadType, file_name: HeaderValue) -> Self {
+        Self {
+            data,
+            ty,
+            file_name,
         }
+    }
+
+    /// Non-ASCII file names are sent in `filename*` (RFC 6266), with an ASCII fallback in `filename`.
+    fn content_disposition(ty: DownloadType, file_name: &HeaderValue) -> HeaderValue {
+        let file_name = String::from_utf8_lossy(file_name.as_bytes());
+        let file_name = file_name.as_ref();
+
+        let fallback = file_name
+            .chars()
+            .map(|c| match c {
+                ' ' => ' ',
+                '"' | '\\' => '_',
+                c if c.is_ascii_graphic() => c,
+                _ => '_',
+            })
+            .collect::<String>();
+
+        let mut value = format!("{}; filename=\"{fallback}\"", ty.as_str());
 
-        Ok(inner(data, file_name.try_into()?))
+        if fallback != file_name {
+            value.push_str("; filename*=UTF-8''");
+            value.extend(percent_encoding::utf8_percent_encode(file_name, ATTR_CHAR));
+        }
+
+        HeaderValue::try_from(value).unwrap()
     }
+}
 
-    fn content_disposition(ty: DownloadType, file_name: HeaderValue) -> HeaderValue {
-        let mut buf = BytesMut::with_capacity(16);
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[cfg(feature = "fs")]
+impl Download<DiskFile> {
+    /// Streams the file at `path` as an attachment named after it.
+    pub fn from_path<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        let path = path.into();
 
-        buf.extend_from_slice(ty.as_str().as_bytes());
-        buf.extend_from_slice(b"; filename=\"");
-        buf.extend_from_slice(file_name.as_bytes());
-        buf.put_u8(b'"');
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "download".to_string());
 
-        HeaderValue::from_maybe_shared(buf.freeze()).unwrap()
+        // a file name with control characters is not a valid header value
+        let file_name = HeaderValue::try_from(file_name)
+            .unwrap_or_else(|_| HeaderValue::from_static("download"));
+
+        Self::new(DiskFile(path), DownloadType::Attachment, file_name)
     }
 }
 
@@ -100,22 +227,187 @@ impl<T: IntoResponse + MediaType> IntoResponse for Download<T> {
 
         headers.insert(
             CONTENT_DISPOSITION,
-            Self::content_disposition(ty, file_name),
+            Self::content_disposition(ty, &file_name),
         );
 
+        response.extensions_mut().insert(Ranged);
+
         Ok(response)
     }
 }
 
+/// Applies the range and conditional headers of a request to the response of a [`Download`].
+pub(crate) async fn resolve<H: Handler>(inner: Arc<H>, req: Request) -> Result<Response, Error> {
+    let method = req.head.method.clone();
+
+    // only what `respond` looks at, to avoid cloning every header of every request
+    let request_headers = [
+        RANGE,
+        IF_RANGE,
+        IF_MATCH,
+        IF_NONE_MATCH,
+        IF_MODIFIED_SINCE,
+        IF_UNMODIFIED_SINCE,
+    ]
+    .into_iter()
+    .filter_map(|name| {
+        let value = req.head.headers.get(&name)?.clone();
+        Some((name, value))
+    })
+    .collect::<HeaderMap>();
+
+    let mut response = inner.call(req).await?;
+
+    let head_body = response.extensions_mut().remove::<HeadBody>();
+
+    if response.extensions_mut().remove::<Ranged>().is_none() || response.status() != StatusCode::OK
+    {
+        return Ok(response);
+    }
+
+    #[cfg(feature = "fs")]
+    if let Some(DiskFile(path)) = response.extensions_mut().remove::<DiskFile>() {
+        let (file, meta) = crate::fs::open_file(&path).await?;
+
+        let etag = crate::fs::file_etag(&meta, None);
+        let content = Content::File(file.into_std().await);
+
+        return Ok(respond(
+            &method,
+            &request_headers,
+            response,
+            content,
+            meta.len(),
+            etag,
+            meta.modified().ok(),
+        ));
+    }
+
+    if let Some(HeadBody(body)) = head_body
+        && let Some(body) = body.lock().unwrap().take()
+    {
+        *response.body_mut() = body;
+    }
+
+    // a streamed body is sent as it is, rather than buffered to derive its `ETag` and ranges
+    if response.body().size_hint().exact().is_none() {
+        if method == Method::HEAD {
+            response.body_mut().clear();
+        }
+
+        return Ok(response);
+    }
+
+    let body = std::mem::take(response.body_mut());
+
+    let bytes = body
+        .collect()
+        .await
+        .map_err(|e| Error::from((StatusCode::INTERNAL_SERVER_ERROR, e)))?
+        .to_bytes();
+
+    let etag = bytes_etag(&bytes);
+    let len = bytes.len() as u64;
+
+    Ok(respond(
+        &method,
+        &request_headers,
+        response,
+        Content::Bytes(bytes),
+        len,
+        etag,
+        None,
+    ))
+}
+
 impl<T: MediaType + ResponseMediaType> ApiResponse for Download<T> {
     fn responses(
         schemas: &mut BTreeMap<String, Schema>,
         schemas_in_progress: &mut Vec<String>,
     ) -> Option<BTreeMap<StatusCode, openapi::Response>> {
-        Some(<Self as MultiResponse>::responses(
-            schemas,
-            schemas_in_progress,
-        ))
+        let mut responses = <Self as MultiResponse>::responses(schemas, schemas_in_progress);
+
+        let mut header = |name: HeaderName, required: bool, description: &str| {
+            let header = Header {
+                description: Some(description.to_string()),
+                style: Default::default(),
+                required,
+                deprecated: Default::default(),
+                format: ParameterSchemaOrContent::Schema(<String as ToSchema>::schema_ref(
+                    schemas,
+                    schemas_in_progress,
+                )),
+                example: Default::default(),
+                examples: Default::default(),
+                extensions: Default::default(),
+            };
+
+            (name.as_str().to_string(), ReferenceOr::Item(header))
+        };
+
+        let accept_ranges = header(
+            ACCEPT_RANGES,
+            false,
+            "`bytes`, absent for a body of unknown length",
+        );
+        let etag = header(
+            ETAG,
+            false,
+            "the entity tag of the content, absent for a body of unknown length",
+        );
+        let content_disposition = header(
+            CONTENT_DISPOSITION,
+            true,
+            "`inline` or `attachment`, with the file name",
+        );
+        let content_range = header(
+            CONTENT_RANGE,
+            true,
+            "the range sent, out of the complete length",
+        );
+        let unsatisfied_range = header(CONTENT_RANGE, true, "`bytes */<complete length>`");
+
+        let ok = responses.entry(StatusCode::OK).or_default();
+        ok.headers.extend([
+            content_disposition.clone(),
+            accept_ranges.clone(),
+            etag.clone(),
+        ]);
+
+        let mut partial_content = ok.clone();
+        partial_content.description =
+            "a single range, or `multipart/byteranges` for several ranges".to_string();
+        partial_content
+            .content
+            .insert("multipart/byteranges".to_string(), Default::default());
+        partial_content
+            .headers
+            .extend([content_range, accept_ranges.clone()]);
+
+        let status_only = |status: StatusCode, headers: Vec<_>| openapi::Response {
+            description: status.canonical_reason().unwrap_or_default().to_string(),
+            headers: headers.into_iter().collect(),
+            ..Default::default()
+        };
+
+        responses.insert(StatusCode::PARTIAL_CONTENT, partial_content);
+        responses.insert(
+            StatusCode::NOT_MODIFIED,
+            status_only(StatusCode::NOT_MODIFIED, vec![etag]),
+        );
+        responses.insert(
+            StatusCode::PRECONDITION_FAILED,
+            status_only(StatusCode::PRECONDITION_FAILED, vec![]),
+        );
+        responses.insert(
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            status_only(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                vec![unsatisfied_range, accept_ranges],
+            ),
+        );
+
+        Some(responses)
     }
 }
 
@@ -167,3 +459,87 @@ impl<T: MediaType + ResponseMediaType> SingleResponse for Download<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use http::{
+        HeaderValue, Method, StatusCode,
+        header::{ACCEPT_RANGES, CONTENT_LENGTH, ETAG, IF_NONE_MATCH},
+    };
+    use indexmap::IndexMap;
+    use predawn_core::{error::Error, into_response::IntoResponse};
+
+    use super::{Download, DownloadType, resolve};
+    use crate::{
+        handler::{DynHandler, HandlerExt, handler_fn},
+        route::MethodRouter,
+        server::{Server, bind},
+    };
+
+    async fn serve() -> SocketAddr {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let get = handler_fn(|_| async {
+            Download::attachment(b"hello world".to_vec(), "hello.txt")
+                .unwrap()
+                .into_response()
+                .map_err(Error::from)
+        });
+
+        let mut map = IndexMap::new();
+        map.insert(Method::GET, DynHandler::new(get));
+
+        let router = MethodRouter::from(map).around(resolve);
+
+        tokio::spawn(Server::new(listener).run(router));
+
+        addr
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        let disposition = |ty, file_name: &str| {
+            Download::<()>::content_disposition(ty, &HeaderValue::try_from(file_name).unwrap())
+        };
+
+        let value = disposition(DownloadType::Attachment, "report.pdf");
+        assert_eq!(value, "attachment; filename=\"report.pdf\"");
+
+        let value = disposition(DownloadType::Inline, "报告 2024.pdf");
+        assert_eq!(
+            value,
+            "inline; filename=\"__ 2024.pdf\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A%202024.pdf"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_head_matches_get() {
+        let addr = serve().await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}/");
+
+        let get = client.get(&url).send().await.unwrap();
+        let etag = get.headers()[ETAG].clone();
+
+        assert_eq!(get.headers()[ACCEPT_RANGES], "bytes");
+
+        let head = client.head(&url).send().await.unwrap();
+
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()[ETAG], etag);
+        assert_eq!(head.headers()[ACCEPT_RANGES], "bytes");
+        assert_eq!(head.headers()[CONTENT_LENGTH], "11");
+
+        let head = client
+            .head(&url)
+            .header(IF_NONE_MATCH, etag)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(head.status(), StatusCode::NOT_MODIFIED);
+    }
+}
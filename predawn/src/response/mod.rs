@@ -1,9 +1,19 @@
+pub(crate) mod conditional;
 mod download;
+#[cfg(feature = "cookie")]
+mod set_cookie;
 pub mod sse;
 mod to_header_value;
 
 pub use predawn_core::response::Response;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+#[cfg(feature = "fs")]
+pub use self::download::DiskFile;
+pub(crate) use self::download::{clear_head_body, resolve as resolve_download};
+#[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+#[cfg(feature = "cookie")]
+pub use self::set_cookie::SetCookie;
 pub use self::{
     download::Download,
     to_header_value::{MaybeHeaderValue, ToHeaderValue},
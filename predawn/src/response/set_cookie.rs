@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use http::{HeaderValue, StatusCode, header::SET_COOKIE};
+use predawn_core::{
+    api_response::ApiResponse,
+    into_response::IntoResponse,
+    openapi::{self, Header, ParameterSchemaOrContent, ReferenceOr, Schema},
+    response::Response,
+};
+use predawn_schema::ToSchema;
+
+use crate::extract::cookie::{Cookie, CookieJar};
+
+/// Appends `Set-Cookie` headers to the response of `T`.
+#[derive(Debug)]
+pub struct SetCookie<T> {
+    data: T,
+    cookies: Vec<Cookie<'static>>,
+}
+
+impl<T> SetCookie<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            cookies: Vec::new(),
+        }
+    }
+
+    pub fn cookie<C: Into<Cookie<'static>>>(mut self, cookie: C) -> Self {
+        self.cookies.push(cookie.into());
+        self
+    }
+
+    /// Appends the cookies that were added to or removed from the jar.
+    pub fn jar<J: Into<CookieJar>>(mut self, jar: J) -> Self {
+        self.cookies.extend(jar.into().delta().cloned());
+        self
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for SetCookie<T> {
+    type Error = T::Error;
+
+    fn into_response(self) -> Result<Response, Self::Error> {
+        let SetCookie { data, cookies } = self;
+
+        let mut response = data.into_response()?;
+
+        let headers = response.headers_mut();
+
+        for cookie in cookies {
+            if let Ok(value) = HeaderValue::try_from(cookie.encoded().to_string()) {
+                headers.append(SET_COOKIE, value);
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+impl<T: ApiResponse> ApiResponse for SetCookie<T> {
+    fn responses(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<BTreeMap<StatusCode, openapi::Response>> {
+        let mut responses = T::responses(schemas, schemas_in_progress)?;
+
+        for response in responses.values_mut() {
+            let header = Header {
+                description: Some("cookies set by the server".to_string()),
+                style: Default::default(),
+                required: false,
+                deprecated: Default::default(),
+                format: ParameterSchemaOrContent::Schema(<String as ToSchema>::schema_ref(
+                    schemas,
+                    schemas_in_progress,
+                )),
+                example: Default::default(),
+                examples: Default::default(),
+                extensions: Default::default(),
+            };
+
+            response
+                .headers
+                .insert(SET_COOKIE.as_str().to_string(), ReferenceOr::Item(header));
+        }
+
+        Some(responses)
+    }
+}
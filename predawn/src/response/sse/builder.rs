@@ -2,11 +2,12 @@ use std::{
     marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use bytes::Bytes;
 use futures_core::{Stream, TryStream};
-use futures_util::TryStreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 use http::header::{CACHE_CONTROL, CONTENT_TYPE};
 use pin_project_lite::pin_project;
 use predawn_core::{
@@ -19,6 +20,7 @@ use crate::response_error::EventStreamError;
 
 pub struct EventStreamBuilder<F> {
     pub(crate) keep_alive: Option<KeepAlive>,
+    pub(crate) retry: Option<Duration>,
     pub(crate) _marker: PhantomData<F>,
 }
 
@@ -27,6 +29,12 @@ impl<F> EventStreamBuilder<F> {
         self.keep_alive = Some(keep_alive);
         self
     }
+
+    /// Sets the reconnection time of the stream, sent to the client before the first event.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
 }
 
 impl<F: OnCreateEvent> EventStreamBuilder<F> {
@@ -36,6 +44,7 @@ impl<F: OnCreateEvent> EventStreamBuilder<F> {
     {
         EventStreamBuilder {
             keep_alive: self.keep_alive,
+            retry: self.retry,
             _marker: PhantomData,
         }
     }
@@ -101,17 +110,23 @@ where
         }
     }
 
-    let stream = SseStream {
-        stream: stream.map_err(Into::into).and_then(|item| async move {
-            let item = item.into();
+    let retry = builder
+        .retry
+        .map(|retry| Ok(Event::only_retry(retry).as_bytes()));
 
-            let data = F::data(&item);
+    let events = stream.map_err(Into::into).and_then(|item| async move {
+        let item = item.into();
 
-            let event = Event::data(data).map_err(Box::new)?;
-            let event = F::modify_event(item, event).map_err(Box::new)?;
+        let data = F::data(&item);
 
-            Ok::<_, BoxError>(event.as_bytes())
-        }),
+        let event = Event::data(data).map_err(Box::new)?;
+        let event = F::modify_event(item, event).map_err(Box::new)?;
+
+        Ok::<_, BoxError>(event.as_bytes())
+    });
+
+    let stream = SseStream {
+        stream: futures_util::stream::iter(retry).chain(events),
         keep_alive: builder.keep_alive.map(KeepAliveStream::new).transpose()?,
     };
 
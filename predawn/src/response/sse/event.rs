@@ -52,6 +52,16 @@ impl Event {
         })
     }
 
+    pub(crate) fn only_retry(retry: Duration) -> Self {
+        Self {
+            ty: Default::default(),
+            id: Default::default(),
+            data: Default::default(),
+            comment: Default::default(),
+            retry: Some(retry),
+        }
+    }
+
     pub fn ty<T: Into<Box<str>>>(self, ty: T) -> Result<Self, EventStreamError> {
         fn inner(mut evt: Event, ty: Box<str>) -> Result<Event, EventStreamError> {
             if invalid(ty.as_bytes()) {
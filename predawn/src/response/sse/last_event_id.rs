@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use headers::{Header, HeaderValue};
+use http::HeaderName;
+use predawn_core::{
+    api_request::ApiRequestHead,
+    from_request::{FromRequestHead, OptionalFromRequestHead},
+    impl_deref,
+    openapi::{Parameter, Schema},
+    request::Head,
+};
+use predawn_schema::ToSchema;
+
+use crate::{
+    extract::{HeaderSchema, TypedHeader},
+    response_error::TypedHeaderError,
+};
+
+static LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// The `Last-Event-ID` header, sent by a browser reconnecting to an event stream with the id of
+/// the last event it received.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LastEventId(pub Box<str>);
+
+impl_deref!(LastEventId: Box<str>);
+
+impl LastEventId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Header for LastEventId {
+    fn name() -> &'static HeaderName {
+        &LAST_EVENT_ID
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+
+        let id = value.to_str().map_err(|_| headers::Error::invalid())?;
+
+        Ok(Self(id.into()))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if let Ok(value) = HeaderValue::from_str(&self.0) {
+            values.extend(Some(value));
+        }
+    }
+}
+
+impl FromRequestHead for LastEventId {
+    type Error = TypedHeaderError;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        let TypedHeader(id) =
+            <TypedHeader<Self> as FromRequestHead>::from_request_head(head).await?;
+        Ok(id)
+    }
+}
+
+impl OptionalFromRequestHead for LastEventId {
+    type Error = TypedHeaderError;
+
+    async fn from_request_head(head: &mut Head) -> Result<Option<Self>, Self::Error> {
+        let id = <TypedHeader<Self> as OptionalFromRequestHead>::from_request_head(head).await?;
+        Ok(id.map(|TypedHeader(id)| id))
+    }
+}
+
+impl ApiRequestHead for LastEventId {
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<Parameter>> {
+        let schema = HeaderSchema {
+            schema: <String as ToSchema>::schema_ref(schemas, schemas_in_progress),
+            description: Some(
+                "The id of the last event received, to resume the event stream after it".into(),
+            ),
+            example: Some("42".into()),
+        };
+
+        Some(vec![schema.into_parameter(&LAST_EVENT_ID)])
+    }
+}
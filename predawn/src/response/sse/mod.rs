@@ -1,11 +1,15 @@
 mod builder;
 mod event;
 mod keep_alive;
+mod last_event_id;
+mod replay;
 mod stream;
 
 pub use self::{
     builder::{DefaultOnCreateEvent, EventStreamBuilder, OnCreateEvent},
     event::Event,
     keep_alive::KeepAlive,
+    last_event_id::LastEventId,
+    replay::{EventReplay, MemoryReplayBuffer, ReplayBuffer, ReplayOnCreateEvent, Replayed},
     stream::EventStream,
 };
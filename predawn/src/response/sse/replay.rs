@@ -0,0 +1,339 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use futures_core::Stream;
+use futures_util::{StreamExt, TryStreamExt};
+use predawn_core::error::BoxError;
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{Event, EventStream, LastEventId, OnCreateEvent};
+use crate::response_error::EventStreamError;
+
+/// An event and the id it was stored under by a [`ReplayBuffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replayed<T> {
+    pub id: Box<str>,
+    pub data: T,
+}
+
+/// Keeps the most recent events of an [`EventReplay`], so that they can be sent again to clients
+/// that missed them.
+pub trait ReplayBuffer<T>: Send + Sync + 'static {
+    /// Stores the event and returns its id, which must not contain `\r`, `\n` or `\0`.
+    fn push(&self, data: T) -> impl Future<Output = Result<Box<str>, BoxError>> + Send;
+
+    /// Returns the events stored after the one with `last_event_id`, oldest first.
+    ///
+    /// All stored events are returned if the id is unknown, such as when it has already been evicted.
+    fn since(
+        &self,
+        last_event_id: &str,
+    ) -> impl Future<Output = Result<Vec<Replayed<T>>, BoxError>> + Send;
+}
+
+/// A ring buffer that keeps the last `capacity` events in memory, they are lost when the process exits.
+pub struct MemoryReplayBuffer<T> {
+    capacity: usize,
+    state: Mutex<MemoryState<T>>,
+}
+
+struct MemoryState<T> {
+    next_id: u64,
+    events: VecDeque<(u64, T)>,
+}
+
+impl<T> fmt::Debug for MemoryReplayBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryReplayBuffer")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> MemoryReplayBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            capacity,
+            state: Mutex::new(MemoryState {
+                next_id: 0,
+                events: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+}
+
+impl<T> ReplayBuffer<T> for MemoryReplayBuffer<T>
+where
+    T: Clone + Send + 'static,
+{
+    async fn push(&self, data: T) -> Result<Box<str>, BoxError> {
+        let mut state = self.state.lock().unwrap();
+
+        let id = state.next_id;
+        state.next_id += 1;
+
+        if state.events.len() == self.capacity {
+            state.events.pop_front();
+        }
+
+        state.events.push_back((id, data));
+
+        Ok(id.to_string().into())
+    }
+
+    async fn since(&self, last_event_id: &str) -> Result<Vec<Replayed<T>>, BoxError> {
+        let state = self.state.lock().unwrap();
+
+        let oldest = state.events.front().map_or(state.next_id, |(id, _)| *id);
+
+        let after = match last_event_id.parse::<u64>() {
+            Ok(id) if id < state.next_id && id + 1 >= oldest => id + 1,
+            _ => oldest,
+        };
+
+        let events = state
+            .events
+            .iter()
+            .filter(|(id, _)| *id >= after)
+            .map(|(id, data)| Replayed {
+                id: id.to_string().into(),
+                data: data.clone(),
+            })
+            .collect();
+
+        Ok(events)
+    }
+}
+
+/// The source of a replay-capable event stream: published events are stored in a
+/// [`ReplayBuffer`] and sent to every subscriber, and a subscriber that reconnects with a
+/// [`LastEventId`] is first sent the events it missed.
+///
+/// A subscriber that lags more than `capacity` events behind the live events is disconnected,
+/// a browser then reconnects and resumes from the buffer.
+pub struct EventReplay<T, B = MemoryReplayBuffer<T>> {
+    inner: Arc<ReplayInner<T, B>>,
+}
+
+struct ReplayInner<T, B> {
+    buffer: B,
+    sender: broadcast::Sender<Replayed<T>>,
+    // keeps the events of concurrent publishers in the same order in the buffer and the channel
+    ordering: tokio::sync::Mutex<()>,
+}
+
+impl<T, B> Clone for EventReplay<T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, B: fmt::Debug> fmt::Debug for EventReplay<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventReplay")
+            .field("buffer", &self.inner.buffer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> EventReplay<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Keeps the last `capacity` events in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_buffer(MemoryReplayBuffer::new(capacity), capacity)
+    }
+}
+
+impl<T, B> EventReplay<T, B>
+where
+    T: Clone + Send + 'static,
+    B: ReplayBuffer<T>,
+{
+    pub fn with_buffer(buffer: B, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+
+        Self {
+            inner: Arc::new(ReplayInner {
+                buffer,
+                sender,
+                ordering: Default::default(),
+            }),
+        }
+    }
+
+    /// Stores the event and sends it to every subscriber, returns its id.
+    pub async fn publish(&self, data: T) -> Result<Box<str>, BoxError> {
+        let ReplayInner {
+            buffer,
+            sender,
+            ordering,
+        } = &*self.inner;
+
+        let _guard = ordering.lock().await;
+
+        let id = buffer.push(data.clone()).await?;
+
+        // there may be no subscribers
+        let _ = sender.send(Replayed {
+            id: id.clone(),
+            data,
+        });
+
+        Ok(id)
+    }
+
+    /// The events missed since `last_event_id`, followed by the events published from now on.
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<LastEventId>,
+    ) -> impl Stream<Item = Result<Replayed<T>, BoxError>> + Send + 'static {
+        let inner = self.inner.clone();
+
+        // subscribe before reading the buffer, so that no event is missed in between
+        let receiver = inner.sender.subscribe();
+
+        let missed_and_live = async move {
+            let missed = match last_event_id {
+                Some(id) => inner.buffer.since(id.as_str()).await?,
+                None => Vec::new(),
+            };
+
+            // events published while reading the buffer are received from both
+            let sent = missed
+                .iter()
+                .map(|event| event.id.clone())
+                .collect::<HashSet<_>>();
+
+            let live = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                match receiver.recv().await {
+                    Ok(event) => Some((event, receiver)),
+                    Err(RecvError::Lagged(_) | RecvError::Closed) => None,
+                }
+            })
+            .filter(move |event| std::future::ready(!sent.contains(&event.id)))
+            .map(Ok);
+
+            Ok::<_, BoxError>(futures_util::stream::iter(missed.into_iter().map(Ok)).chain(live))
+        };
+
+        futures_util::stream::once(missed_and_live).try_flatten()
+    }
+
+    /// Sends [`subscribe`](Self::subscribe) as events with ids, use
+    /// [`ReplayOnCreateEvent`] to customize the [`EventStreamBuilder`](super::EventStreamBuilder).
+    pub fn event_stream(&self, last_event_id: Option<LastEventId>) -> EventStream<T>
+    where
+        T: Serialize,
+    {
+        EventStream::builder()
+            .on_create_event::<ReplayOnCreateEvent<T>>()
+            .build(self.subscribe(last_event_id))
+    }
+}
+
+/// Sets the id of the events created from [`Replayed`] items.
+#[derive(Debug)]
+pub struct ReplayOnCreateEvent<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> OnCreateEvent for ReplayOnCreateEvent<T>
+where
+    T: Serialize + Send + 'static,
+{
+    type Data = T;
+    type Item = Replayed<T>;
+
+    fn data(item: &Self::Item) -> &Self::Data {
+        &item.data
+    }
+
+    fn modify_event(item: Self::Item, event: Event) -> Result<Event, EventStreamError> {
+        event.id(item.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, time::Duration};
+
+    use futures_util::StreamExt;
+    use http_body_util::BodyExt;
+    use predawn_core::into_response::IntoResponse;
+
+    use super::{EventReplay, LastEventId, ReplayBuffer, Replayed};
+    use crate::response::sse::EventStream;
+
+    fn replayed(id: &str, data: u32) -> Replayed<u32> {
+        Replayed {
+            id: id.into(),
+            data,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let replay = EventReplay::new(2);
+
+        for data in 0..3 {
+            replay.publish(data).await.unwrap();
+        }
+
+        let buffer = &replay.inner.buffer;
+        assert_eq!(buffer.since("1").await.unwrap(), [replayed("2", 2)]);
+        assert_eq!(buffer.since("2").await.unwrap(), []);
+        assert_eq!(buffer.since("0").await.unwrap().len(), 2);
+        assert_eq!(buffer.since("unknown").await.unwrap().len(), 2);
+
+        let mut resumed = Box::pin(replay.subscribe(Some(LastEventId("1".into()))));
+        let mut fresh = Box::pin(replay.subscribe(None));
+
+        assert_eq!(resumed.next().await.unwrap().unwrap(), replayed("2", 2));
+
+        replay.publish(3).await.unwrap();
+
+        assert_eq!(resumed.next().await.unwrap().unwrap(), replayed("3", 3));
+        assert_eq!(fresh.next().await.unwrap().unwrap(), replayed("3", 3));
+    }
+
+    #[tokio::test]
+    async fn test_event_ids_and_retry() {
+        let replay = EventReplay::new(2);
+        replay.publish("hello").await.unwrap();
+
+        let mut body = replay
+            .event_stream(Some(LastEventId("unknown".into())))
+            .into_response()
+            .unwrap()
+            .into_body();
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), "id: 0\ndata: \"hello\"\n\n");
+
+        let stream = futures_util::stream::iter([Ok::<_, Infallible>(1_u32)]);
+
+        let body = EventStream::<u32>::builder()
+            .retry(Duration::from_secs(3))
+            .build(stream)
+            .into_response()
+            .unwrap()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+
+        assert_eq!(body, "retry: 3000\n\ndata: 1\n\n");
+    }
+}
@@ -35,6 +35,7 @@ impl<T> EventStream<T> {
     pub fn builder() -> EventStreamBuilder<DefaultOnCreateEvent<T>> {
         EventStreamBuilder {
             keep_alive: None,
+            retry: None,
             _marker: PhantomData,
         }
     }
@@ -1,15 +1,32 @@
-use std::{collections::BTreeSet, error::Error, fmt, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
 
 use error2::{ErrorExt, Location, NextError};
-use http::{HeaderName, StatusCode, header::CONTENT_TYPE};
-use predawn_core::media_type::MediaType;
+use http::{HeaderName, HeaderValue, StatusCode, header::CONTENT_TYPE};
+use indexmap::IndexMap;
 pub use predawn_core::response_error::*;
+use predawn_core::{
+    error::BoxError,
+    into_response::IntoResponse,
+    media_type::MediaType,
+    openapi::{self, ObjectType, ReferenceOr, Schema, SchemaKind, Type, merge_responses},
+    problem::{ProblemDetails, problem_details_enabled},
+    response::Response,
+};
+use predawn_schema::ToSchema;
+use serde_json::json;
 use snafu::Snafu;
 
 use crate::{
     extract::multipart::Multipart,
     payload::{Form, Json},
     response::ToHeaderValue,
+    validate::{ValidationError, ValidationErrors},
 };
 
 #[derive(Debug, Snafu)]
@@ -88,6 +105,132 @@ impl ResponseError for QueryError {
     }
 }
 
+#[cfg(feature = "cookie")]
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(display("{source}"))]
+pub struct CookieParamsError {
+    #[snafu(implicit)]
+    location: Location,
+    source: serde_path_to_error::Error<serde_html_form::de::Error>,
+}
+
+#[cfg(feature = "cookie")]
+impl ErrorExt for CookieParamsError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        (self.location, NextError::Std(&self.source))
+    }
+}
+
+#[cfg(feature = "cookie")]
+impl ResponseError for CookieParamsError {
+    fn as_status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::BAD_REQUEST);
+    }
+}
+
+#[cfg(feature = "cookie")]
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(display("not found `CookieKeys` in request extensions"))]
+pub struct MissingCookieKeysError {
+    #[snafu(implicit)]
+    location: Location,
+}
+
+#[cfg(feature = "cookie")]
+impl ErrorExt for MissingCookieKeysError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        (self.location, NextError::None)
+    }
+}
+
+#[cfg(feature = "cookie")]
+impl ResponseError for MissingCookieKeysError {
+    fn as_status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
+#[cfg(feature = "session")]
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(module)]
+pub enum SessionError {
+    #[snafu(display("not found `Session` in request extensions, is `SessionMiddleware` applied?"))]
+    NotFoundSessionError {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("failed to serialize session value `{key}`"))]
+    SerializeValueError {
+        #[snafu(implicit)]
+        location: Location,
+        key: Box<str>,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to deserialize session value `{key}`"))]
+    DeserializeValueError {
+        #[snafu(implicit)]
+        location: Location,
+        key: Box<str>,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to encode session record"))]
+    EncodeRecordError {
+        #[snafu(implicit)]
+        location: Location,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to access session file `{}`", path.display()))]
+    FileError {
+        #[snafu(implicit)]
+        location: Location,
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[cfg(feature = "session")]
+impl ErrorExt for SessionError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            SessionError::NotFoundSessionError { location } => (*location, NextError::None),
+            SessionError::SerializeValueError {
+                location, source, ..
+            }
+            | SessionError::DeserializeValueError {
+                location, source, ..
+            }
+            | SessionError::EncodeRecordError { location, source } => {
+                (*location, NextError::Std(source))
+            }
+            SessionError::FileError {
+                location, source, ..
+            } => (*location, NextError::Std(source)),
+        }
+    }
+}
+
+#[cfg(feature = "session")]
+impl ResponseError for SessionError {
+    fn as_status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
 #[derive(Debug, Snafu, Clone)]
 #[snafu(visibility(pub(crate)))]
 #[snafu(display("invalid UTF-8 in the following path parameters: {keys:?}"))]
@@ -529,6 +672,23 @@ pub enum MultipartError {
         expected: usize,
         actual: usize,
     },
+
+    #[snafu(display("content type `{content_type}` is not allowed for field `{name}`"))]
+    DisallowedContentType {
+        #[snafu(implicit)]
+        location: Location,
+        name: &'static str,
+        content_type: Box<str>,
+    },
+
+    #[cfg(feature = "fs")]
+    #[snafu(display("failed to spool field `{name}` to a temporary file"))]
+    SpoolField {
+        #[snafu(implicit)]
+        location: Location,
+        name: &'static str,
+        source: std::io::Error,
+    },
 }
 
 impl ErrorExt for MultipartError {
@@ -543,13 +703,19 @@ impl ErrorExt for MultipartError {
                 location, source, ..
             } => (*location, NextError::Std(source)),
 
+            #[cfg(feature = "fs")]
+            MultipartError::SpoolField {
+                location, source, ..
+            } => (*location, NextError::Std(source)),
+
             MultipartError::InvalidMultipartContentType { location }
             | MultipartError::DuplicateField { location, .. }
             | MultipartError::ParseErrorAtName { location, .. }
             | MultipartError::MissingField { location, .. }
             | MultipartError::MissingFileName { location, .. }
             | MultipartError::MissingContentType { location, .. }
-            | MultipartError::IncorrectNumberOfFields { location, .. } => {
+            | MultipartError::IncorrectNumberOfFields { location, .. }
+            | MultipartError::DisallowedContentType { location, .. } => {
                 (*location, NextError::None)
             }
         }
@@ -559,9 +725,8 @@ impl ErrorExt for MultipartError {
 impl ResponseError for MultipartError {
     fn as_status(&self) -> StatusCode {
         match self {
-            MultipartError::InvalidMultipartContentType { .. } => {
-                StatusCode::UNSUPPORTED_MEDIA_TYPE
-            }
+            MultipartError::InvalidMultipartContentType { .. }
+            | MultipartError::DisallowedContentType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
 
             MultipartError::ByParseMultipart { source, .. }
             | MultipartError::ByParseField { source, .. } => status_code_from_multer_error(source),
@@ -573,11 +738,15 @@ impl ResponseError for MultipartError {
             | MultipartError::MissingFileName { .. }
             | MultipartError::MissingContentType { .. }
             | MultipartError::IncorrectNumberOfFields { .. } => StatusCode::BAD_REQUEST,
+
+            #[cfg(feature = "fs")]
+            MultipartError::SpoolField { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     fn status_codes(codes: &mut BTreeSet<StatusCode>) {
         DeserializeJsonError::status_codes(codes);
+        ReadBytesError::status_codes(codes);
         codes.insert(StatusCode::UNSUPPORTED_MEDIA_TYPE);
         codes.insert(StatusCode::BAD_REQUEST);
         codes.insert(StatusCode::PAYLOAD_TOO_LARGE);
@@ -605,6 +774,10 @@ fn status_code_from_multer_error(err: &multer::Error) -> StatusCode {
                 return status_code_from_multer_error(err);
             }
 
+            if let Some(err) = err.downcast_ref::<ReadBytesError>() {
+                return err.as_status();
+            }
+
             if err.is::<http_body_util::LengthLimitError>() {
                 return StatusCode::PAYLOAD_TOO_LARGE;
             }
@@ -1011,6 +1184,241 @@ impl ResponseError for RequestBodyLimitError {
     }
 }
 
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(display("the request was not handled within `{timeout:?}`"))]
+pub struct TimeoutError {
+    #[snafu(implicit)]
+    pub location: Location,
+    pub timeout: Duration,
+}
+
+impl ErrorExt for TimeoutError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        (self.location, NextError::None)
+    }
+}
+
+impl ResponseError for TimeoutError {
+    fn as_status(&self) -> StatusCode {
+        StatusCode::GATEWAY_TIMEOUT
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::GATEWAY_TIMEOUT);
+    }
+}
+
+#[cfg(feature = "fs")]
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(module)]
+pub enum ServeFileError {
+    #[snafu(display("file not found"))]
+    NotFoundError {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("failed to read file `{}`", path.display()))]
+    IoError {
+        #[snafu(implicit)]
+        location: Location,
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[cfg(feature = "fs")]
+impl ErrorExt for ServeFileError {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            ServeFileError::NotFoundError { location } => (*location, NextError::None),
+            ServeFileError::IoError {
+                location, source, ..
+            } => (*location, NextError::Std(source)),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl ResponseError for ServeFileError {
+    fn as_status(&self) -> StatusCode {
+        match self {
+            ServeFileError::NotFoundError { .. } => StatusCode::NOT_FOUND,
+            ServeFileError::IoError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        codes.insert(StatusCode::NOT_FOUND);
+        codes.insert(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[snafu(module)]
+pub enum ValidError<E: ResponseError> {
+    #[snafu(display("{source}"))]
+    ExtractError {
+        #[snafu(implicit)]
+        location: Location,
+        source: E,
+    },
+
+    #[snafu(display("validation failed: {errors}"))]
+    InvalidError {
+        #[snafu(implicit)]
+        location: Location,
+        #[snafu(source)]
+        errors: ValidationErrors,
+    },
+}
+
+impl<E: ResponseError> ErrorExt for ValidError<E> {
+    fn entry(&self) -> (Location, NextError<'_>) {
+        match self {
+            ValidError::ExtractError { location, source } => (*location, NextError::Ext(source)),
+            ValidError::InvalidError { location, .. } => (*location, NextError::None),
+        }
+    }
+}
+
+impl<E: ResponseError> ResponseError for ValidError<E> {
+    fn as_status(&self) -> StatusCode {
+        match self {
+            ValidError::ExtractError { source, .. } => source.as_status(),
+            ValidError::InvalidError { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    fn status_codes(codes: &mut BTreeSet<StatusCode>) {
+        E::status_codes(codes);
+        codes.insert(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    fn as_response(&self) -> Response {
+        let errors = match self {
+            ValidError::ExtractError { source, .. } => return source.as_response(),
+            ValidError::InvalidError { errors, .. } => errors,
+        };
+
+        if problem_details_enabled() {
+            return self
+                .problem_details()
+                .into_response()
+                .unwrap_or_else(|a| match a {});
+        }
+
+        let errors = errors.iter().map(validation_error_json).collect::<Vec<_>>();
+
+        Response::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .header(
+                CONTENT_TYPE,
+                HeaderValue::from_static(<Json<()> as MediaType>::MEDIA_TYPE),
+            )
+            .body(json!({ "errors": errors }).to_string().into())
+            .unwrap()
+    }
+
+    fn responses(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> BTreeMap<StatusCode, openapi::Response> {
+        let mut responses = E::responses(schemas, schemas_in_progress);
+
+        let content = if problem_details_enabled() {
+            ProblemDetails::content(Self::problem_details_schema(schemas, schemas_in_progress))
+        } else {
+            let mut content = IndexMap::new();
+            content.insert(
+                <Json<()> as MediaType>::MEDIA_TYPE.to_string(),
+                openapi::MediaType {
+                    schema: Some(validation_errors_schema(schemas, schemas_in_progress)),
+                    ..Default::default()
+                },
+            );
+            content
+        };
+
+        merge_responses(
+            &mut responses,
+            BTreeMap::from([(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                openapi::Response {
+                    description: StatusCode::UNPROCESSABLE_ENTITY
+                        .canonical_reason()
+                        .unwrap_or_default()
+                        .to_string(),
+                    content,
+                    ..Default::default()
+                },
+            )]),
+        );
+
+        responses
+    }
+
+    fn problem_details(&self) -> ProblemDetails {
+        match self {
+            ValidError::ExtractError { source, .. } => source.problem_details(),
+            ValidError::InvalidError { errors, .. } => errors.iter().fold(
+                ProblemDetails::new(StatusCode::UNPROCESSABLE_ENTITY).detail("validation failed"),
+                |problem, error| problem.error(validation_error_json(error)),
+            ),
+        }
+    }
+
+    fn problem_details_schema(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> ReferenceOr<Schema> {
+        ReferenceOr::Item(Schema {
+            schema_data: Default::default(),
+            schema_kind: SchemaKind::AllOf {
+                all_of: vec![
+                    ProblemDetails::schema_ref(schemas, schemas_in_progress),
+                    validation_errors_schema(schemas, schemas_in_progress),
+                ],
+            },
+        })
+    }
+
+    #[doc(hidden)]
+    fn inner(self) -> BoxError {
+        match self {
+            ValidError::ExtractError { source, .. } => source.inner(),
+            error @ ValidError::InvalidError { .. } => Box::new(error),
+        }
+    }
+}
+
+fn validation_error_json(error: &ValidationError) -> serde_json::Value {
+    json!({
+        "path": error.path,
+        "keyword": error.keyword,
+        "message": error.message,
+    })
+}
+
+fn validation_errors_schema(
+    schemas: &mut BTreeMap<String, Schema>,
+    schemas_in_progress: &mut Vec<String>,
+) -> ReferenceOr<Schema> {
+    let mut obj = ObjectType::default();
+    obj.required.push("errors".to_string());
+    obj.properties.insert(
+        "errors".to_string(),
+        <Vec<ValidationError> as ToSchema>::schema_ref_box(schemas, schemas_in_progress),
+    );
+
+    ReferenceOr::Item(Schema {
+        schema_data: Default::default(),
+        schema_kind: SchemaKind::Type(Type::Object(obj)),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
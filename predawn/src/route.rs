@@ -8,6 +8,7 @@ use snafu::ResultExt;
 use crate::{
     handler::{DynHandler, Handler},
     path_params::PathParams,
+    response::clear_head_body,
     response_error::{MatchSnafu, MethodNotAllowedSnafu},
 };
 
@@ -37,7 +38,7 @@ impl Handler for MethodRouter {
                     Either::Right(
                         async move {
                             let mut response = self.call(req).await?;
-                            response.body_mut().clear();
+                            clear_head_body(&mut response);
                             Ok(response)
                         }
                         .boxed(),
@@ -0,0 +1,498 @@
+#[cfg(feature = "tls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+pub mod tls;
+
+use std::{convert::Infallible, future, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use http::Extensions;
+use hyper::{body::Incoming, service::service_fn};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto::Builder,
+};
+use predawn_core::{
+    body::ResponseBody,
+    request::{LocalAddr, RemoteAddr, Request},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpSocket, TcpStream},
+    signal,
+    sync::{
+        OwnedSemaphorePermit, Semaphore,
+        watch::{self, Receiver, Sender},
+    },
+};
+
+use crate::{
+    config::server::{Http1Config, Http2Config, HttpProtocol},
+    handler::Handler,
+};
+
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Binds a listener to `addr`, setting `SO_REUSEPORT` on it if `reuse_port` is true.
+pub fn bind(addr: SocketAddr, reuse_port: bool) -> io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+
+    // the same as `TcpListener::bind`
+    #[cfg(unix)]
+    socket.set_reuseaddr(true)?;
+
+    if reuse_port {
+        set_reuse_port(&socket)?;
+    }
+
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "cygwin",
+        target_os = "nuttx"
+    ))
+))]
+fn set_reuse_port(socket: &TcpSocket) -> io::Result<()> {
+    socket.set_reuseport(true)
+}
+
+#[cfg(not(all(
+    unix,
+    not(any(
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "cygwin",
+        target_os = "nuttx"
+    ))
+)))]
+fn set_reuse_port(_: &TcpSocket) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "`SO_REUSEPORT` is not supported on this platform",
+    ))
+}
+
+pub struct Server {
+    tcp_listener: TcpListener,
+    builder: Builder<TokioExecutor>,
+    max_connections: Option<usize>,
+    tcp_nodelay: bool,
+    #[cfg(feature = "tls")]
+    tls: Option<tls::RustlsConfig>,
+}
+
+impl Server {
+    pub fn new(tcp_listener: TcpListener) -> Self {
+        let mut builder = Builder::new(TokioExecutor::new());
+
+        builder
+            .http1()
+            .timer(TokioTimer::new())
+            .header_read_timeout(None);
+        builder.http2().timer(TokioTimer::new());
+
+        Self {
+            tcp_listener,
+            builder,
+            max_connections: None,
+            tcp_nodelay: false,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    pub fn protocol(mut self, protocol: HttpProtocol) -> Self {
+        self.builder = match protocol {
+            HttpProtocol::Auto => self.builder,
+            HttpProtocol::Http1 => self.builder.http1_only(),
+            HttpProtocol::Http2 => self.builder.http2_only(),
+        };
+
+        self
+    }
+
+    pub fn http1(mut self, config: &Http1Config) -> Self {
+        let Http1Config {
+            keep_alive,
+            header_read_timeout,
+            max_headers,
+            max_buf_size,
+            half_close,
+        } = *config;
+
+        let mut http1 = self.builder.http1();
+
+        http1
+            .keep_alive(keep_alive)
+            .header_read_timeout(header_read_timeout)
+            .half_close(half_close);
+
+        if let Some(max_headers) = max_headers {
+            http1.max_headers(max_headers);
+        }
+
+        if let Some(max_buf_size) = max_buf_size {
+            http1.max_buf_size(max_buf_size);
+        }
+
+        self
+    }
+
+    pub fn http2(mut self, config: &Http2Config) -> Self {
+        let Http2Config {
+            max_concurrent_streams,
+            initial_stream_window_size,
+            initial_connection_window_size,
+            adaptive_window,
+            max_frame_size,
+            max_header_list_size,
+            keep_alive_interval,
+            keep_alive_timeout,
+        } = *config;
+
+        let mut http2 = self.builder.http2();
+
+        http2
+            .initial_stream_window_size(initial_stream_window_size)
+            .initial_connection_window_size(initial_connection_window_size)
+            .adaptive_window(adaptive_window)
+            .max_frame_size(max_frame_size)
+            .keep_alive_interval(keep_alive_interval)
+            .keep_alive_timeout(keep_alive_timeout);
+
+        // `None` would remove the default limits
+        if let Some(max_concurrent_streams) = max_concurrent_streams {
+            http2.max_concurrent_streams(max_concurrent_streams);
+        }
+
+        if let Some(max_header_list_size) = max_header_list_size {
+            http2.max_header_list_size(max_header_list_size);
+        }
+
+        self
+    }
+
+    /// Connections beyond `max` are not accepted until others are closed.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.tcp_nodelay = nodelay;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+    pub fn tls(mut self, config: tls::RustlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    pub async fn run<H>(self, handler: H) -> io::Result<()>
+    where
+        H: Handler,
+    {
+        self.run_with_graceful_shutdown(handler, future::pending::<()>())
+            .await
+    }
+
+    pub async fn run_with_graceful_shutdown<H, S>(self, handler: H, signal: S) -> io::Result<()>
+    where
+        H: Handler,
+        S: Future<Output = ()> + Send + 'static,
+    {
+        let Self {
+            tcp_listener,
+            builder,
+            max_connections,
+            tcp_nodelay,
+            #[cfg(feature = "tls")]
+            tls,
+        } = self;
+        let local_addr = LocalAddr(tcp_listener.local_addr()?);
+        let handler = Arc::new(handler);
+        let builder = Arc::new(builder);
+        let connections = max_connections.map(|max| Arc::new(Semaphore::new(max)));
+
+        tracing::info!("listening {}", local_addr);
+
+        let (signal_sender, signal_receiver) = watch::channel(());
+
+        tokio::spawn(async move {
+            signal.await;
+            tracing::info!("received graceful shutdown signal. Telling tasks to shutdown");
+            drop(signal_receiver);
+        });
+
+        let (close_sender, close_receiver) = watch::channel(());
+
+        loop {
+            tokio::select! {
+                conn = tcp_accept(&tcp_listener, tcp_nodelay, connections.as_ref()) => {
+                    match conn {
+                        Some((tcp_stream, remote_addr, permit)) => handle_conn(
+                            tcp_stream,
+                            local_addr,
+                            remote_addr,
+                            #[cfg(feature = "tls")]
+                            tls.clone(),
+                            builder.clone(),
+                            signal_sender.clone(),
+                            (close_receiver.clone(), permit),
+                            handler.clone()
+                        ),
+                        None => continue,
+                    }
+                }
+                _ = signal_sender.closed() => {
+                    tracing::info!("signal received, not accepting new connections");
+                    break;
+                }
+            }
+        }
+
+        drop(close_receiver);
+        drop(tcp_listener);
+
+        tracing::info!(
+            "waiting for {} task(s) to finish",
+            close_sender.receiver_count()
+        );
+        close_sender.closed().await;
+
+        Ok(())
+    }
+}
+
+fn is_connection_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+async fn tcp_accept(
+    listener: &TcpListener,
+    tcp_nodelay: bool,
+    connections: Option<&Arc<Semaphore>>,
+) -> Option<(TcpStream, RemoteAddr, Option<OwnedSemaphorePermit>)> {
+    let permit = match connections {
+        // the semaphore is never closed
+        Some(connections) => Some(connections.clone().acquire_owned().await.ok()?),
+        None => None,
+    };
+
+    match listener.accept().await {
+        Ok((tcp_stream, remote_addr)) => {
+            if tcp_nodelay && let Err(e) = tcp_stream.set_nodelay(true) {
+                tracing::debug!("failed to set TCP_NODELAY for {remote_addr}: {e}");
+            }
+
+            Some((tcp_stream, RemoteAddr(remote_addr), permit))
+        }
+        Err(e) => {
+            if is_connection_error(&e) {
+                return None;
+            }
+
+            tracing::error!("accept error: {e}");
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            None
+        }
+    }
+}
+
+/// `close_guard` is held until the connection is closed.
+#[allow(clippy::too_many_arguments)]
+fn handle_conn<H: Handler + Clone>(
+    tcp_stream: TcpStream,
+    local_addr: LocalAddr,
+    remote_addr: RemoteAddr,
+    #[cfg(feature = "tls")] tls: Option<tls::RustlsConfig>,
+    builder: Arc<Builder<TokioExecutor>>,
+    signal_sender: Sender<()>,
+    close_guard: (Receiver<()>, Option<OwnedSemaphorePermit>),
+    handler: H,
+) {
+    tracing::trace!("connection {remote_addr} accepted");
+
+    tokio::spawn(async move {
+        #[cfg(feature = "tls")]
+        if let Some(tls) = tls {
+            match tls.accept(tcp_stream).await {
+                Ok((tls_stream, extensions)) => {
+                    serve_conn(
+                        tls_stream,
+                        extensions,
+                        local_addr,
+                        remote_addr,
+                        &builder,
+                        signal_sender,
+                        handler,
+                    )
+                    .await
+                }
+                Err(e) => tracing::debug!("tls handshake with {remote_addr} failed: {e}"),
+            }
+
+            drop(close_guard);
+            return;
+        }
+
+        serve_conn(
+            tcp_stream,
+            Extensions::new(),
+            local_addr,
+            remote_addr,
+            &builder,
+            signal_sender,
+            handler,
+        )
+        .await;
+
+        drop(close_guard);
+    });
+}
+
+async fn serve_conn<I, H>(
+    io: I,
+    extensions: Extensions,
+    local_addr: LocalAddr,
+    remote_addr: RemoteAddr,
+    builder: &Builder<TokioExecutor>,
+    signal_sender: Sender<()>,
+    handler: H,
+) where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    H: Handler + Clone,
+{
+    let io = TokioIo::new(io);
+
+    let conn = builder.serve_connection_with_upgrades(
+        io,
+        service_fn(|mut request: http::Request<Incoming>| {
+            let handler = handler.clone();
+
+            if !extensions.is_empty() {
+                request.extensions_mut().extend(extensions.clone());
+            }
+
+            async move {
+                Ok::<http::Response<ResponseBody>, Infallible>(
+                    handler
+                        .call(Request::new(request, local_addr, remote_addr))
+                        .await
+                        .unwrap_or_else(|e| e.response()),
+                )
+            }
+        }),
+    );
+    let mut conn = std::pin::pin!(conn);
+
+    tokio::select! {
+        _ = conn.as_mut() => {
+        }
+        _ = signal_sender.closed() => {
+            tracing::info!("signal received in task, starting graceful shutdown");
+            conn.as_mut().graceful_shutdown();
+            // This `conn` should continue to be polled until shutdown can finish.
+            let _ = conn.as_mut().await;
+        }
+    }
+
+    tracing::trace!("connection {remote_addr} closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use predawn_core::error::Error;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::timeout,
+    };
+
+    use super::{Server, bind};
+    use crate::handler::handler_fn;
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_bind_reuse_port() {
+        let listener = bind(([127, 0, 0, 1], 0).into(), true).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        assert!(bind(addr, true).is_ok());
+        assert!(bind(addr, false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_connections() {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = Server::new(listener).max_connections(1).tcp_nodelay(true);
+        let handler = handler_fn(|_| async { Ok::<_, Error>("hello") });
+        tokio::spawn(server.run(handler));
+
+        async fn request(stream: &mut TcpStream) -> String {
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut buf = [0; 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        }
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        assert!(request(&mut first).await.ends_with("hello"));
+
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let mut second = Box::pin(async move { request(&mut second).await });
+        assert!(
+            timeout(Duration::from_millis(200), second.as_mut())
+                .await
+                .is_err()
+        );
+
+        drop(first);
+        assert!(second.await.ends_with("hello"));
+    }
+}
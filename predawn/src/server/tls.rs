@@ -0,0 +1,587 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt, io,
+    path::Path,
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, SystemTime},
+};
+
+use http::Extensions;
+use predawn_core::{
+    api_request::ApiRequestHead,
+    from_request::OptionalFromRequestHead,
+    openapi::{Parameter, Schema},
+    request::Head,
+};
+use tokio::net::TcpStream;
+pub use tokio_rustls::rustls;
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        RootCertStore, ServerConfig,
+        crypto::ring,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+        server::WebPkiClientVerifier,
+    },
+    server::TlsStream,
+};
+
+use crate::config::server::{HttpProtocol, TlsConfig};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The certificate chain presented by the client, only available when client authentication is enabled.
+#[derive(Debug, Clone)]
+pub struct PeerCertificates(pub Arc<[CertificateDer<'static>]>);
+
+impl std::ops::Deref for PeerCertificates {
+    type Target = [CertificateDer<'static>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl OptionalFromRequestHead for PeerCertificates {
+    type Error = Infallible;
+
+    async fn from_request_head(head: &mut Head) -> Result<Option<Self>, Self::Error> {
+        Ok(head.extensions.get::<Self>().cloned())
+    }
+}
+
+impl ApiRequestHead for PeerCertificates {
+    fn parameters(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Option<Vec<Parameter>> {
+        None
+    }
+}
+
+/// A rustls server config that can be swapped at runtime.
+#[derive(Clone)]
+pub struct RustlsConfig {
+    inner: Arc<RwLock<Arc<ServerConfig>>>,
+    source: Option<Arc<TlsConfig>>,
+    protocol: HttpProtocol,
+}
+
+impl fmt::Debug for RustlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RustlsConfig")
+            .field("source", &self.source)
+            .field("protocol", &self.protocol)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RustlsConfig {
+    /// Loads the certificates described by `config`, advertising the ALPN protocols allowed by
+    /// `protocol`.
+    ///
+    /// If `config.watch` is `true`, a background task is spawned which reloads the certificates
+    /// when the files are modified, so this must be called within a tokio runtime.
+    pub fn load(config: TlsConfig, protocol: HttpProtocol) -> io::Result<Self> {
+        // read before loading, so that a change made in the meantime is picked up by the watcher
+        let watch = config
+            .watch
+            .then(|| (config.watch_interval, modified_times(&config)));
+
+        let server_config = load_server_config(&config, protocol)?;
+
+        let this = Self {
+            inner: Arc::new(RwLock::new(Arc::new(server_config))),
+            source: Some(Arc::new(config)),
+            protocol,
+        };
+
+        if let Some((interval, last_modified)) = watch {
+            this.spawn_watcher(interval, last_modified);
+        }
+
+        Ok(this)
+    }
+
+    pub fn from_server_config(config: Arc<ServerConfig>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(config)),
+            source: None,
+            protocol: HttpProtocol::Auto,
+        }
+    }
+
+    pub fn get(&self) -> Arc<ServerConfig> {
+        self.inner.read().unwrap().clone()
+    }
+
+    pub fn set(&self, config: Arc<ServerConfig>) {
+        *self.inner.write().unwrap() = config;
+    }
+
+    /// Reloads the certificates from the files they were originally loaded from, on a blocking thread.
+    ///
+    /// Does nothing if this config was created by [`RustlsConfig::from_server_config`].
+    pub async fn reload(&self) -> io::Result<()> {
+        let Some(source) = self.source.clone() else {
+            return Ok(());
+        };
+
+        let protocol = self.protocol;
+
+        let server_config =
+            tokio::task::spawn_blocking(move || load_server_config(&source, protocol))
+                .await
+                .map_err(io::Error::other)??;
+
+        self.set(Arc::new(server_config));
+
+        Ok(())
+    }
+
+    fn spawn_watcher(&self, interval: Duration, mut last_modified: [Option<SystemTime>; 3]) {
+        let Some(source) = self.source.clone() else {
+            return;
+        };
+
+        let weak = Arc::downgrade(&self.inner);
+        let protocol = self.protocol;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let Some(inner) = Weak::upgrade(&weak) else {
+                    break;
+                };
+
+                let modified = {
+                    let source = source.clone();
+                    tokio::task::spawn_blocking(move || modified_times(&source)).await
+                };
+
+                let Ok(modified) = modified else {
+                    continue;
+                };
+
+                if modified == last_modified {
+                    continue;
+                }
+
+                let this = RustlsConfig {
+                    inner,
+                    source: Some(source.clone()),
+                    protocol,
+                };
+
+                match this.reload().await {
+                    Ok(()) => {
+                        tracing::info!("reloaded tls certificates");
+                        last_modified = modified;
+                    }
+                    Err(e) => tracing::error!("failed to reload tls certificates: {e}"),
+                }
+            }
+        });
+    }
+
+    pub(crate) async fn accept(
+        &self,
+        tcp_stream: TcpStream,
+    ) -> io::Result<(TlsStream<TcpStream>, Extensions)> {
+        let acceptor = TlsAcceptor::from(self.get());
+
+        let tls_stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(tcp_stream))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "tls handshake timed out"))??;
+
+        let mut extensions = Extensions::new();
+
+        if let Some(certs) = tls_stream.get_ref().1.peer_certificates() {
+            extensions.insert(PeerCertificates(certs.iter().cloned().collect()));
+        }
+
+        Ok((tls_stream, extensions))
+    }
+}
+
+fn modified_times(config: &TlsConfig) -> [Option<SystemTime>; 3] {
+    fn modified(path: Option<&Path>) -> Option<SystemTime> {
+        std::fs::metadata(path?).ok()?.modified().ok()
+    }
+
+    [
+        modified(Some(&config.cert)),
+        modified(Some(&config.key)),
+        modified(config.client_ca.as_deref()),
+    ]
+}
+
+fn invalid_data<E: fmt::Display>(path: &Path) -> impl FnOnce(E) -> io::Error {
+    move |e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("failed to load `{}`: {e}", path.display()),
+        )
+    }
+}
+
+fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
+    CertificateDer::pem_file_iter(path)
+        .map_err(invalid_data(path))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid_data(path))
+}
+
+fn alpn_protocols(protocol: HttpProtocol) -> Vec<Vec<u8>> {
+    match protocol {
+        HttpProtocol::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        HttpProtocol::Http1 => vec![b"http/1.1".to_vec()],
+        HttpProtocol::Http2 => vec![b"h2".to_vec()],
+    }
+}
+
+fn load_server_config(config: &TlsConfig, protocol: HttpProtocol) -> io::Result<ServerConfig> {
+    let certs = load_certs(&config.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&config.key).map_err(invalid_data(&config.key))?;
+
+    let provider = Arc::new(ring::default_provider());
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let builder = match &config.client_ca {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+
+            for cert in load_certs(path)? {
+                roots.add(cert).map_err(invalid_data(path))?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+
+            let verifier = if config.client_auth_optional {
+                verifier.allow_unauthenticated()
+            } else {
+                verifier
+            };
+
+            builder.with_client_cert_verifier(verifier.build().map_err(invalid_data(path))?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .map_err(invalid_data(&config.cert))?;
+
+    server_config.alpn_protocols = alpn_protocols(protocol);
+
+    Ok(server_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Duration,
+    };
+
+    use predawn_core::{error::Error, request::Request};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+    use tokio_rustls::{
+        TlsConnector,
+        rustls::{
+            ClientConfig, RootCertStore,
+            crypto::ring,
+            pki_types::{CertificateDer, PrivateKeyDer, ServerName, pem::PemObject},
+        },
+    };
+
+    use super::{PeerCertificates, RustlsConfig};
+    use crate::{
+        config::server::{HttpProtocol, TlsConfig},
+        handler::handler_fn,
+        server::{Server, bind},
+    };
+
+    fn testdata(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/server/testdata")
+            .join(name)
+    }
+
+    fn tls_config() -> TlsConfig {
+        TlsConfig {
+            cert: testdata("server.pem"),
+            key: testdata("server.key"),
+            client_ca: None,
+            client_auth_optional: false,
+            watch: false,
+            watch_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// A copy of the server certificate and key that the test may overwrite.
+    fn copied_tls_config() -> TlsConfig {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let dir = std::env::temp_dir().join(format!(
+            "predawn-tls-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = tls_config();
+        std::fs::copy(&config.cert, dir.join("server.pem")).unwrap();
+        std::fs::copy(&config.key, dir.join("server.key")).unwrap();
+
+        TlsConfig {
+            cert: dir.join("server.pem"),
+            key: dir.join("server.key"),
+            ..config
+        }
+    }
+
+    fn cert_der(path: &Path) -> CertificateDer<'static> {
+        CertificateDer::from_pem_file(path).unwrap()
+    }
+
+    fn connector(alpn: &[&[u8]], client_auth: bool) -> TlsConnector {
+        let mut roots = RootCertStore::empty();
+        roots.add(cert_der(&testdata("ca.pem"))).unwrap();
+
+        let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots);
+
+        let mut config = if client_auth {
+            let key = PrivateKeyDer::from_pem_file(testdata("client.key")).unwrap();
+
+            builder
+                .with_client_auth_cert(vec![cert_der(&testdata("client.pem"))], key)
+                .unwrap()
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+
+        TlsConnector::from(Arc::new(config))
+    }
+
+    /// Responds with the number of certificates presented by the client.
+    fn serve(config: RustlsConfig, protocol: HttpProtocol) -> SocketAddr {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = Server::new(listener).protocol(protocol).tls(config);
+
+        let handler = handler_fn(|req: Request| async move {
+            let certs = req
+                .head
+                .extensions
+                .get::<PeerCertificates>()
+                .map_or(0, |certs| certs.len());
+
+            Ok::<_, Error>(certs.to_string())
+        });
+
+        tokio::spawn(server.run(handler));
+
+        addr
+    }
+
+    /// Returns the certificate of the server and the body of a `GET /` over HTTP/1.1, `None` if
+    /// the handshake or the request fails.
+    async fn get(
+        addr: SocketAddr,
+        connector: &TlsConnector,
+    ) -> Option<(CertificateDer<'static>, String)> {
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let server_name = ServerName::try_from("localhost").unwrap();
+
+        let mut tls_stream = connector.connect(server_name, tcp_stream).await.ok()?;
+        let cert = tls_stream.get_ref().1.peer_certificates()?[0].clone();
+
+        tls_stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .ok()?;
+
+        // the buffer keeps what was read before an unclean close
+        let mut buf = Vec::new();
+        let _ = tls_stream.read_to_end(&mut buf).await;
+
+        let response = String::from_utf8(buf).ok()?;
+
+        if !response.starts_with("HTTP/1.1 200") {
+            return None;
+        }
+
+        let (_, body) = response.split_once("\r\n\r\n")?;
+
+        Some((cert, body.to_string()))
+    }
+
+    async fn negotiate(protocol: HttpProtocol, alpn: &[&[u8]]) -> Option<Vec<u8>> {
+        let addr = serve(
+            RustlsConfig::load(tls_config(), protocol).unwrap(),
+            protocol,
+        );
+
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let server_name = ServerName::try_from("localhost").unwrap();
+
+        let tls_stream = connector(alpn, false)
+            .connect(server_name, tcp_stream)
+            .await
+            .ok()?;
+
+        Some(
+            tls_stream
+                .get_ref()
+                .1
+                .alpn_protocol()
+                .unwrap_or_default()
+                .to_vec(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_handshake() {
+        let config = tls_config();
+        let server_cert = cert_der(&config.cert);
+
+        let addr = serve(
+            RustlsConfig::load(config, HttpProtocol::Auto).unwrap(),
+            HttpProtocol::Auto,
+        );
+
+        let response = get(addr, &connector(&[b"http/1.1"], false)).await;
+        assert_eq!(response, Some((server_cert, "0".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_client_auth() {
+        let config = |client_auth_optional| TlsConfig {
+            client_ca: Some(testdata("ca.pem")),
+            client_auth_optional,
+            ..tls_config()
+        };
+
+        let with_cert = connector(&[b"http/1.1"], true);
+        let without_cert = connector(&[b"http/1.1"], false);
+
+        let required = serve(
+            RustlsConfig::load(config(false), HttpProtocol::Auto).unwrap(),
+            HttpProtocol::Auto,
+        );
+
+        let body = get(required, &with_cert).await.map(|(_, body)| body);
+        assert_eq!(body.as_deref(), Some("1"));
+        assert_eq!(get(required, &without_cert).await, None);
+
+        let optional = serve(
+            RustlsConfig::load(config(true), HttpProtocol::Auto).unwrap(),
+            HttpProtocol::Auto,
+        );
+
+        let body = get(optional, &with_cert).await.map(|(_, body)| body);
+        assert_eq!(body.as_deref(), Some("1"));
+
+        let body = get(optional, &without_cert).await.map(|(_, body)| body);
+        assert_eq!(body.as_deref(), Some("0"));
+    }
+
+    #[tokio::test]
+    async fn test_alpn_follows_protocol() {
+        let both: &[&[u8]] = &[b"h2", b"http/1.1"];
+
+        assert_eq!(
+            negotiate(HttpProtocol::Auto, both).await.as_deref(),
+            Some(&b"h2"[..])
+        );
+        assert_eq!(
+            negotiate(HttpProtocol::Http1, both).await.as_deref(),
+            Some(&b"http/1.1"[..])
+        );
+        assert_eq!(
+            negotiate(HttpProtocol::Http2, both).await.as_deref(),
+            Some(&b"h2"[..])
+        );
+
+        assert_eq!(negotiate(HttpProtocol::Http1, &[b"h2"]).await, None);
+        assert_eq!(negotiate(HttpProtocol::Http2, &[b"http/1.1"]).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let config = copied_tls_config();
+        let cert_path = config.cert.clone();
+
+        let rustls_config = RustlsConfig::load(config, HttpProtocol::Http1).unwrap();
+        let addr = serve(rustls_config.clone(), HttpProtocol::Http1);
+        let connector = connector(&[b"http/1.1"], false);
+
+        let (cert, _) = get(addr, &connector).await.unwrap();
+        assert_eq!(cert, cert_der(&testdata("server.pem")));
+
+        std::fs::copy(testdata("server2.pem"), &cert_path).unwrap();
+        rustls_config.reload().await.unwrap();
+
+        let (cert, _) = get(addr, &connector).await.unwrap();
+        assert_eq!(cert, cert_der(&testdata("server2.pem")));
+
+        assert_eq!(
+            rustls_config.get().alpn_protocols,
+            vec![b"http/1.1".to_vec()]
+        );
+
+        std::fs::remove_dir_all(cert_path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let config = TlsConfig {
+            watch: true,
+            watch_interval: Duration::from_millis(20),
+            ..copied_tls_config()
+        };
+        let cert_path = config.cert.clone();
+
+        let addr = serve(
+            RustlsConfig::load(config, HttpProtocol::Auto).unwrap(),
+            HttpProtocol::Auto,
+        );
+        let connector = connector(&[b"http/1.1"], false);
+
+        std::fs::copy(testdata("server2.pem"), &cert_path).unwrap();
+
+        let reloaded = cert_der(&testdata("server2.pem"));
+        let mut cert = None;
+
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+
+            cert = get(addr, &connector).await.map(|(cert, _)| cert);
+
+            if cert.as_ref() == Some(&reloaded) {
+                break;
+            }
+        }
+
+        assert_eq!(cert, Some(reloaded));
+
+        std::fs::remove_dir_all(cert_path.parent().unwrap()).unwrap();
+    }
+}
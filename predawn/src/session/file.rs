@@ -0,0 +1,197 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use predawn_core::error::Error;
+use snafu::ResultExt;
+
+use super::{Record, SessionId, SessionStore};
+use crate::response_error::{SessionError, session_error};
+
+/// Keeps every session as a JSON file named after its id in a directory.
+///
+/// The directory is created on the first write.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, id: &SessionId) -> PathBuf {
+        // `SessionId` only contains hex digits, so it can not escape the directory
+        self.dir.join(format!("{}.json", id.as_str()))
+    }
+}
+
+fn file_error(path: &Path) -> session_error::FileSnafu<&Path> {
+    session_error::FileSnafu { path }
+}
+
+async fn read_record(path: &Path) -> Result<Option<Record>, SessionError> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context(file_error(path)),
+    };
+
+    match serde_json::from_slice(&bytes) {
+        Ok(record) => Ok(Some(record)),
+        Err(e) => {
+            tracing::warn!("ignoring corrupt session file `{}`: {e}", path.display());
+            Ok(None)
+        }
+    }
+}
+
+async fn remove_file(path: &Path) -> Result<(), SessionError> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e).context(file_error(path)),
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileStore {
+    async fn load(&self, id: &SessionId) -> Result<Option<Record>, Error> {
+        Ok(read_record(&self.path(id)).await?)
+    }
+
+    async fn save(&self, id: &SessionId, record: &Record) -> Result<(), Error> {
+        let bytes = serde_json::to_vec(record).context(session_error::EncodeRecordSnafu)?;
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .context(file_error(&self.dir))?;
+
+        // write to a temporary file first, so that a concurrent `load` never sees a partial record,
+        // named uniquely so that concurrent saves of the same session do not share it
+        static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+
+        let path = self.path(id);
+        let tmp = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        tokio::fs::write(&tmp, bytes)
+            .await
+            .context(file_error(&tmp))?;
+
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e).context(file_error(&path))?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &SessionId) -> Result<(), Error> {
+        Ok(remove_file(&self.path(id)).await?)
+    }
+
+    async fn delete_expired(&self) -> Result<(), Error> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).context(file_error(&self.dir))?,
+        };
+
+        let now = SystemTime::now();
+
+        while let Some(entry) = entries.next_entry().await.context(file_error(&self.dir))? {
+            let path = entry.path();
+
+            let is_session = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(SessionId::parse)
+                .is_some()
+                && path.extension().is_some_and(|ext| ext == "json");
+
+            if !is_session {
+                continue;
+            }
+
+            match read_record(&path).await? {
+                Some(record) if !record.is_expired(now) => {}
+                _ => remove_file(&path).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::FileStore;
+    use crate::session::{Record, SessionId, SessionStore};
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("predawn-session-{}", std::process::id()));
+        let store = FileStore::new(&dir);
+
+        let now = SystemTime::now();
+        let mut record = Record::new(now);
+        record.data.insert("user".into(), 1.into());
+        record.touch(now, Duration::from_secs(60), None);
+
+        let alive = SessionId::generate();
+        let expired = SessionId::generate();
+
+        store.save(&alive, &record).await.unwrap();
+        assert_eq!(store.load(&alive).await.unwrap(), Some(record.clone()));
+
+        record.expires_at = now - Duration::from_secs(1);
+        store.save(&expired, &record).await.unwrap();
+
+        store.delete_expired().await.unwrap();
+        assert!(store.load(&alive).await.unwrap().is_some());
+        assert!(store.load(&expired).await.unwrap().is_none());
+
+        store.delete(&alive).await.unwrap();
+        assert!(store.load(&alive).await.unwrap().is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_saves() {
+        let dir =
+            std::env::temp_dir().join(format!("predawn-session-concurrent-{}", std::process::id()));
+        let store = FileStore::new(&dir);
+
+        let now = SystemTime::now();
+        let id = SessionId::generate();
+
+        let saves = (0..16).map(|n| {
+            let mut record = Record::new(now);
+            record.data.insert("n".into(), n.into());
+            record.touch(now, Duration::from_secs(60), None);
+
+            let (store, id) = (store.clone(), id.clone());
+            tokio::spawn(async move { store.save(&id, &record).await })
+        });
+
+        for save in saves.collect::<Vec<_>>() {
+            save.await.unwrap().unwrap();
+        }
+
+        assert!(store.load(&id).await.unwrap().is_some());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, sync::RwLock, time::SystemTime};
+
+use async_trait::async_trait;
+use predawn_core::error::Error;
+
+use super::{Record, SessionId, SessionStore};
+
+/// Keeps sessions in memory, they are lost when the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    records: RwLock<HashMap<SessionId, Record>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &SessionId) -> Result<Option<Record>, Error> {
+        Ok(self.records.read().unwrap().get(id).cloned())
+    }
+
+    async fn save(&self, id: &SessionId, record: &Record) -> Result<(), Error> {
+        self.records
+            .write()
+            .unwrap()
+            .insert(id.clone(), record.clone());
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &SessionId) -> Result<(), Error> {
+        self.records.write().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> Result<(), Error> {
+        let now = SystemTime::now();
+
+        self.records
+            .write()
+            .unwrap()
+            .retain(|_, record| !record.is_expired(now));
+
+        Ok(())
+    }
+}
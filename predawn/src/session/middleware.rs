@@ -0,0 +1,298 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use http::{HeaderValue, header::SET_COOKIE};
+use predawn_core::{error::Error, request::Request, response::Response};
+
+use super::{FileStore, MemoryStore, Record, Session, SessionId, SessionStore, State};
+use crate::{
+    config::{
+        cookie::SameSite,
+        session::{SessionConfig, SessionStoreConfig},
+    },
+    extract::cookie::{Cookie, CookieJar},
+    handler::Handler,
+    middleware::Middleware,
+};
+
+/// Loads the [`Session`] of a request from its store and saves it after the handler ran.
+///
+/// A store that fails to save the session does not replace the response of the handler, the error
+/// is logged and the changes made to the session during the request are lost.
+#[derive(Clone)]
+pub struct SessionMiddleware {
+    store: Arc<dyn SessionStore>,
+    cookie_name: Arc<str>,
+    cookie_path: Arc<str>,
+    cookie_domain: Option<Arc<str>>,
+    secure: bool,
+    http_only: bool,
+    same_site: SameSite,
+    idle_timeout: Duration,
+    absolute_timeout: Option<Duration>,
+    cleanup_interval: Duration,
+}
+
+impl SessionMiddleware {
+    pub fn new<S: SessionStore>(store: S) -> Self {
+        Self::from_arc(Arc::new(store))
+    }
+
+    pub fn from_arc(store: Arc<dyn SessionStore>) -> Self {
+        Self::from_config(store, &SessionConfig::default())
+    }
+
+    pub fn from_config(store: Arc<dyn SessionStore>, config: &SessionConfig) -> Self {
+        Self {
+            store,
+            cookie_name: config.cookie_name.as_str().into(),
+            cookie_path: config.cookie_path.as_str().into(),
+            cookie_domain: config.cookie_domain.as_deref().map(Into::into),
+            secure: config.secure,
+            http_only: config.http_only,
+            same_site: config.same_site,
+            idle_timeout: config.idle_timeout,
+            absolute_timeout: config.absolute_timeout,
+            cleanup_interval: config.cleanup_interval,
+        }
+    }
+
+    pub fn cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    pub fn cookie_path(mut self, path: &str) -> Self {
+        self.cookie_path = path.into();
+        self
+    }
+
+    pub fn cookie_domain(mut self, domain: &str) -> Self {
+        self.cookie_domain = Some(domain.into());
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub fn absolute_timeout(mut self, timeout: Duration) -> Self {
+        self.absolute_timeout = Some(timeout);
+        self
+    }
+
+    pub fn cleanup_interval(mut self, interval: Duration) -> Self {
+        self.cleanup_interval = interval;
+        self
+    }
+}
+
+impl From<&SessionConfig> for SessionMiddleware {
+    fn from(config: &SessionConfig) -> Self {
+        let store: Arc<dyn SessionStore> = match &config.store {
+            SessionStoreConfig::Memory => Arc::new(MemoryStore::new()),
+            SessionStoreConfig::File { dir } => Arc::new(FileStore::new(dir)),
+        };
+
+        Self::from_config(store, config)
+    }
+}
+
+impl<H: Handler> Middleware<H> for SessionMiddleware {
+    type Output = SessionHandler<H>;
+
+    fn transform(self, input: H) -> Self::Output {
+        SessionHandler {
+            middleware: self,
+            last_cleanup: Mutex::new(Instant::now()),
+            inner: input,
+        }
+    }
+}
+
+pub struct SessionHandler<H> {
+    middleware: SessionMiddleware,
+    last_cleanup: Mutex<Instant>,
+    inner: H,
+}
+
+impl<H: Handler> Handler for SessionHandler<H> {
+    async fn call(&self, mut req: Request) -> Result<Response, Error> {
+        self.cleanup_if_due();
+
+        let now = SystemTime::now();
+        let store = &self.middleware.store;
+
+        let id = CookieJar::from_headers(&req.head.headers)
+            .get(&self.middleware.cookie_name)
+            .and_then(|cookie| SessionId::parse(cookie.value()));
+
+        let session = match id {
+            Some(id) => match store.load(&id).await? {
+                Some(record) if !record.is_expired(now) => Session::new(Some(id), record),
+                Some(_) => {
+                    store.delete(&id).await?;
+                    Session::new(None, Record::new(now))
+                }
+                None => Session::new(None, Record::new(now)),
+            },
+            None => Session::new(None, Record::new(now)),
+        };
+
+        req.head.extensions.insert(session.clone());
+
+        let mut result = self.inner.call(req).await;
+
+        let state = session.with_state(|state| {
+            std::mem::replace(
+                state,
+                State {
+                    id: None,
+                    record: Record::new(now),
+                    modified: false,
+                    renewed: false,
+                    destroyed: false,
+                },
+            )
+        });
+
+        let cookie = match self.save(state, now).await {
+            Ok(cookie) => cookie,
+            Err(e) => {
+                tracing::error!("failed to save the session: {e}");
+                None
+            }
+        };
+
+        if let Some(cookie) = cookie {
+            let response = match &mut result {
+                Ok(response) => response,
+                Err(e) => e.response_mut(),
+            };
+
+            if let Ok(value) = HeaderValue::try_from(cookie.encoded().to_string()) {
+                response.headers_mut().append(SET_COOKIE, value);
+            }
+        }
+
+        result
+    }
+}
+
+impl<H> SessionHandler<H> {
+    /// Persists the session and returns the cookie to send back, if the client needs a new one.
+    async fn save(&self, state: State, now: SystemTime) -> Result<Option<Cookie<'static>>, Error> {
+        let State {
+            id,
+            mut record,
+            modified,
+            renewed,
+            destroyed,
+        } = state;
+
+        let store = &self.middleware.store;
+
+        if destroyed || record.data.is_empty() {
+            return match id {
+                Some(id) => {
+                    store.delete(&id).await?;
+                    Ok(Some(self.removal_cookie()))
+                }
+                None => Ok(None),
+            };
+        }
+
+        let idle_timeout = self.middleware.idle_timeout;
+        let absolute_timeout = self.middleware.absolute_timeout;
+
+        match id {
+            Some(id) if !renewed => {
+                // avoid writing to the store on every request, the expiry may end up
+                // at most a tenth of the idle timeout earlier than requested
+                let stale = now
+                    .duration_since(record.accessed_at)
+                    .is_ok_and(|elapsed| elapsed >= idle_timeout / 10);
+
+                if modified || stale {
+                    record.touch(now, idle_timeout, absolute_timeout);
+                    store.save(&id, &record).await?;
+                }
+
+                Ok(None)
+            }
+            old => {
+                if let Some(old) = old {
+                    store.delete(&old).await?;
+                }
+
+                let id = SessionId::generate();
+
+                record.touch(now, idle_timeout, absolute_timeout);
+                store.save(&id, &record).await?;
+
+                Ok(Some(self.cookie(id.as_str().to_string())))
+            }
+        }
+    }
+
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        let middleware = &self.middleware;
+
+        let mut cookie = Cookie::build((middleware.cookie_name.to_string(), value))
+            .path(middleware.cookie_path.to_string())
+            .secure(middleware.secure)
+            .http_only(middleware.http_only)
+            .same_site(middleware.same_site.into())
+            .build();
+
+        if let Some(domain) = &middleware.cookie_domain {
+            cookie.set_domain(domain.to_string());
+        }
+
+        cookie
+    }
+
+    fn removal_cookie(&self) -> Cookie<'static> {
+        let mut cookie = self.cookie(String::new());
+        cookie.make_removal();
+        cookie
+    }
+
+    fn cleanup_if_due(&self) {
+        {
+            let mut last_cleanup = self.last_cleanup.lock().unwrap();
+
+            if last_cleanup.elapsed() < self.middleware.cleanup_interval {
+                return;
+            }
+
+            *last_cleanup = Instant::now();
+        }
+
+        let store = self.middleware.store.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = store.delete_expired().await {
+                tracing::error!("failed to delete expired sessions: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,308 @@
+mod file;
+mod memory;
+mod middleware;
+mod store;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use predawn_core::{
+    api_request::ApiRequestHead,
+    from_request::{FromRequestHead, OptionalFromRequestHead},
+    openapi::{Parameter, Schema},
+    request::Head,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use snafu::{OptionExt, ResultExt};
+
+pub use self::{
+    file::FileStore,
+    memory::MemoryStore,
+    middleware::{SessionHandler, SessionMiddleware},
+    store::SessionStore,
+};
+use crate::response_error::{SessionError, session_error};
+
+const ID_LEN: usize = 32;
+
+/// A random identifier of a session, encoded as lowercase hex.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionId(Box<str>);
+
+impl SessionId {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; ID_LEN];
+        getrandom::fill(&mut bytes).expect("failed to generate session id");
+
+        let mut id = String::with_capacity(ID_LEN * 2);
+
+        for byte in bytes {
+            id.push(char::from_digit((byte >> 4) as u32, 16).unwrap());
+            id.push(char::from_digit((byte & 0xf) as u32, 16).unwrap());
+        }
+
+        Self(id.into())
+    }
+
+    /// Parses an id sent by the client, returns `None` if it could not have been generated by [`SessionId::generate`].
+    pub fn parse(id: &str) -> Option<Self> {
+        let valid = id.len() == ID_LEN * 2
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+
+        valid.then(|| Self(id.into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the id is a credential, so do not leak it into logs
+        f.write_str("SessionId(..)")
+    }
+}
+
+/// The data of a session as persisted by a [`SessionStore`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub data: HashMap<String, serde_json::Value>,
+    pub created_at: SystemTime,
+    pub accessed_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+impl Record {
+    fn new(now: SystemTime) -> Self {
+        Self {
+            data: Default::default(),
+            created_at: now,
+            accessed_at: now,
+            expires_at: now,
+        }
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at <= now
+    }
+
+    fn touch(
+        &mut self,
+        now: SystemTime,
+        idle_timeout: Duration,
+        absolute_timeout: Option<Duration>,
+    ) {
+        self.accessed_at = now;
+        self.expires_at = now + idle_timeout;
+
+        if let Some(timeout) = absolute_timeout {
+            self.expires_at = self.expires_at.min(self.created_at + timeout);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    id: Option<SessionId>,
+    record: Record,
+    modified: bool,
+    renewed: bool,
+    destroyed: bool,
+}
+
+/// A server-side session, extracted from requests that pass through [`SessionMiddleware`].
+///
+/// Values are stored as JSON, changes are written to the store after the handler returns.
+#[derive(Clone)]
+pub struct Session {
+    state: Arc<Mutex<State>>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session").finish_non_exhaustive()
+    }
+}
+
+impl Session {
+    fn new(id: Option<SessionId>, record: Record) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                id,
+                record,
+                modified: false,
+                renewed: false,
+                destroyed: false,
+            })),
+        }
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        f(&mut self.state.lock().unwrap())
+    }
+
+    /// The id of the session, `None` if the session has not been saved yet.
+    pub fn id(&self) -> Option<SessionId> {
+        self.with_state(|state| state.id.clone())
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SessionError> {
+        self.with_state(|state| {
+            state
+                .record
+                .data
+                .get(key)
+                .map(|value| {
+                    T::deserialize(value).context(session_error::DeserializeValueSnafu { key })
+                })
+                .transpose()
+        })
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), SessionError> {
+        let value =
+            serde_json::to_value(value).context(session_error::SerializeValueSnafu { key })?;
+
+        self.with_state(|state| {
+            if state.record.data.get(key) != Some(&value) {
+                state.record.data.insert(key.to_string(), value);
+                state.modified = true;
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn remove<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SessionError> {
+        let value = self.with_state(|state| {
+            let value = state.record.data.remove(key);
+            state.modified |= value.is_some();
+            value
+        });
+
+        value
+            .map(|value| {
+                T::deserialize(value).context(session_error::DeserializeValueSnafu { key })
+            })
+            .transpose()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.with_state(|state| state.record.data.contains_key(key))
+    }
+
+    pub fn clear(&self) {
+        self.with_state(|state| {
+            state.modified |= !state.record.data.is_empty();
+            state.record.data.clear();
+        });
+    }
+
+    /// Moves the data to a new session id, the old one is invalidated.
+    ///
+    /// Call this whenever the privilege level changes, e.g. on login, to prevent session fixation.
+    pub fn renew(&self) {
+        self.with_state(|state| state.renewed = true);
+    }
+
+    /// Removes the session from the store and tells the client to delete the cookie.
+    pub fn destroy(&self) {
+        self.with_state(|state| {
+            state.record.data.clear();
+            state.destroyed = true;
+        });
+    }
+
+    /// A snapshot of all values in the session.
+    pub fn entries(&self) -> BTreeMap<String, serde_json::Value> {
+        self.with_state(|state| {
+            state
+                .record
+                .data
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+    }
+}
+
+impl FromRequestHead for Session {
+    type Error = SessionError;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        head.extensions
+            .get::<Self>()
+            .cloned()
+            .context(session_error::NotFoundSessionSnafu)
+    }
+}
+
+impl OptionalFromRequestHead for Session {
+    type Error = Infallible;
+
+    async fn from_request_head(head: &mut Head) -> Result<Option<Self>, Self::Error> {
+        Ok(head.extensions.get::<Self>().cloned())
+    }
+}
+
+impl ApiRequestHead for Session {
+    fn parameters(_: &mut BTreeMap<String, Schema>, _: &mut Vec<String>) -> Option<Vec<Parameter>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{Record, Session, SessionId};
+
+    #[test]
+    fn test_session_id() {
+        let id = SessionId::generate();
+
+        assert_eq!(id.as_str().len(), 64);
+        assert_eq!(SessionId::parse(id.as_str()), Some(id.clone()));
+        assert_ne!(SessionId::generate(), id);
+
+        assert_eq!(SessionId::parse("../../etc/passwd"), None);
+        assert_eq!(SessionId::parse(&id.as_str().to_uppercase()), None);
+        assert_eq!(SessionId::parse(&id.as_str()[1..]), None);
+    }
+
+    #[test]
+    fn test_record_touch() {
+        let created = SystemTime::UNIX_EPOCH;
+        let mut record = Record::new(created);
+
+        let now = created + Duration::from_secs(50);
+        record.touch(now, Duration::from_secs(30), None);
+        assert_eq!(record.expires_at, now + Duration::from_secs(30));
+
+        record.touch(now, Duration::from_secs(30), Some(Duration::from_secs(60)));
+        assert_eq!(record.expires_at, created + Duration::from_secs(60));
+        assert!(!record.is_expired(now));
+        assert!(record.is_expired(created + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_session_values() {
+        let session = Session::new(None, Record::new(SystemTime::now()));
+
+        session.insert("user", 42u64).unwrap();
+        assert_eq!(session.get::<u64>("user").unwrap(), Some(42));
+        assert!(session.get::<String>("user").is_err());
+
+        assert_eq!(session.remove::<u64>("user").unwrap(), Some(42));
+        assert_eq!(session.get::<u64>("user").unwrap(), None);
+        assert!(session.with_state(|state| state.modified));
+    }
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use predawn_core::error::Error;
+
+use super::{Record, SessionId};
+
+#[async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// Returns the record of the session, expired records may be returned.
+    async fn load(&self, id: &SessionId) -> Result<Option<Record>, Error>;
+
+    async fn save(&self, id: &SessionId, record: &Record) -> Result<(), Error>;
+
+    async fn delete(&self, id: &SessionId) -> Result<(), Error>;
+
+    /// Removes all records whose `expires_at` has passed.
+    async fn delete_expired(&self) -> Result<(), Error>;
+}
@@ -1,15 +1,22 @@
-use std::net::SocketAddr;
+use std::{env, fs, net::SocketAddr, path::Path};
 
+use predawn_core::openapi::OpenAPI;
 use reqwest::{Client, RequestBuilder, redirect::Policy};
 use rudi::Context;
+use serde_json::Value;
 use tokio::net::TcpListener;
 
 use crate::{
     app::{Hooks, create_app},
+    config::openapi::OpenAPIConfig,
     environment::Environment,
+    openapi::diff_values,
     server::Server,
 };
 
+/// Set to overwrite the baseline in [`TestClient::assert_openapi_compatible`].
+pub const UPDATE_OPENAPI_ENV: &str = "PREDAWN_UPDATE_OPENAPI";
+
 macro_rules! impl_request_methods {
     ($($name:ident),+ $(,)?) => {
         $(
@@ -23,7 +30,6 @@ macro_rules! impl_request_methods {
 pub struct TestClient {
     client: Client,
     addr: SocketAddr,
-    #[allow(dead_code)]
     cx: Context,
 }
 
@@ -46,4 +52,61 @@ impl TestClient {
 
         Self { client, addr, cx }
     }
+
+    /// A [`Client`](crate::client::Client) of the app, for the clients generated by `#[controller(client)]`.
+    pub fn typed_client(&self) -> crate::client::Client {
+        crate::client::Client::with_client(self.client.clone(), format!("http://{}", self.addr))
+    }
+}
+
+/// Checks of the OpenAPI document of the app.
+impl TestClient {
+    /// The OpenAPI document of the app, in the version set by `openapi.version`.
+    pub fn openapi(&self) -> Value {
+        let version = self.cx.get_single::<OpenAPIConfig>().version;
+        version.render(self.cx.get_single::<OpenAPI>())
+    }
+
+    /// Panics if the OpenAPI document of the app has breaking changes compared to `baseline`, a JSON
+    /// document like the one written by [`export`](crate::openapi::export).
+    ///
+    /// The baseline is written if the [`UPDATE_OPENAPI_ENV`] environment variable is set, without it a
+    /// missing baseline fails, so that a wrong path does not pass silently.
+    pub fn assert_openapi_compatible<P: AsRef<Path>>(&self, baseline: P) {
+        let baseline = baseline.as_ref();
+        let current = self.openapi();
+
+        if env::var_os(UPDATE_OPENAPI_ENV).is_some() {
+            let mut json = serde_json::to_string_pretty(&current).unwrap();
+            json.push('\n');
+
+            fs::write(baseline, json)
+                .unwrap_or_else(|e| panic!("failed to write `{}`: {e}", baseline.display()));
+
+            return;
+        }
+
+        let old = fs::read(baseline).unwrap_or_else(|e| {
+            panic!(
+                "failed to read `{}`: {e}, set `{UPDATE_OPENAPI_ENV}` to write it",
+                baseline.display()
+            )
+        });
+
+        let old = serde_json::from_slice::<Value>(&old)
+            .unwrap_or_else(|e| panic!("failed to parse `{}` as JSON: {e}", baseline.display()));
+
+        let breaking_changes = diff_values(&old, &current)
+            .into_iter()
+            .filter(|change| change.breaking)
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>();
+
+        assert!(
+            breaking_changes.is_empty(),
+            "the OpenAPI document has breaking changes compared to `{}`, set `{UPDATE_OPENAPI_ENV}` to accept them:\n{}",
+            baseline.display(),
+            breaking_changes.join("\n")
+        );
+    }
 }
@@ -2,13 +2,19 @@ use std::collections::BTreeMap;
 
 use predawn_core::openapi::{ParameterData, Schema};
 
-use crate::openapi;
+use crate::{openapi, validate::ValidationErrors};
 
 pub trait ToParameters {
     fn parameters(
         schemas: &mut BTreeMap<String, Schema>,
         schemas_in_progress: &mut Vec<String>,
     ) -> Vec<ParameterData>;
+
+    /// Checks the constraints of the parameters, used by `Valid<Query<T>>` and the other
+    /// extractors of parameters.
+    fn validate_parameters(&self, path: &str, errors: &mut ValidationErrors) {
+        let _ = (path, errors);
+    }
 }
 
 pub trait Tag {
@@ -22,3 +28,10 @@ pub trait SecurityScheme {
 
     fn create() -> openapi::SecurityScheme;
 }
+
+/// A named example of a request or response body.
+pub trait Example {
+    const NAME: &'static str;
+
+    fn create() -> openapi::Example;
+}
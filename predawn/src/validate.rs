@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+use predawn_core::{
+    api_request::{ApiRequest, ApiRequestHead},
+    body::RequestBody,
+    from_request::{FromRequest, FromRequestHead},
+    impl_deref,
+    openapi::{self, Parameter, Schema},
+    request::Head,
+};
+pub use predawn_schema::validate::*;
+use snafu::ResultExt;
+
+#[cfg(feature = "cookie")]
+use crate::extract::cookie::CookieParams;
+use crate::{
+    ToParameters,
+    extract::{Path, Query},
+    payload::{Form, Json},
+    response_error::{ValidError, valid_error},
+};
+
+/// Checks the value extracted by `E` with its [`Validate`] impl.
+///
+/// Responds `422 Unprocessable Entity` with every failing field path when validation fails.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Valid<E>(pub E);
+
+impl_deref!(Valid);
+
+impl<E> Valid<E> {
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+impl<E> FromRequestHead for Valid<E>
+where
+    E: FromRequestHead + Validate,
+{
+    type Error = ValidError<E::Error>;
+
+    async fn from_request_head(head: &mut Head) -> Result<Self, Self::Error> {
+        let extracted = E::from_request_head(head)
+            .await
+            .context(valid_error::ExtractSnafu)?;
+
+        extracted.validate().context(valid_error::InvalidSnafu)?;
+
+        Ok(Valid(extracted))
+    }
+}
+
+impl<E> FromRequest for Valid<E>
+where
+    E: FromRequest + Validate,
+{
+    type Error = ValidError<E::Error>;
+
+    const BODY_LIMIT: Option<usize> = E::BODY_LIMIT;
+
+    async fn from_request(head: &mut Head, body: RequestBody) -> Result<Self, Self::Error> {
+        let extracted = E::from_request(head, body)
+            .await
+            .context(valid_error::ExtractSnafu)?;
+
+        extracted.validate().context(valid_error::InvalidSnafu)?;
+
+        Ok(Valid(extracted))
+    }
+}
+
+impl<E: ApiRequestHead> ApiRequestHead for Valid<E> {
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<Parameter>> {
+        E::parameters(schemas, schemas_in_progress)
+    }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        E::extensions(schemas, schemas_in_progress)
+    }
+}
+
+impl<E: ApiRequest> ApiRequest for Valid<E> {
+    fn parameters(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<Vec<Parameter>> {
+        E::parameters(schemas, schemas_in_progress)
+    }
+
+    fn request_body(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> Option<openapi::RequestBody> {
+        E::request_body(schemas, schemas_in_progress)
+    }
+
+    fn extensions(
+        schemas: &mut BTreeMap<String, Schema>,
+        schemas_in_progress: &mut Vec<String>,
+    ) -> IndexMap<String, serde_json::Value> {
+        E::extensions(schemas, schemas_in_progress)
+    }
+}
+
+macro_rules! forward_validate {
+    ($($ty:ident),+ $(,)?) => {
+        $(
+            impl<T: Validate> Validate for $ty<T> {
+                fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+                    self.0.validate_at(path, errors)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! forward_validate_parameters {
+    ($($ty:ident),+ $(,)?) => {
+        $(
+            impl<T: ToParameters> Validate for $ty<T> {
+                fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+                    self.0.validate_parameters(path, errors)
+                }
+            }
+        )+
+    };
+}
+
+forward_validate![Json, Form];
+
+forward_validate_parameters![Query, Path];
+
+#[cfg(feature = "cookie")]
+forward_validate_parameters![CookieParams];
+
+#[cfg(all(test, feature = "macro"))]
+mod tests {
+    use http::StatusCode;
+    use predawn_core::{
+        error::Error,
+        from_request::FromRequest,
+        openapi::{ReferenceOr, SchemaKind, Type},
+        request::Request,
+    };
+
+    use super::{Valid, Validate, ValidationErrors};
+    use crate::{
+        ToParameters, ToSchema,
+        extract::{Query, multipart::Multipart},
+        handler::handler_fn,
+        payload::Json,
+        server::{Server, bind},
+    };
+
+    #[derive(ToSchema)]
+    struct Address {
+        #[schema(pattern = "^[0-9]{5}$")]
+        zip: String,
+    }
+
+    #[derive(ToSchema)]
+    struct User {
+        #[schema(min_length = 2, max_length = 8)]
+        name: String,
+        #[schema(min = 18, max = 150)]
+        age: Option<u8>,
+        #[schema(format = "email")]
+        email: String,
+        #[schema(nested)]
+        addresses: Vec<Address>,
+    }
+
+    #[test]
+    fn test_validate() {
+        let user = User {
+            name: "Alice".into(),
+            age: None,
+            email: "alice@example.com".into(),
+            addresses: vec![Address {
+                zip: "12345".into(),
+            }],
+        };
+        assert!(user.validate().is_ok());
+
+        let user = User {
+            name: "A".into(),
+            age: Some(17),
+            email: "alice".into(),
+            addresses: vec![
+                Address {
+                    zip: "12345".into(),
+                },
+                Address { zip: "123".into() },
+            ],
+        };
+
+        let errors = user.validate().unwrap_err();
+        let errors = errors
+            .iter()
+            .map(|error| (error.path.as_str(), error.keyword))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            [
+                ("name", "minLength"),
+                ("age", "minimum"),
+                ("email", "format"),
+                ("addresses[1].zip", "pattern"),
+            ]
+        );
+    }
+
+    #[derive(ToParameters)]
+    struct Pagination {
+        #[schema(min = 1)]
+        page: u32,
+        #[schema(max_length = 3)]
+        sort: Option<String>,
+    }
+
+    #[derive(ToSchema)]
+    struct Page<T: ToSchema> {
+        #[schema(nested)]
+        items: Vec<T>,
+    }
+
+    #[derive(ToSchema)]
+    #[schema(custom_validate)]
+    struct Custom {
+        value: u32,
+    }
+
+    impl Validate for Custom {
+        fn validate_at(&self, path: &str, errors: &mut ValidationErrors) {
+            if self.value == 0 {
+                errors.push(path.into(), "custom", "must not be zero".into());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_parameters_and_generics() {
+        let query = Query(Pagination {
+            page: 0,
+            sort: Some("name".into()),
+        });
+
+        let errors = query.validate().unwrap_err();
+        let paths = errors.iter().map(|e| e.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["page", "sort"]);
+
+        let page = Page {
+            items: vec![Custom { value: 1 }, Custom { value: 0 }],
+        };
+
+        let errors = page.validate().unwrap_err();
+        assert_eq!(errors.iter().next().unwrap().path, "items[1]");
+    }
+
+    #[derive(ToSchema, serde::Deserialize)]
+    struct Plain {
+        value: u32,
+    }
+
+    #[derive(ToSchema)]
+    enum Color {
+        Red,
+    }
+
+    #[derive(ToSchema, ToParameters)]
+    struct Filter {
+        #[schema(max_length = 3)]
+        q: String,
+    }
+
+    #[derive(ToParameters)]
+    struct Search {
+        #[schema(flatten)]
+        filter: Filter,
+    }
+
+    fn assert_from_request<E: FromRequest>() {}
+
+    #[test]
+    fn test_validate_unconstrained_and_parameters() {
+        assert_from_request::<Valid<Json<Plain>>>();
+
+        let plain = Json(Plain { value: 0 });
+        assert!(plain.validate().is_ok());
+        assert_eq!(plain.value, 0);
+        assert!(Color::Red.validate().is_ok());
+
+        let filter = || Filter { q: "long".into() };
+
+        assert!(filter().validate().is_err());
+        assert!(Query(filter()).validate().is_err());
+
+        let errors = Query(Search { filter: filter() }).validate().unwrap_err();
+        assert_eq!(errors.iter().next().unwrap().path, "q");
+    }
+
+    #[derive(ToSchema, Multipart)]
+    struct Signup {
+        #[schema(min_length = 3)]
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_validate_multipart() {
+        let listener = bind(([127, 0, 0, 1], 0).into(), false).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // validated only when extracted through `Valid`
+        let handler = handler_fn(|req: Request| async move {
+            let (mut head, body) = req.split();
+
+            let signup = if head.uri.path() == "/valid" {
+                Valid::<Signup>::from_request(&mut head, body).await?.0
+            } else {
+                Signup::from_request(&mut head, body).await?
+            };
+
+            Ok::<_, Error>(signup.name)
+        });
+
+        tokio::spawn(Server::new(listener).run(handler));
+
+        let signup = |path: &str, name: &str| {
+            let body = format!(
+                "--boundary\r\ncontent-disposition: form-data; name=\"name\"\r\n\r\n{name}\r\n--boundary--\r\n"
+            );
+
+            reqwest::Client::new()
+                .post(format!("http://{addr}{path}"))
+                .header("content-type", "multipart/form-data; boundary=boundary")
+                .body(body)
+                .send()
+        };
+
+        assert_eq!(
+            signup("/valid", "alice").await.unwrap().status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            signup("/valid", "al").await.unwrap().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(signup("/", "al").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_schema_keywords() {
+        let schema = User::schema(&mut Default::default(), &mut Default::default());
+
+        let SchemaKind::Type(Type::Object(obj)) = schema.schema_kind else {
+            panic!("must be an object");
+        };
+
+        let property = |name: &str| match &obj.properties[name] {
+            ReferenceOr::Item(schema) => schema.schema_kind.clone(),
+            ReferenceOr::Reference { .. } => panic!("constrained field must be inlined"),
+        };
+
+        let SchemaKind::Type(Type::String(name)) = property("name") else {
+            panic!("must be a string");
+        };
+        assert_eq!((name.min_length, name.max_length), (Some(2), Some(8)));
+
+        let SchemaKind::Type(Type::Integer(age)) = property("age") else {
+            panic!("must be an integer");
+        };
+        assert_eq!((age.minimum, age.maximum), (Some(18), Some(150)));
+
+        let SchemaKind::Type(Type::String(email)) = property("email") else {
+            panic!("must be a string");
+        };
+        assert_eq!(
+            serde_json::to_value(&email.format).unwrap(),
+            serde_json::json!("email")
+        );
+    }
+}
@@ -0,0 +1,441 @@
+#![cfg(feature = "macro")]
+
+use std::collections::BTreeMap;
+
+use predawn::{ToParameters, ToSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Returns the schema of `T` and the schemas it registered in the components.
+fn schema_of<T: ToSchema>() -> (Value, Value) {
+    let mut schemas = BTreeMap::new();
+    let schema = T::schema(&mut schemas, &mut Vec::new());
+
+    (
+        serde_json::to_value(schema).unwrap(),
+        serde_json::to_value(schemas).unwrap(),
+    )
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind")]
+enum Internal {
+    Circle(Circle),
+    Square { side: f64 },
+    Empty,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Circle(Circle),
+    Square { side: f64 },
+    Empty,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+enum Untagged {
+    Circle(Circle),
+    Square { side: f64 },
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum Renamed {
+    BigCircle(Circle),
+    SmallSquare { side_length: f64 },
+    NoShape,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum RenamedInternal {
+    BigCircle(Circle),
+    #[serde(rename = "box")]
+    SmallSquare {
+        side: f64,
+    },
+}
+
+const F64: &str = "#/components/schemas/f64";
+const CIRCLE: &str = "#/components/schemas/to_schema.Circle";
+
+#[test]
+fn test_internally_tagged() {
+    let (schema, schemas) = schema_of::<Internal>();
+
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Internal",
+            "oneOf": [
+                { "$ref": "#/components/schemas/to_schema.Internal.Circle" },
+                { "$ref": "#/components/schemas/to_schema.Internal.Square" },
+                { "$ref": "#/components/schemas/to_schema.Internal.Empty" }
+            ],
+            "discriminator": {
+                "propertyName": "kind",
+                "mapping": {
+                    "Circle": "#/components/schemas/to_schema.Internal.Circle",
+                    "Square": "#/components/schemas/to_schema.Internal.Square",
+                    "Empty": "#/components/schemas/to_schema.Internal.Empty"
+                }
+            }
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.Internal.Circle"],
+        json!({
+            "allOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": { "type": "string", "enum": ["Circle"] } },
+                    "required": ["kind"]
+                },
+                { "$ref": CIRCLE }
+            ]
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.Internal.Square"],
+        json!({
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["Square"] },
+                "side": { "$ref": F64 }
+            },
+            "required": ["kind", "side"]
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.Internal.Empty"],
+        json!({
+            "type": "object",
+            "properties": { "kind": { "type": "string", "enum": ["Empty"] } },
+            "required": ["kind"]
+        })
+    );
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    let (schema, schemas) = schema_of::<Adjacent>();
+
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Adjacent",
+            "oneOf": [
+                { "$ref": "#/components/schemas/to_schema.Adjacent.Circle" },
+                { "$ref": "#/components/schemas/to_schema.Adjacent.Square" },
+                { "$ref": "#/components/schemas/to_schema.Adjacent.Empty" }
+            ],
+            "discriminator": {
+                "propertyName": "t",
+                "mapping": {
+                    "Circle": "#/components/schemas/to_schema.Adjacent.Circle",
+                    "Square": "#/components/schemas/to_schema.Adjacent.Square",
+                    "Empty": "#/components/schemas/to_schema.Adjacent.Empty"
+                }
+            }
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.Adjacent.Circle"],
+        json!({
+            "type": "object",
+            "properties": {
+                "t": { "type": "string", "enum": ["Circle"] },
+                "c": { "$ref": CIRCLE }
+            },
+            "required": ["t", "c"]
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.Adjacent.Square"],
+        json!({
+            "type": "object",
+            "properties": {
+                "t": { "type": "string", "enum": ["Square"] },
+                "c": {
+                    "type": "object",
+                    "properties": { "side": { "$ref": F64 } },
+                    "required": ["side"]
+                }
+            },
+            "required": ["t", "c"]
+        })
+    );
+
+    // a unit variant has no content
+    assert_eq!(
+        schemas["to_schema.Adjacent.Empty"],
+        json!({
+            "type": "object",
+            "properties": { "t": { "type": "string", "enum": ["Empty"] } },
+            "required": ["t"]
+        })
+    );
+}
+
+#[test]
+fn test_untagged() {
+    let (schema, schemas) = schema_of::<Untagged>();
+
+    // the variants are inlined, and any of them may match
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Untagged",
+            "anyOf": [
+                { "allOf": [{ "$ref": CIRCLE }] },
+                {
+                    "type": "object",
+                    "properties": { "side": { "$ref": F64 } },
+                    "required": ["side"]
+                }
+            ]
+        })
+    );
+
+    assert!(schemas.get("to_schema.Untagged.Circle").is_none());
+}
+
+#[test]
+fn test_rename_all() {
+    let (schema, _) = schema_of::<Renamed>();
+
+    // `rename_all` on an enum renames its variants, not the fields of its struct variants
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Renamed",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "big_circle": { "$ref": CIRCLE } },
+                    "required": ["big_circle"]
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "small_square": {
+                            "type": "object",
+                            "properties": { "side_length": { "$ref": F64 } },
+                            "required": ["side_length"]
+                        }
+                    },
+                    "required": ["small_square"]
+                },
+                { "type": "string", "enum": ["no_shape"] }
+            ]
+        })
+    );
+
+    let (schema, schemas) = schema_of::<RenamedInternal>();
+
+    assert_eq!(
+        schema["discriminator"],
+        json!({
+            "propertyName": "kind",
+            "mapping": {
+                "big-circle": "#/components/schemas/to_schema.RenamedInternal.BigCircle",
+                "box": "#/components/schemas/to_schema.RenamedInternal.SmallSquare"
+            }
+        })
+    );
+
+    assert_eq!(
+        schemas["to_schema.RenamedInternal.BigCircle"]["allOf"][0]["properties"]["kind"],
+        json!({ "type": "string", "enum": ["big-circle"] })
+    );
+
+    assert_eq!(
+        schemas["to_schema.RenamedInternal.SmallSquare"]["properties"]["kind"],
+        json!({ "type": "string", "enum": ["box"] })
+    );
+
+    // the mapping is keyed by the tags serde writes
+    for value in [
+        RenamedInternal::BigCircle(Circle { radius: 1.0 }),
+        RenamedInternal::SmallSquare { side: 1.0 },
+    ] {
+        let value = serde_json::to_value(value).unwrap();
+        let tag = value["kind"].as_str().unwrap();
+
+        assert!(schema["discriminator"]["mapping"].get(tag).is_some());
+    }
+}
+
+mod hex {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:x}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        u32::from_str_radix(&s, 16).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, ToParameters)]
+#[serde(deny_unknown_fields)]
+struct Fields {
+    #[serde(skip)]
+    skipped: u32,
+    #[serde(skip_serializing)]
+    write_only: u32,
+    #[serde(skip_deserializing)]
+    read_only: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maybe: Option<u32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    name: String,
+    #[serde(with = "hex")]
+    #[schema(value_type = String)]
+    hex: u32,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, ToParameters)]
+#[serde(default)]
+struct Defaulted {
+    a: u32,
+    b: String,
+}
+
+impl Default for Defaulted {
+    fn default() -> Self {
+        Self {
+            a: 1,
+            b: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, ToParameters)]
+#[serde(transparent)]
+struct Transparent {
+    #[schema(flatten)]
+    inner: Defaulted,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+struct Meters(f64);
+
+const U32: &str = "#/components/schemas/u32";
+const STRING: &str = "#/components/schemas/alloc.string.String";
+
+#[test]
+fn test_field_attributes() {
+    let (schema, _) = schema_of::<Fields>();
+
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Fields",
+            "type": "object",
+            "properties": {
+                "write_only": { "allOf": [{ "$ref": U32 }], "writeOnly": true },
+                "read_only": { "allOf": [{ "$ref": U32 }], "readOnly": true },
+                "maybe": { "$ref": "#/components/schemas/core.option.Option<u32>" },
+                "name": { "$ref": STRING },
+                "hex": { "$ref": STRING }
+            },
+            // fields skipped by a predicate may be missing
+            "required": ["write_only", "read_only", "hex"],
+            "additionalProperties": false
+        })
+    );
+
+    // a value in the shape of the schema is accepted
+    let fields: Fields = serde_json::from_value(json!({
+        "write_only": 1,
+        "maybe": 2,
+        "name": "n",
+        "hex": "ff"
+    }))
+    .unwrap();
+
+    assert_eq!(fields.skipped, 0);
+    assert_eq!(fields.write_only, 1);
+    assert_eq!(fields.hex, 255);
+}
+
+#[test]
+fn test_container_attributes() {
+    let (schema, _) = schema_of::<Defaulted>();
+
+    // every field may be missing
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Defaulted",
+            "type": "object",
+            "properties": {
+                "a": { "$ref": U32 },
+                "b": { "$ref": STRING }
+            }
+        })
+    );
+
+    let (schema, _) = schema_of::<Transparent>();
+
+    assert_eq!(
+        schema,
+        json!({
+            "title": "Transparent",
+            "type": "object",
+            "properties": {
+                "a": { "$ref": U32 },
+                "b": { "$ref": STRING }
+            }
+        })
+    );
+
+    let (schema, _) = schema_of::<Meters>();
+
+    assert_eq!(
+        schema,
+        json!({ "title": "Meters", "type": "number", "format": "double" })
+    );
+}
+
+fn parameters_of<T: ToParameters>() -> Value {
+    serde_json::to_value(T::parameters(&mut BTreeMap::new(), &mut Vec::new())).unwrap()
+}
+
+#[test]
+fn test_parameter_attributes() {
+    // parameters are only deserialized, so `skip_serializing_if` does not make them optional
+    assert_eq!(
+        parameters_of::<Fields>(),
+        json!([
+            { "name": "write_only", "required": true, "schema": { "$ref": U32 } },
+            { "name": "maybe", "schema": { "$ref": "#/components/schemas/core.option.Option<u32>" } },
+            { "name": "name", "required": true, "schema": { "$ref": STRING } },
+            { "name": "hex", "required": true, "schema": { "$ref": STRING } }
+        ])
+    );
+
+    let defaulted = json!([
+        { "name": "a", "schema": { "$ref": U32 } },
+        { "name": "b", "schema": { "$ref": STRING } }
+    ]);
+
+    assert_eq!(parameters_of::<Defaulted>(), defaulted);
+    assert_eq!(parameters_of::<Transparent>(), defaulted);
+}
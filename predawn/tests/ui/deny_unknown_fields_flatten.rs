@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use predawn::ToSchema;
+use serde::Deserialize;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+struct Body {
+    value: u32,
+    #[serde(flatten)]
+    rest: HashMap<String, u32>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use predawn::ToSchema;
+use serde::Serialize;
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "type")]
+enum Message {
+    Text(String),
+    Count(u32),
+    Pair((u8, u8)),
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use predawn::{ToParameters, ToSchema};
+use serde::Deserialize;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(transparent)]
+struct Skipped {
+    #[serde(skip)]
+    value: u32,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(transparent, tag = "type")]
+struct Tagged {
+    value: u32,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(transparent)]
+enum Enum {
+    A(u32),
+}
+
+#[derive(Deserialize, ToParameters)]
+#[serde(transparent)]
+struct Two {
+    a: u32,
+    b: u32,
+}
+
+fn main() {}
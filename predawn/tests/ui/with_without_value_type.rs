@@ -0,0 +1,20 @@
+use predawn::{ToParameters, ToSchema};
+use serde::{Deserialize, Deserializer};
+
+fn hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    u32::deserialize(deserializer)
+}
+
+#[derive(Deserialize, ToSchema)]
+struct Body {
+    #[serde(deserialize_with = "hex")]
+    value: u32,
+}
+
+#[derive(Deserialize, ToParameters)]
+struct Query {
+    #[serde(deserialize_with = "hex")]
+    value: u32,
+}
+
+fn main() {}